@@ -0,0 +1,149 @@
+use ansi_term::Style;
+use unicode_width::UnicodeWidthChar;
+
+/// Columns taken by text in terminal. Wide characters like CJK take two columns,
+/// and ANSI escape sequences take none.
+pub fn width(text: &str) -> usize {
+    let mut chars = text.chars();
+    let mut width = 0;
+    while let Some(c) = chars.next() {
+        if c == '\u{001b}' {
+            // Skip until the final byte of a CSI sequence.
+            if chars.next() == Some('[') {
+                chars.find(|c| ('@'..='~').contains(c));
+            }
+        } else {
+            width += c.width().unwrap_or_default();
+        }
+    }
+    width
+}
+
+/// Render a progress bar like `████░░░░  50% 5/10`.
+pub fn progress(current: usize, total: usize, size: usize) -> String {
+    let current = current.min(total);
+    let ratio = if total == 0 {
+        1.0
+    } else {
+        current as f64 / total as f64
+    };
+    let filled = (ratio * size as f64).round() as usize;
+    format!(
+        "{}{} {:>3}% {}/{}",
+        "█".repeat(filled),
+        "░".repeat(size - filled),
+        (ratio * 100.0).floor() as usize,
+        current,
+        total
+    )
+}
+
+/// Lay out rows as aligned columns. Headers are printed in bold if `styled` is set.
+pub fn table(headers: &[String], rows: &[Vec<String>], styled: bool) -> String {
+    let columns = rows
+        .iter()
+        .map(|row| row.len())
+        .chain(std::iter::once(headers.len()))
+        .max()
+        .unwrap_or_default();
+    let widths = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row.as_slice())
+                .chain(std::iter::once(headers))
+                .filter_map(|row| row.get(i))
+                .map(|cell| width(cell))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let line = |row: &[String], style: Style| {
+        let cells = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let padding = " ".repeat(widths[i] - width(cell));
+                format!("{}{}", style.paint(cell), padding)
+            })
+            .collect::<Vec<_>>();
+        cells.join("  ").trim_end().to_string()
+    };
+
+    let style = if styled {
+        Style::new().bold()
+    } else {
+        Style::new()
+    };
+    let header = Some(headers)
+        .filter(|headers| !headers.is_empty())
+        .map(|headers| line(headers, style));
+    header
+        .into_iter()
+        .chain(rows.iter().map(|row| line(row, Style::new())))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(row: &[&str]) -> Vec<String> {
+        row.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    #[test]
+    fn align_columns() {
+        let headers = texts(&["name", "uid"]);
+        let rows = vec![texts(&["kumiko", "1"]), texts(&["reina", "10"])];
+        assert_eq!(
+            table(&headers, &rows, false),
+            "name    uid\nkumiko  1\nreina   10"
+        );
+    }
+
+    #[test]
+    fn without_headers() {
+        let rows = vec![texts(&["a", "b", "c"]), texts(&["dd"])];
+        assert_eq!(table(&[], &rows, false), "a   b  c\ndd");
+    }
+
+    #[test]
+    fn wide_characters() {
+        let headers = texts(&["名前", "id"]);
+        let rows = vec![
+            texts(&["久美子", "1"]),
+            texts(&["\u{001b}[31mreina\u{001b}[0m", "2"]),
+        ];
+        assert_eq!(
+            table(&headers, &rows, false),
+            "名前    id\n久美子  1\n\u{001b}[31mreina\u{001b}[0m   2"
+        );
+    }
+
+    #[test]
+    fn display_width() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("黄前久美子"), 10);
+        assert_eq!(width("\u{001b}[1;31mok\u{001b}[0m"), 2);
+    }
+
+    #[test]
+    fn progress_bar() {
+        assert_eq!(progress(5, 10, 4), "██░░  50% 5/10");
+        assert_eq!(progress(12, 10, 4), "████ 100% 10/10");
+        assert_eq!(progress(0, 0, 2), "██ 100% 0/0");
+        assert_eq!(progress(1, 3, 3), "█░░  33% 1/3");
+    }
+
+    #[test]
+    fn bold_headers() {
+        let headers = texts(&["id"]);
+        let rows = vec![texts(&["1"])];
+        assert_eq!(
+            table(&headers, &rows, true),
+            format!("{}\n1", Style::new().bold().paint("id"))
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::shell::theme::Theme;
+use ansi_term::Style;
+use serde_json::Value;
+
+/// Colors of JSON tokens, which are picked from a theme of syntax highlighting.
+/// Keys look like switches, and numbers, booleans and `null` look like variables.
+struct Palette {
+    key: Style,
+    string: Style,
+    literal: Style,
+}
+
+impl From<&Theme> for Palette {
+    fn from(theme: &Theme) -> Self {
+        Palette {
+            key: theme.switch,
+            string: theme.string,
+            literal: theme.variable,
+        }
+    }
+}
+
+/// Check if a `Content-Type` is JSON, like `application/json` or `application/problem+json`.
+pub fn is_json_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    mime.eq_ignore_ascii_case("application/json") || mime.to_ascii_lowercase().ends_with("+json")
+}
+
+/// Format a value with indentation of 2 spaces. Lines are separated by LF.
+/// Tokens are colored if a theme is given.
+pub fn pretty(value: &Value, theme: Option<&Theme>) -> String {
+    let palette = theme.map(Palette::from);
+    let mut output = String::new();
+    write_value(&mut output, value, palette.as_ref(), 0);
+    output
+}
+
+fn paint(text: &str, style: Option<Style>) -> String {
+    match style {
+        Some(style) => style.paint(text).to_string(),
+        None => text.to_string(),
+    }
+}
+
+fn quote(text: &str) -> String {
+    Value::from(text).to_string()
+}
+
+fn write_value(output: &mut String, value: &Value, palette: Option<&Palette>, depth: usize) {
+    let indent = |depth: usize| "  ".repeat(depth);
+    match value {
+        Value::Array(items) if items.is_empty() => output.push_str("[]"),
+        Value::Array(items) => {
+            output.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                output.push_str(&indent(depth + 1));
+                write_value(output, item, palette, depth + 1);
+                output.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&indent(depth));
+            output.push(']');
+        }
+        Value::Object(map) if map.is_empty() => output.push_str("{}"),
+        Value::Object(map) => {
+            output.push_str("{\n");
+            for (i, (key, item)) in map.iter().enumerate() {
+                output.push_str(&indent(depth + 1));
+                output.push_str(&paint(&quote(key), palette.map(|p| p.key)));
+                output.push_str(": ");
+                write_value(output, item, palette, depth + 1);
+                output.push_str(if i + 1 < map.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&indent(depth));
+            output.push('}');
+        }
+        Value::String(text) => output.push_str(&paint(&quote(text), palette.map(|p| p.string))),
+        literal => output.push_str(&paint(&literal.to_string(), palette.map(|p| p.literal))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn content_type() {
+        assert!(is_json_type("application/json"));
+        assert!(is_json_type("application/json; charset=utf-8"));
+        assert!(is_json_type("application/problem+JSON"));
+        assert!(!is_json_type("text/html"));
+        assert!(!is_json_type(""));
+    }
+
+    #[test]
+    fn format_plain() {
+        let value = json!({"name": "kumiko", "scores": [1, 2.5], "tags": [], "meta": {}, "ok": true, "skin": null});
+        assert_eq!(
+            pretty(&value, None),
+            "{\n  \"name\": \"kumiko\",\n  \"scores\": [\n    1,\n    2.5\n  ],\n  \"tags\": [],\n  \"meta\": {},\n  \"ok\": true,\n  \"skin\": null\n}"
+        );
+        assert_eq!(pretty(&json!("a\"b"), None), "\"a\\\"b\"");
+        assert_eq!(pretty(&json!(3), None), "3");
+    }
+
+    #[test]
+    fn keep_order() {
+        let value: Value = serde_json::from_str(r#"{"b":1,"a":2}"#).unwrap();
+        assert_eq!(pretty(&value, None), "{\n  \"b\": 1,\n  \"a\": 2\n}");
+    }
+
+    #[test]
+    fn format_colored() {
+        let theme = Theme::light();
+        let value = json!({"id": 1, "name": "a", "admin": false});
+        assert_eq!(
+            pretty(&value, Some(&theme)),
+            format!(
+                "{{\n  {}: {},\n  {}: {},\n  {}: {}\n}}",
+                theme.switch.paint("\"id\""),
+                theme.variable.paint("1"),
+                theme.switch.paint("\"name\""),
+                theme.string.paint("\"a\""),
+                theme.switch.paint("\"admin\""),
+                theme.variable.paint("false"),
+            )
+        );
+    }
+}
@@ -1,10 +1,14 @@
+mod format;
+mod json;
 mod parser;
 mod programs;
 mod shell;
 mod stdio;
+mod style;
 mod symbols;
 mod terminal;
 mod utils;
+mod widgets;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
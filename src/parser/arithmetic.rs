@@ -0,0 +1,267 @@
+use super::ast::*;
+use super::pos::Position;
+use combine::{
+    parser,
+    parser::{
+        char::{digit, spaces, string},
+        choice::{choice, optional},
+        combinator::attempt,
+        repeat::{chainl1, many, many1},
+        token::{eof, one_of, position, satisfy, token},
+        EasyParser,
+    },
+    stream::{self, easy, Positioned, Stream},
+    ParseError, Parser,
+};
+
+fn name<Input>() -> impl Parser<Input, Output = Identifier>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    (
+        position(),
+        satisfy(|c: char| c.is_ascii_alphabetic() || c == '_'),
+        many(satisfy(|c: char| c.is_ascii_alphanumeric() || c == '_')),
+        position(),
+    )
+        .map(|(start, head, tail, end): (_, char, String, _)| {
+            let span = Span { start, end };
+            let name = format!("{}{}", head, tail);
+            Identifier { name, span }
+        })
+}
+
+fn number<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    (position(), many1(digit()), position()).map(|(start, value, end)| {
+        let span = Span { start, end };
+        Expr::Number(Number { value, span })
+    })
+}
+
+fn variable<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let status = (position(), token('?'), position()).map(|(start, _, end)| {
+        let span = Span { start, end };
+        let name = "?".to_string();
+        Identifier { name, span }
+    });
+
+    (position(), token('$'), choice((name(), status)), position()).map(|(start, _, id, end)| {
+        let span = Span { start, end };
+        Expr::Variable(Variable { id, span })
+    })
+}
+
+fn paren<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    (
+        position(),
+        token('(').skip(spaces()),
+        expr(),
+        token(')'),
+        position(),
+    )
+        .map(|(start, _, expr, _, end)| {
+            let span = Span { start, end };
+            let expr = Box::new(expr);
+            Expr::Paren(ParenExpr { expr, span })
+        })
+}
+
+fn primary<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    choice((number(), variable(), name().map(Expr::Name), paren())).skip(spaces())
+}
+
+parser! {
+    fn unary[Input]()(Input) -> Expr
+    where [
+        Input: Stream<Token = char, Position = Position>,
+        Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+        Input: Positioned,
+    ]
+    {
+        let prefixed = (position(), one_of("+-!".chars()).skip(spaces()), unary()).map(
+            |(start, op, operand)| {
+                let op = match op {
+                    '+' => UnaryOp::Plus,
+                    '-' => UnaryOp::Minus,
+                    _ => UnaryOp::Not,
+                };
+                let end = operand.span().end.clone();
+                let span = Span { start, end };
+                let operand = Box::new(operand);
+                Expr::Unary(UnaryExpr { op, operand, span })
+            },
+        );
+        choice((prefixed, primary()))
+    }
+}
+
+fn operator<Input>(
+    symbol: &'static str,
+    op: BinaryOp,
+) -> impl Parser<Input, Output = (BinaryOp, Span)>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    (position(), attempt(string(symbol)), position())
+        .skip(spaces())
+        .map(move |(start, _, end)| (op, Span { start, end }))
+}
+
+fn binary(op: BinaryOp, op_span: Span) -> impl FnMut(Expr, Expr) -> Expr {
+    move |left, right| {
+        let start = left.span().start.clone();
+        let end = right.span().end.clone();
+        let span = Span { start, end };
+        Expr::Binary(BinaryExpr {
+            op,
+            op_span: op_span.clone(),
+            left: Box::new(left),
+            right: Box::new(right),
+            span,
+        })
+    }
+}
+
+fn multiplicative<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let op = choice((
+        operator("*", BinaryOp::Mul),
+        operator("/", BinaryOp::Div),
+        operator("%", BinaryOp::Rem),
+    ));
+    chainl1(unary(), op.map(|(op, span)| binary(op, span)))
+}
+
+fn additive<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let op = choice((operator("+", BinaryOp::Add), operator("-", BinaryOp::Sub)));
+    chainl1(multiplicative(), op.map(|(op, span)| binary(op, span)))
+}
+
+fn relational<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let op = choice((
+        operator("<=", BinaryOp::Le),
+        operator(">=", BinaryOp::Ge),
+        operator("<", BinaryOp::Lt),
+        operator(">", BinaryOp::Gt),
+    ));
+    chainl1(additive(), op.map(|(op, span)| binary(op, span)))
+}
+
+fn equality<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let op = choice((operator("==", BinaryOp::Eq), operator("!=", BinaryOp::Ne)));
+    chainl1(relational(), op.map(|(op, span)| binary(op, span)))
+}
+
+parser! {
+    pub(super) fn expr[Input]()(Input) -> Expr
+    where [
+        Input: Stream<Token = char, Position = Position>,
+        Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+        Input: Positioned,
+    ]
+    {
+        equality()
+    }
+}
+
+pub(super) fn arithmetic<Input>() -> impl Parser<Input, Output = Arithmetic>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    // Once we've seen `$((`, any failure inside should be reported
+    // instead of falling back to plain text.
+    (
+        position(),
+        attempt(string("$((")).skip(spaces()),
+        expr(),
+        string("))"),
+        position(),
+    )
+        .map(|(start, _, expr, _, end)| {
+            let span = Span { start, end };
+            Arithmetic { expr, span }
+        })
+}
+
+fn assignment<Input>() -> impl Parser<Input, Output = Assignment>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let target = attempt(
+        name()
+            .skip(spaces())
+            .skip(token('='))
+            .skip(combine::not_followed_by(token('=')))
+            .skip(spaces()),
+    );
+
+    (
+        position(),
+        spaces().with(optional(target)),
+        expr(),
+        position(),
+    )
+        .map(|(start, name, expr, end)| {
+            let span = Span { start, end };
+            Assignment { name, expr, span }
+        })
+}
+
+/// Parse an arithmetic expression with an optional `name=` prefix,
+/// as accepted by the `let` builtin.
+pub fn parse_assignment(input: &str) -> Result<Assignment, easy::Errors<char, &str, Position>> {
+    assignment()
+        .skip(eof())
+        .easy_parse(stream::position::Stream::with_positioner(
+            input,
+            Position::new(),
+        ))
+        .map(|x| x.0)
+}
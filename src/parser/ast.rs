@@ -1,5 +1,6 @@
 pub use super::pos::Position;
 
+#[derive(Clone, Default)]
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct Span {
     pub start: Position,
@@ -22,12 +23,14 @@ pub enum Template {
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct RawText {
     pub text: String,
+    #[allow(dead_code)]
     pub span: Span,
 }
 
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct TemplateBody {
     pub parts: Vec<TemplatePart>,
+    #[allow(dead_code)]
     pub span: Span,
 }
 
@@ -35,11 +38,14 @@ pub struct TemplateBody {
 pub enum TemplatePart {
     Raw(TemplateLiteral),
     Variable(Variable),
+    Arithmetic(Arithmetic),
+    Substitution(Substitution),
 }
 
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct TemplateLiteral {
     pub value: String,
+    #[allow(dead_code)]
     pub span: Span,
 }
 
@@ -52,6 +58,7 @@ pub struct Variable {
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct ParamLiteral {
     pub literal: Template,
+    #[allow(dead_code)]
     pub span: Span,
 }
 
@@ -62,6 +69,20 @@ pub struct Command {
     pub span: Span,
 }
 
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct Pipeline {
+    pub commands: Vec<Command>,
+    pub span: Span,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct Statement {
+    pub pipeline: Pipeline,
+    pub background: Option<Span>,
+    #[allow(dead_code)]
+    pub span: Span,
+}
+
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct Program {
     pub id: Identifier,
@@ -84,6 +105,7 @@ pub struct Parameter {
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct Parameters {
     pub params: Vec<Parameter>,
+    #[allow(dead_code)]
     pub span: Span,
 }
 
@@ -91,20 +113,137 @@ pub struct Parameters {
 pub struct Switch {
     pub name: Identifier,
     pub value: Option<Template>,
+    #[allow(dead_code)]
     pub span: Span,
 }
 
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct Comment {
+    #[allow(dead_code)]
     pub content: String,
+    #[allow(dead_code)]
+    pub span: Span,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct Substitution {
+    pub pipeline: Pipeline,
+    pub span: Span,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct Arithmetic {
+    pub expr: Expr,
+    pub span: Span,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub enum Expr {
+    Number(Number),
+    Variable(Variable),
+    Name(Identifier),
+    Unary(UnaryExpr),
+    Binary(BinaryExpr),
+    Paren(ParenExpr),
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct Number {
+    pub value: String,
     pub span: Span,
 }
 
-impl Default for Span {
-    fn default() -> Span {
-        Span {
-            start: Position::default(),
-            end: Position::default(),
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub enum UnaryOp {
+    Plus,
+    Minus,
+    Not,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct UnaryExpr {
+    pub op: UnaryOp,
+    pub operand: Box<Expr>,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct BinaryExpr {
+    pub op: BinaryOp,
+    pub op_span: Span,
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+    pub span: Span,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct ParenExpr {
+    pub expr: Box<Expr>,
+    pub span: Span,
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct Assignment {
+    pub name: Option<Identifier>,
+    pub expr: Expr,
+    #[allow(dead_code)]
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn span(&self) -> &Span {
+        match self {
+            Expr::Number(number) => &number.span,
+            Expr::Variable(variable) => &variable.span,
+            Expr::Name(id) => &id.span,
+            Expr::Unary(unary) => &unary.span,
+            Expr::Binary(binary) => &binary.span,
+            Expr::Paren(paren) => &paren.span,
+        }
+    }
+}
+
+impl UnaryOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnaryOp::Plus => "+",
+            UnaryOp::Minus => "-",
+            UnaryOp::Not => "!",
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
         }
     }
 }
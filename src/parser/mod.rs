@@ -1,18 +1,22 @@
+mod arithmetic;
 pub(crate) mod ast;
 mod pos;
 
 #[cfg(test)]
 mod tests;
 
+use arithmetic::arithmetic;
+pub use arithmetic::parse_assignment;
 use ast::*;
 use combine::{
+    parser,
     parser::{
         char::{alpha_num, space, spaces, string},
         choice::{choice, optional},
-        combinator::attempt,
-        repeat::{many, many1, sep_end_by1, skip_many1},
+        combinator::{attempt, not_followed_by},
+        repeat::{many, many1, sep_by1, sep_end_by1, skip_many1},
         sequence::between,
-        token::{one_of, position, satisfy, token},
+        token::{eof, one_of, position, satisfy, token},
         EasyParser,
     },
     stream::{self, easy, Positioned, Stream},
@@ -77,8 +81,9 @@ where
         let quote = quoted || "'#".chars().all(|x| x != c);
 
         let forbidden = "$\n\"".chars().all(|x| x != c);
+        let control = quoted || "&|)".chars().all(|x| x != c);
 
-        space && quote && forbidden
+        space && quote && forbidden && control
     }));
 
     (position(), value_parser, position()).map(|(start, value, end)| {
@@ -107,15 +112,38 @@ where
     Input: Positioned,
 {
     let template_literal = template_literal(quoted).map(TemplatePart::Raw);
+    let arithmetic = arithmetic().map(TemplatePart::Arithmetic);
+    let substitution = substitution().map(TemplatePart::Substitution);
     let variable = variable().map(TemplatePart::Variable);
     let dollar = single_dollar().map(TemplatePart::Raw);
     choice((
         attempt(template_literal),
+        arithmetic,
+        substitution,
         attempt(variable),
         attempt(dollar),
     ))
 }
 
+fn substitution<Input>() -> impl Parser<Input, Output = Substitution>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    (
+        position(),
+        attempt(string("$(")).skip(spaces()),
+        pipeline(),
+        token(')'),
+        position(),
+    )
+        .map(|(start, _, pipeline, _, end)| {
+            let span = Span { start, end };
+            Substitution { pipeline, span }
+        })
+}
+
 fn template_body<Input>(quoted: bool) -> impl Parser<Input, Output = TemplateBody>
 where
     Input: Stream<Token = char, Position = Position>,
@@ -246,11 +274,7 @@ where
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
     Input: Positioned,
 {
-    choice((
-        attempt(short_switch()),
-        attempt(long_switch()),
-        attempt(literal()),
-    ))
+    choice((attempt(long_switch()), attempt(short_switch()), literal()))
 }
 
 fn parameter<Input>() -> impl Parser<Input, Output = Parameter>
@@ -288,10 +312,27 @@ where
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
     Input: Positioned,
 {
-    (position(), loose_identifer(), position()).map(|(start, id, end)| {
-        let span = Span { start, end };
-        Program { id, span }
-    })
+    // `[` is a program name when it's a whole word, like `[ -n $a ]`.
+    let bracket = (
+        position(),
+        token('['),
+        position(),
+        not_followed_by(satisfy(|c: char| !c.is_whitespace())),
+    )
+        .map(|(start, _, end, _)| {
+            let span = Span { start, end };
+            let name = "[".to_string();
+            Identifier { name, span }
+        });
+    (
+        position(),
+        choice((loose_identifer(), attempt(bracket))),
+        position(),
+    )
+        .map(|(start, id, end)| {
+            let span = Span { start, end };
+            Program { id, span }
+        })
 }
 
 fn command<Input>() -> impl Parser<Input, Output = Command>
@@ -317,6 +358,52 @@ where
         })
 }
 
+parser! {
+    fn pipeline[Input]()(Input) -> Pipeline
+    where [
+        Input: Stream<Token = char, Position = Position>,
+        Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+        Input: Positioned,
+    ]
+    {
+        (
+            position(),
+            sep_by1(command(), token('|').skip(spaces())),
+            position(),
+        )
+            .map(|(start, commands, end)| {
+                let span = Span { start, end };
+                Pipeline { commands, span }
+            })
+    }
+}
+
+fn statement<Input>() -> impl Parser<Input, Output = Statement>
+where
+    Input: Stream<Token = char, Position = Position>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    Input: Positioned,
+{
+    let background =
+        (position(), token('&'), position()).map(|(start, _, end)| Span { start, end });
+
+    (
+        position(),
+        pipeline(),
+        optional(background),
+        position(),
+        spaces(),
+    )
+        .map(|(start, pipeline, background, end, _)| {
+            let span = Span { start, end };
+            Statement {
+                pipeline,
+                background,
+                span,
+            }
+        })
+}
+
 fn comment<Input>() -> impl Parser<Input, Output = Comment>
 where
     Input: Stream<Token = char, Position = Position>,
@@ -335,15 +422,85 @@ where
         })
 }
 
-pub fn parse_interactive(
-    input: &str,
-) -> Result<(Command, &str), easy::Errors<char, &str, Position>> {
+/// Parse a whole command line, where only a comment can follow the statement.
+pub fn parse_interactive(input: &str) -> Result<Statement, easy::Errors<char, &str, Position>> {
     spaces()
-        .with(command())
+        .with(statement())
         .skip(optional(comment()))
+        .skip(eof())
         .easy_parse(stream::position::Stream::with_positioner(
             input,
             Position::new(),
         ))
-        .map(|x| (x.0, x.1.input))
+        .map(|x| x.0)
+}
+
+/// What can be recovered from a line which can't be parsed.
+pub struct Recovery {
+    /// Statement parsed from the longest valid prefix, if there's one.
+    pub statement: Option<Statement>,
+    /// Count of chars in the valid prefix.
+    pub valid: usize,
+    /// Chars which cause the error, as a range of char indexes.
+    pub error: std::ops::Range<usize>,
+}
+
+/// Find out which part of a line is still valid after parsing failed,
+/// so it can be highlighted as usual, and mark the part causing the error.
+pub fn recover(input: &str, err: &easy::Errors<char, &str, Position>) -> Recovery {
+    let chars = input.chars().collect::<Vec<_>>();
+    let offsets = input
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(input.len()))
+        .collect::<Vec<_>>();
+    let failed = err.position.index.min(chars.len());
+
+    // A valid prefix must end at the end of a word.
+    let (statement, valid) = (1..=failed)
+        .rev()
+        .filter(|&end| {
+            !chars[end - 1].is_whitespace()
+                && chars
+                    .get(end)
+                    .is_none_or(|c| c.is_whitespace() || "|&".contains(*c))
+        })
+        .find_map(|end| {
+            let statement = parse_interactive(&input[..offsets[end]]).ok()?;
+            Some((Some(statement), end))
+        })
+        .unwrap_or((None, 0));
+
+    let start = (valid..chars.len())
+        .find(|&i| !chars[i].is_whitespace())
+        .unwrap_or(chars.len());
+    let end = (failed.max(start)..chars.len())
+        .find(|&i| chars[i].is_whitespace())
+        .unwrap_or(chars.len());
+    let end = (start..end)
+        .rev()
+        .find(|&i| !chars[i].is_whitespace())
+        .map_or(start, |i| i + 1);
+
+    Recovery {
+        statement,
+        valid,
+        error: start..end,
+    }
+}
+
+/// Describe a parsing error in the same way for every caller,
+/// such as "syntax error at line 1, column 3, unexpected `)`".
+pub fn describe_error(err: &easy::Errors<char, &str, Position>) -> String {
+    let unexpected = err
+        .errors
+        .iter()
+        .take(1)
+        .map(|e| {
+            let mut msg = format!("{}", e);
+            msg.make_ascii_lowercase();
+            msg
+        })
+        .fold(String::new(), |output, msg| output + &msg);
+    format!("syntax error at {}, {}", err.position, unexpected)
 }
@@ -322,3 +322,245 @@ fn parse_comment() {
         ))
     )
 }
+
+#[test]
+fn parse_arithmetic() {
+    let result = template_part(false)
+        .parse(source("$((1 - -x))"))
+        .map(|x| x.0);
+    assert_eq!(
+        result,
+        Ok(TemplatePart::Arithmetic(Arithmetic {
+            expr: Expr::Binary(BinaryExpr {
+                op: BinaryOp::Sub,
+                op_span: Span {
+                    start: Position {
+                        line: 1,
+                        column: 6,
+                        index: 5,
+                    },
+                    end: Position {
+                        line: 1,
+                        column: 7,
+                        index: 6,
+                    },
+                },
+                left: Box::new(Expr::Number(Number {
+                    value: "1".to_string(),
+                    span: Span {
+                        start: Position {
+                            line: 1,
+                            column: 4,
+                            index: 3,
+                        },
+                        end: Position {
+                            line: 1,
+                            column: 5,
+                            index: 4,
+                        },
+                    },
+                })),
+                right: Box::new(Expr::Unary(UnaryExpr {
+                    op: UnaryOp::Minus,
+                    operand: Box::new(Expr::Name(Identifier {
+                        name: "x".to_string(),
+                        span: Span {
+                            start: Position {
+                                line: 1,
+                                column: 9,
+                                index: 8,
+                            },
+                            end: Position {
+                                line: 1,
+                                column: 10,
+                                index: 9,
+                            },
+                        },
+                    })),
+                    span: Span {
+                        start: Position {
+                            line: 1,
+                            column: 8,
+                            index: 7,
+                        },
+                        end: Position {
+                            line: 1,
+                            column: 10,
+                            index: 9,
+                        },
+                    },
+                })),
+                span: Span {
+                    start: Position {
+                        line: 1,
+                        column: 4,
+                        index: 3,
+                    },
+                    end: Position {
+                        line: 1,
+                        column: 10,
+                        index: 9,
+                    },
+                },
+            }),
+            span: Span {
+                start: Position {
+                    line: 1,
+                    column: 1,
+                    index: 0,
+                },
+                end: Position {
+                    line: 1,
+                    column: 12,
+                    index: 11,
+                },
+            },
+        }))
+    );
+}
+
+#[test]
+fn parse_arithmetic_with_error() {
+    let result = parse_interactive("echo $((1 +))");
+    assert_eq!(result.unwrap_err().position.column, 12);
+
+    let result = parse_interactive("echo $(date)");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn parse_let_assignment() {
+    let result = parse_assignment("x = 1").map(|x| x.name.map(|id| id.name));
+    assert_eq!(result, Ok(Some("x".to_string())));
+
+    let result = parse_assignment("x == 1").map(|x| x.name.map(|id| id.name));
+    assert_eq!(result, Ok(None));
+
+    assert!(parse_assignment("x = ").is_err());
+}
+
+#[test]
+fn parse_background() {
+    let statement = parse_interactive("curl a& # b").unwrap();
+    assert_eq!(statement.pipeline.commands[0].span.end.index, 6);
+    assert_eq!(statement.background.map(|span| span.start.index), Some(6));
+
+    // Nothing but a comment can follow a statement.
+    let err = parse_interactive("curl a&b").unwrap_err();
+    assert_eq!(err.position.column, 8);
+    let err = parse_interactive("echo a && b").unwrap_err();
+    assert_eq!(err.position.column, 9);
+
+    let statement = parse_interactive("echo 'a&b' \"c&d\"").unwrap();
+    assert!(statement.background.is_none());
+}
+
+#[test]
+fn parse_pipeline() {
+    let statement = parse_interactive("echo a|grep  a |  wc -l").unwrap();
+    let names = statement
+        .pipeline
+        .commands
+        .iter()
+        .map(|command| command.program.id.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["echo", "grep", "wc"]);
+    assert_eq!(statement.pipeline.commands[1].span.start.index, 7);
+    assert_eq!(statement.pipeline.commands[2].span.start.index, 18);
+
+    assert!(parse_interactive("echo a |").is_err());
+    let statement = parse_interactive("echo 'a|b'").unwrap();
+    assert_eq!(statement.pipeline.commands.len(), 1);
+}
+
+#[test]
+fn parse_substitution() {
+    let statement = parse_interactive("export a=$( echo \"$(date)\" | wc )").unwrap();
+    let params = statement.pipeline.commands[0].parameters.as_ref().unwrap();
+    let parts = match &params.params[0].param {
+        Param::Literal(ParamLiteral {
+            literal: Template::Unquoted(body),
+            ..
+        }) => &body.parts,
+        _ => unreachable!(),
+    };
+    let substitution = match &parts[1] {
+        TemplatePart::Substitution(substitution) => substitution,
+        _ => unreachable!(),
+    };
+    assert_eq!(substitution.span.start.index, 9);
+    assert_eq!(substitution.span.end.index, 33);
+    assert_eq!(substitution.pipeline.commands.len(), 2);
+    assert_eq!(substitution.pipeline.span.start.index, 12);
+
+    assert!(parse_interactive("echo $(date").is_err());
+
+    // A `)` outside of substitutions isn't cut off silently.
+    let err = parse_interactive("echo a)b").unwrap_err();
+    assert_eq!(err.position.column, 7);
+    assert!(describe_error(&err).contains("unexpected `)`"));
+    assert!(parse_interactive("echo :)").is_err());
+    let statement = parse_interactive("echo ':)' \":)\"").unwrap();
+    assert_eq!(statement.pipeline.commands.len(), 1);
+}
+
+#[test]
+fn parse_switches() {
+    let statement = parse_interactive("curl --json -o=a --").unwrap();
+    let params = statement.pipeline.commands[0].parameters.as_ref().unwrap();
+    let switches = params
+        .params
+        .iter()
+        .map(|param| match &param.param {
+            Param::LongSwitch(switch) => format!("long {}", switch.name.name),
+            Param::ShortSwitch(switch) => format!("short {}", switch.name.name),
+            Param::Literal(_) => "literal".to_string(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(switches, vec!["long json", "short o", "short -"]);
+}
+
+#[test]
+fn parse_bracket() {
+    let statement = parse_interactive("[ -n $a ] | [ a = b ]").unwrap();
+    let commands = &statement.pipeline.commands;
+    assert_eq!(commands[0].program.id.name, "[");
+    assert_eq!(commands[0].program.span.end.index, 1);
+    assert_eq!(commands[0].parameters.as_ref().unwrap().params.len(), 3);
+    assert_eq!(commands[1].program.id.name, "[");
+
+    let statement = parse_interactive("[").unwrap();
+    assert_eq!(statement.pipeline.commands[0].program.id.name, "[");
+    assert!(parse_interactive("[a ]").is_err());
+}
+
+#[test]
+fn recover_from_error() {
+    let recovered = |input| {
+        let err = parse_interactive(input).err().unwrap();
+        let recovery = recover(input, &err);
+        let names = recovery.statement.map(|statement| {
+            statement
+                .pipeline
+                .commands
+                .iter()
+                .map(|command| command.program.id.name.clone())
+                .collect::<Vec<_>>()
+        });
+        (names, recovery.valid, recovery.error)
+    };
+
+    assert_eq!(
+        recovered("echo $((1+))"),
+        (Some(vec!["echo".to_string()]), 4, 5..12)
+    );
+    assert_eq!(
+        recovered("echo a|wc |  "),
+        (Some(vec!["echo".to_string(), "wc".to_string()]), 9, 10..11)
+    );
+    assert_eq!(
+        recovered("echo 久美子 \"abc"),
+        (Some(vec!["echo".to_string()]), 8, 9..13)
+    );
+    assert_eq!(recovered("|x"), (None, 0, 0..2));
+}
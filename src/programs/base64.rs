@@ -0,0 +1,148 @@
+use super::text::{parse_count, Options};
+use crate::shell::{executable::Builtin, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use base64::alphabet::STANDARD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+
+/// Encode data as base64, or decode it with `-d`.
+/// Data is given as arguments, like textures properties of Yggdrasil,
+/// or read from standard input.
+pub struct Base64;
+
+const ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decode base64 text, where whitespaces are ignored,
+/// and both standard and URL-safe alphabets are accepted.
+fn decode(text: &str) -> Option<Vec<u8>> {
+    let text = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect::<String>();
+    ENGINE.decode(text).ok()
+}
+
+/// Encode data as base64, and wrap lines after given columns unless it's 0.
+fn encode(data: &[u8], wrap: usize) -> Vec<String> {
+    let encoded = ENGINE.encode(data);
+    if encoded.is_empty() {
+        vec![]
+    } else if wrap == 0 {
+        vec![encoded]
+    } else {
+        encoded
+            .as_bytes()
+            .chunks(wrap)
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect()
+    }
+}
+
+impl Builtin for Base64 {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("base64", arguments, "d", "w") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let wrap = match options.value('w').map(|value| parse_count("base64", value)) {
+            Some(Ok(wrap)) => wrap,
+            Some(Err(message)) => {
+                stdio.println(&message);
+                return 2;
+            }
+            None => 76,
+        };
+        let decoding = options.flag('d');
+        let inputs = if options.operands.is_empty() {
+            vec![stdio.stdin_bytes().unwrap_or_default()]
+        } else {
+            options
+                .operands
+                .into_iter()
+                .map(String::into_bytes)
+                .collect()
+        };
+
+        for input in inputs {
+            if !decoding {
+                encode(&input, wrap)
+                    .iter()
+                    .for_each(|line| stdio.println(line));
+                continue;
+            }
+            match decode(&String::from_utf8_lossy(&input)) {
+                Some(data) => stdio.write(&data),
+                None => {
+                    stdio.println("base64: invalid input");
+                    return 1;
+                }
+            }
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{run, run_binary};
+    use super::*;
+
+    #[test]
+    fn encode_data() {
+        assert_eq!(
+            run(&Base64, None, &["kumiko", "a"]),
+            (0, "a3VtaWtv\nYQ==\n".to_string())
+        );
+        assert_eq!(
+            run_binary(&Base64, &[0xfb, 0xff], &[]),
+            (0, b"+/8=\n".to_vec())
+        );
+        assert_eq!(run(&Base64, Some(""), &[]), (0, "".to_string()));
+        assert_eq!(
+            run(&Base64, None, &["-w", "4", "kumiko"]),
+            (0, "a3Vt\naWtv\n".to_string())
+        );
+        assert_eq!(
+            run(&Base64, None, &["-w0", &"a".repeat(60)])
+                .1
+                .lines()
+                .count(),
+            1
+        );
+        assert_eq!(run(&Base64, None, &[&"a".repeat(60)]).1.lines().count(), 2);
+    }
+
+    #[test]
+    fn decode_data() {
+        assert_eq!(
+            run(&Base64, None, &["-d", "eyJhIjoxfQ=="]),
+            (0, "{\"a\":1}".to_string())
+        );
+        assert_eq!(
+            run(&Base64, Some("a3Vt\naWtv\n"), &["-d"]),
+            (0, "kumiko".to_string())
+        );
+        assert_eq!(run_binary(&Base64, b"-_8", &["-d"]), (0, vec![0xfb, 0xff]));
+        assert_eq!(
+            run(&Base64, None, &["-d", "a*b"]),
+            (1, "base64: invalid input\n".to_string())
+        );
+    }
+
+    #[test]
+    fn usage() {
+        assert_eq!(run(&Base64, None, &["-x"]).0, 2);
+        assert_eq!(run(&Base64, None, &["-w", "a"]).0, 2);
+    }
+}
@@ -1,28 +1,26 @@
 use crate::shell::{executable::Builtin, Arguments, Executables, Vars};
-use crate::terminal::Terminal;
+use crate::stdio::Stdio;
 
 pub struct Clear;
 
-impl Default for Clear {
-    fn default() -> Self {
-        Clear {}
-    }
-}
-
 impl Builtin for Clear {
-    fn run(&self, terminal: &Terminal, _: &mut Executables, _: &mut Vars, _: Arguments) {
-        terminal.clear();
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, _: Arguments) -> i32 {
+        stdio.clear();
+        0
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terminal::Terminal;
     use std::collections::HashMap;
+    use std::rc::Rc;
 
     #[test]
     fn run() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![];
@@ -30,8 +28,8 @@ mod tests {
         terminal.write("text");
         assert_eq!("text", &terminal.get());
 
-        let program = Clear::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Clear;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
         assert_eq!("", &terminal.get());
     }
 }
@@ -1,70 +1,484 @@
-use crate::shell::{executable::Internal, Argument, Arguments};
+use crate::json;
+use crate::shell::{
+    executable::{Exit, Internal},
+    fs::FileSystem,
+    transform::Prefix,
+    Argument, Arguments,
+};
 use crate::stdio::Stdio;
 use futures::channel::oneshot::Sender;
-use js_sys::Reflect;
+use js_sys::{Array, Reflect, Uint8Array};
+use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::{prelude::*, JsCast};
 use wasm_bindgen_futures::{spawn_local, JsFuture};
-use web_sys::Response;
+use web_sys::{Headers, RequestInit, Response};
 
-async fn fetch(url: &str) -> Result<JsValue, JsValue> {
-    let window = web_sys::window().expect("window should exist");
-    let resp = JsFuture::from(window.fetch_with_str(url)).await?;
-    let resp: Response = resp.dyn_into().expect("failed to convert response");
-
-    JsFuture::from(resp.text()?).await
+/// Options of a request, parsed from arguments.
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    url: String,
+    method: Option<String>,
+    headers: Vec<(String, String)>,
+    /// Request bodies from `-d` or `--json`, which are joined with `&`.
+    data: Vec<String>,
+    json: bool,
+    /// Print status and headers before the body.
+    include: bool,
+    /// Send a HEAD request and print status and headers only.
+    head: bool,
+    /// Path in the virtual file system to save the body.
+    output: Option<String>,
+    /// Fail without printing the body if the status isn't successful.
+    fail: bool,
+    /// Format the body as JSON even if `Content-Type` isn't JSON.
+    json_pretty: bool,
 }
 
-pub struct Curl;
-
-impl Default for Curl {
-    fn default() -> Self {
-        Curl
+/// Get the value of a switch, which is either after `=` or the next argument.
+fn switch_value(
+    name: &str,
+    value: Option<String>,
+    arguments: &mut impl Iterator<Item = Argument>,
+) -> Result<String, String> {
+    match value {
+        Some(value) => Ok(value),
+        None => match arguments.next() {
+            Some(Argument::Text(value)) => Ok(value),
+            _ => Err(format!("curl: option {}: requires parameter", name)),
+        },
     }
 }
 
-impl Internal for Curl {
-    fn run(&self, stdio: Rc<Stdio>, arguments: Arguments, exit: Sender<()>) {
-        spawn_local(async move {
-            let url = match arguments.get(0) {
-                Some(url) => url,
-                None => {
-                    stdio.println("No URL is provided.");
-                    exit.send(()).expect("sender failure");
-                    return;
+impl Options {
+    fn parse(arguments: Arguments) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut url = None;
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            let (key, value, prefix) = match argument {
+                Argument::Text(text) if url.is_none() => {
+                    url = Some(text);
+                    continue;
                 }
+                Argument::Text(text) => {
+                    return Err(format!("curl: unexpected argument: {}", text));
+                }
+                Argument::Switch(key, value, prefix) => (key, value, prefix),
             };
-            let url = match url {
-                Argument::Text(t) => t.clone(),
-                Argument::Switch(_, _) => {
-                    stdio.println("No URL is provided.");
-                    exit.send(()).expect("sender failure");
-                    return;
+            // Values of short options can be attached, like `-XPUT`.
+            let (key, value) = match key.chars().next() {
+                Some(c @ ('X' | 'H' | 'd' | 'o')) if prefix == Prefix::Short && key.len() > 1 => {
+                    let attached = value.map(|value| format!("={}", value));
+                    let value = format!("{}{}", &key[1..], attached.unwrap_or_default());
+                    (c.to_string(), Some(value))
                 }
+                _ => (key, value),
             };
-
-            match fetch(&url).await {
-                Ok(text) => match text.as_string() {
-                    Some(text) => {
-                        let text = text.replace("\n", "\r\n");
-                        stdio.reset();
-                        stdio.println(&text);
+            let name = format!("{}{}", prefix.dashes(), key);
+            match key.as_str() {
+                "X" | "request" => {
+                    options.method = Some(switch_value(&name, value, &mut arguments)?);
+                }
+                "H" | "header" => {
+                    let header = switch_value(&name, value, &mut arguments)?;
+                    match header.split_once(':') {
+                        Some((name, value)) => options
+                            .headers
+                            .push((name.trim().to_string(), value.trim().to_string())),
+                        None => return Err(format!("curl: invalid header: {}", header)),
                     }
-                    None => {
-                        stdio.println("conversion failed");
+                }
+                "d" | "data" => {
+                    options
+                        .data
+                        .push(switch_value(&name, value, &mut arguments)?);
+                }
+                "json" => {
+                    options
+                        .data
+                        .push(switch_value(&name, value, &mut arguments)?);
+                    options.json = true;
+                }
+                "i" | "include" => options.include = true,
+                "I" | "head" => options.head = true,
+                "o" | "output" => {
+                    options.output = Some(switch_value(&name, value, &mut arguments)?);
+                }
+                "f" | "fail" => options.fail = true,
+                "json-pretty" => options.json_pretty = true,
+                _ => return Err(format!("curl: option {}: is unknown", name)),
+            }
+        }
+
+        options.url = url.ok_or_else(|| "No URL is provided.".to_string())?;
+        Ok(options)
+    }
+
+    fn method(&self) -> String {
+        match &self.method {
+            Some(method) => method.to_uppercase(),
+            None if self.head => "HEAD".to_string(),
+            None if !self.data.is_empty() => "POST".to_string(),
+            None => "GET".to_string(),
+        }
+    }
+
+    fn body(&self) -> Option<String> {
+        if self.data.is_empty() {
+            None
+        } else {
+            Some(self.data.join("&"))
+        }
+    }
+
+    /// Headers given by user, with default headers of the body type.
+    fn headers(&self) -> Vec<(String, String)> {
+        let mut headers = self.headers.clone();
+        let mut add_default = |name: &str, value: &str| {
+            if !headers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case(name))
+            {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        };
+        if self.json {
+            add_default("Content-Type", "application/json");
+            add_default("Accept", "application/json");
+        } else if !self.data.is_empty() {
+            add_default("Content-Type", "application/x-www-form-urlencoded");
+        }
+        headers
+    }
+}
+
+/// Status line and headers of a response, followed by an empty line.
+fn format_head(status: u16, status_text: &str, headers: &[(String, String)]) -> String {
+    let mut head = format!("HTTP {} {}\r\n", status, status_text);
+    headers.iter().for_each(|(name, value)| {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    });
+    head.push_str("\r\n");
+    head
+}
+
+fn response_headers(response: &Response) -> Vec<(String, String)> {
+    js_sys::try_iter(&response.headers())
+        .ok()
+        .flatten()
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| {
+                    let entry = Array::from(&entry);
+                    let text = |i| entry.get(i).as_string().unwrap_or_default();
+                    (text(0), text(1))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn fetch(options: &Options) -> Result<Response, JsValue> {
+    let init = RequestInit::new();
+    init.set_method(&options.method());
+    let headers = Headers::new()?;
+    for (name, value) in options.headers() {
+        headers.append(&name, &value)?;
+    }
+    init.set_headers(&headers);
+    if let Some(body) = options.body() {
+        init.set_body(&JsValue::from(body));
+    }
+
+    let window = web_sys::window().expect("window should exist");
+    let response = JsFuture::from(window.fetch_with_str_and_init(&options.url, &init)).await?;
+    response.dyn_into()
+}
+
+pub struct Curl {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Curl {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Curl { fs }
+    }
+}
+
+async fn request(
+    options: Options,
+    stdio: &Stdio,
+    fs: &RefCell<FileSystem>,
+) -> Result<i32, JsValue> {
+    let response = fetch(&options).await?;
+    let status = response.status();
+    if options.fail && !response.ok() {
+        stdio.println(&format!(
+            "curl: (22) The requested URL returned error: {}",
+            status
+        ));
+        return Ok(22);
+    }
+
+    stdio.reset();
+    if options.include || options.head {
+        let headers = response_headers(&response);
+        stdio.print(&format_head(status, &response.status_text(), &headers));
+    }
+    if options.head {
+        return Ok(0);
+    }
+
+    if let Some(path) = &options.output {
+        let buffer = JsFuture::from(response.array_buffer()?).await?;
+        let data = Uint8Array::new(&buffer).to_vec();
+        if let Err(message) = fs.borrow_mut().write(path, data) {
+            stdio.println(&format!("curl: {}", message));
+            return Ok(23);
+        }
+        return Ok(0);
+    }
+
+    let content_type = response.headers().get("content-type")?;
+    let is_json = options.json_pretty || content_type.as_deref().is_some_and(json::is_json_type);
+    let text = match JsFuture::from(response.text()?).await?.as_string() {
+        Some(text) => text,
+        None => {
+            stdio.println("conversion failed");
+            return Ok(1);
+        }
+    };
+    let value = if is_json {
+        serde_json::from_str(&text).ok()
+    } else {
+        None
+    };
+    match value {
+        Some(value) => stdio.print_json(&value),
+        None => {
+            let text = text.replace('\n', "\r\n");
+            if text.is_empty() || text.ends_with('\n') {
+                stdio.print(&text);
+            } else {
+                stdio.println(&text);
+            }
+        }
+    }
+    Ok(0)
+}
+
+impl Internal for Curl {
+    fn run(&self, stdio: Rc<Stdio>, arguments: Arguments, exit: Sender<Exit>) {
+        let fs = Rc::clone(&self.fs);
+        spawn_local(async move {
+            let status = match Options::parse(arguments) {
+                Ok(options) => match request(options, &stdio, &fs).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        let message = Reflect::get(&e, &JsValue::from("message"))
+                            .ok()
+                            .and_then(|message| message.as_string())
+                            .unwrap_or_else(|| "unknown error".to_string());
+                        stdio.println(&message);
+                        1
                     }
                 },
-                Err(e) => {
-                    let message = Reflect::get(&e, &JsValue::from("message"))
-                        .ok()
-                        .and_then(|message| message.as_string())
-                        .unwrap_or_else(|| "unknown error".to_string());
+                Err(message) => {
                     stdio.println(&message);
+                    2
                 }
             };
-            if exit.send(()).is_err() {
+            if exit.send(Exit::status(status)).is_err() {
                 stdio.println("Program is hang up...Please refresh the page.");
             }
         });
     }
+
+    fn switches(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "X",
+            "request",
+            "H",
+            "header",
+            "d",
+            "data",
+            "json",
+            "i",
+            "include",
+            "I",
+            "head",
+            "o",
+            "output",
+            "f",
+            "fail",
+            "json-pretty",
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Argument {
+        Argument::Text(value.to_string())
+    }
+
+    fn switch(key: &str, value: Option<&str>) -> Argument {
+        let prefix = if key.len() == 1 {
+            Prefix::Short
+        } else {
+            Prefix::Long
+        };
+        Argument::Switch(key.to_string(), value.map(String::from), prefix)
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = Options::parse(vec![
+            switch("X", None),
+            text("put"),
+            text("/api/players"),
+            switch("H", None),
+            text("Authorization: Bearer token"),
+            switch("d", None),
+            text("a=1"),
+            switch("data", Some("b=2")),
+            switch("i", None),
+            switch("o", None),
+            text("out.json"),
+            switch("fail", None),
+        ])
+        .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                url: "/api/players".to_string(),
+                method: Some("put".to_string()),
+                headers: vec![("Authorization".to_string(), "Bearer token".to_string())],
+                data: vec!["a=1".to_string(), "b=2".to_string()],
+                json: false,
+                include: true,
+                head: false,
+                output: Some("out.json".to_string()),
+                fail: true,
+                json_pretty: false,
+            }
+        );
+        assert_eq!(options.method(), "PUT");
+        assert_eq!(options.body(), Some("a=1&b=2".to_string()));
+    }
+
+    #[test]
+    fn default_method() {
+        let options = Options::parse(vec![text("/")]).unwrap();
+        assert_eq!(options.method(), "GET");
+        assert_eq!(options.body(), None);
+        assert!(options.headers().is_empty());
+
+        let options = Options::parse(vec![switch("I", None), text("/")]).unwrap();
+        assert_eq!(options.method(), "HEAD");
+
+        let options = Options::parse(vec![text("/"), switch("d", Some("a=1"))]).unwrap();
+        assert_eq!(options.method(), "POST");
+        assert_eq!(
+            options.headers(),
+            vec![(
+                "Content-Type".to_string(),
+                "application/x-www-form-urlencoded".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn attached_values() {
+        let short = |key: &str, value: Option<&str>| {
+            Argument::Switch(key.to_string(), value.map(String::from), Prefix::Short)
+        };
+        let options = Options::parse(vec![
+            short("XPUT", None),
+            short("HAccept:x", None),
+            short("dfoo", None),
+            short("da", Some("1")),
+            short("oout.json", None),
+            text("/"),
+        ])
+        .unwrap();
+        assert_eq!(options.method(), "PUT");
+        assert_eq!(
+            options.headers,
+            vec![("Accept".to_string(), "x".to_string())]
+        );
+        assert_eq!(options.body(), Some("foo&a=1".to_string()));
+        assert_eq!(options.output, Some("out.json".to_string()));
+    }
+
+    #[test]
+    fn json_body() {
+        let options = Options::parse(vec![
+            text("/api"),
+            switch("json", None),
+            text("{\"a\":1}"),
+            switch("header", Some("accept: text/plain")),
+        ])
+        .unwrap();
+        assert_eq!(options.method(), "POST");
+        assert_eq!(options.body(), Some("{\"a\":1}".to_string()));
+        assert_eq!(
+            options.headers(),
+            vec![
+                ("accept".to_string(), "text/plain".to_string()),
+                ("Content-Type".to_string(), "application/json".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert_eq!(
+            Options::parse(vec![]),
+            Err("No URL is provided.".to_string())
+        );
+        assert_eq!(
+            Options::parse(vec![switch("i", None)]),
+            Err("No URL is provided.".to_string())
+        );
+        assert_eq!(
+            Options::parse(vec![text("/"), switch("X", None)]),
+            Err("curl: option -X: requires parameter".to_string())
+        );
+        assert_eq!(
+            Options::parse(vec![text("/"), switch("H", Some("nope"))]),
+            Err("curl: invalid header: nope".to_string())
+        );
+        assert_eq!(
+            Options::parse(vec![text("/"), switch("location", None)]),
+            Err("curl: option --location: is unknown".to_string())
+        );
+        assert_eq!(
+            Options::parse(vec![
+                text("/"),
+                Argument::Switch("sS".to_string(), None, Prefix::Short)
+            ]),
+            Err("curl: option -sS: is unknown".to_string())
+        );
+        assert_eq!(
+            Options::parse(vec![text("/"), text("/")]),
+            Err("curl: unexpected argument: /".to_string())
+        );
+    }
+
+    #[test]
+    fn head() {
+        let headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("x-powered-by".to_string(), "Blessing Skin".to_string()),
+        ];
+        assert_eq!(
+            format_head(404, "Not Found", &headers),
+            "HTTP 404 Not Found\r\ncontent-type: application/json\r\nx-powered-by: Blessing Skin\r\n\r\n"
+        );
+    }
 }
@@ -0,0 +1,167 @@
+use super::text::{read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+/// Print selected fields or characters of each line.
+pub struct Cut {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Cut {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Cut { fs }
+    }
+}
+
+/// Parse a list like `1,3-5,7-`, whose positions start from 1.
+fn parse_list(list: &str) -> Result<Vec<RangeInclusive<usize>>, String> {
+    let position = |text: &str, default: usize| -> Result<usize, String> {
+        if text.is_empty() {
+            return Ok(default);
+        }
+        match text.parse() {
+            Ok(0) => Err("cut: fields and positions are numbered from 1".to_string()),
+            Ok(position) => Ok(position),
+            Err(_) => Err(format!("cut: invalid field value: '{}'", text)),
+        }
+    };
+    list.split(',')
+        .map(|part| match part.split_once('-') {
+            Some(("", "")) => Err("cut: invalid range with no endpoint: -".to_string()),
+            Some((start, end)) => Ok(position(start, 1)?..=position(end, usize::MAX)?),
+            None => {
+                let position = position(part, 0)?;
+                if position == 0 {
+                    return Err(format!("cut: invalid field value: '{}'", part));
+                }
+                Ok(position..=position)
+            }
+        })
+        .collect()
+}
+
+fn selected(list: &[RangeInclusive<usize>], position: usize) -> bool {
+    list.iter().any(|range| range.contains(&position))
+}
+
+impl Builtin for Cut {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("cut", arguments, "s", "dfc") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (list, by_fields) = match (options.value('f'), options.value('c')) {
+            (Some(list), None) => (list, true),
+            (None, Some(list)) => (list, false),
+            _ => {
+                stdio.println("cut: you must specify a list of fields or characters");
+                return 2;
+            }
+        };
+        let list = match parse_list(list) {
+            Ok(list) => list,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let delimiter = match options.value('d').unwrap_or("\t") {
+            delimiter if delimiter.chars().count() == 1 => delimiter,
+            _ => {
+                stdio.println("cut: the delimiter must be a single character");
+                return 2;
+            }
+        };
+        let inputs = match read_inputs("cut", stdio, &self.fs.borrow(), &options.operands) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+
+        for line in inputs.iter().flat_map(|input| input.text.lines()) {
+            if !by_fields {
+                let line = line
+                    .chars()
+                    .enumerate()
+                    .filter(|(i, _)| selected(&list, i + 1))
+                    .map(|(_, c)| c)
+                    .collect::<String>();
+                stdio.println(&line);
+            } else if line.contains(delimiter) {
+                let fields = line
+                    .split(delimiter)
+                    .enumerate()
+                    .filter(|(i, _)| selected(&list, i + 1))
+                    .map(|(_, field)| field)
+                    .collect::<Vec<_>>();
+                stdio.println(&fields.join(delimiter));
+            } else if !options.flag('s') {
+                // Lines without delimiter are printed as is, unless `-s` is given.
+                stdio.println(line);
+            }
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    const USERS: &str = "1,kumiko,kumiko@example.com\n2,reina,reina@example.com\nno delimiter\n";
+
+    #[test]
+    fn fields() {
+        let cut = Cut::new(file_system(&[]));
+        assert_eq!(
+            run(&cut, Some(USERS), &["-d", ",", "-f", "2"]),
+            (0, "kumiko\nreina\nno delimiter\n".to_string())
+        );
+        assert_eq!(
+            run(&cut, Some(USERS), &["-d=,", "-f", "1,3", "-s"]),
+            (0, "1,kumiko@example.com\n2,reina@example.com\n".to_string())
+        );
+        assert_eq!(
+            run(&cut, Some("a\tb\tc\td\n"), &["-f", "2-"]),
+            (0, "b\tc\td\n".to_string())
+        );
+        assert_eq!(
+            run(&cut, Some("a:b:c\n"), &["-d", ":", "-f", "-2"]),
+            (0, "a:b\n".to_string())
+        );
+    }
+
+    #[test]
+    fn characters() {
+        let cut = Cut::new(file_system(&[("a.txt", "kumiko\n久美子\n")]));
+        assert_eq!(
+            run(&cut, None, &["-c", "1-2,4", "a.txt"]),
+            (0, "kui\n久美\n".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_list() {
+        let cut = Cut::new(file_system(&[]));
+        assert_eq!(run(&cut, Some("a"), &[]).0, 2);
+        assert_eq!(
+            run(&cut, Some("a"), &["-f", "0"]),
+            (
+                2,
+                "cut: fields and positions are numbered from 1\n".to_string()
+            )
+        );
+        assert_eq!(run(&cut, Some("a"), &["-f", "x"]).0, 2);
+        assert_eq!(run(&cut, Some("a"), &["-f", "-"]).0, 2);
+        assert_eq!(run(&cut, Some("a"), &["-d", "ab", "-f", "1"]).0, 2);
+    }
+}
@@ -1,57 +1,204 @@
-use crate::shell::{executable::Builtin, Argument, Arguments, Executables, Vars};
-use crate::terminal::Terminal;
+use super::{argument_text, printf};
+use crate::shell::{
+    executable::Builtin, transform::Prefix, Argument, Arguments, Executables, Vars,
+};
+use crate::stdio::Stdio;
 
 pub struct Echo;
 
-impl Default for Echo {
-    fn default() -> Self {
-        Echo {}
+/// Print arguments as formatted JSON, for `echo --json`.
+fn print_json(stdio: &Stdio, arguments: Arguments) -> i32 {
+    let text = arguments
+        .into_iter()
+        .map(argument_text)
+        .collect::<Vec<_>>()
+        .join(" ");
+    match serde_json::from_str(&text) {
+        Ok(value) => {
+            stdio.print_json(&value);
+            0
+        }
+        Err(err) => {
+            stdio.println(&format!("echo: invalid JSON: {}", err));
+            1
+        }
+    }
+}
+
+/// Check if an argument is options of `echo`, like `-n` or `-ne`.
+fn is_option(argument: &Argument) -> bool {
+    match argument {
+        Argument::Switch(key, None, Prefix::Short) => {
+            key.chars().all(|c| c == 'n' || c == 'e' || c == 'E')
+        }
+        _ => false,
     }
 }
 
 impl Builtin for Echo {
-    fn run(&self, terminal: &Terminal, _: &mut Executables, _: &mut Vars, arguments: Arguments) {
-        arguments.iter().for_each(|argument| {
-            match argument {
-                Argument::Text(value) => terminal.write(&value),
-                Argument::Switch(key, value) => {
-                    terminal.write(key);
-                    if let Some(value) = value {
-                        terminal.write("=");
-                        terminal.write(&value);
-                    }
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        if let Some(Argument::Switch(key, None, Prefix::Long)) = arguments.first() {
+            if key == "json" {
+                return print_json(stdio, arguments.into_iter().skip(1).collect());
+            }
+        }
+
+        let mut newline = true;
+        let mut escape = false;
+        let options = arguments.iter().take_while(|argument| is_option(argument));
+        for argument in options.clone() {
+            if let Argument::Switch(key, ..) = argument {
+                key.chars().for_each(|c| match c {
+                    'n' => newline = false,
+                    'e' => escape = true,
+                    _ => escape = false,
+                });
+            }
+        }
+
+        let count = options.count();
+        let text = arguments
+            .into_iter()
+            .skip(count)
+            .map(argument_text)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let text = if escape {
+            match printf::unescape(&text) {
+                (text, true) => text,
+                // `\c` suppresses the rest, including the line break.
+                (text, false) => {
+                    newline = false;
+                    text
                 }
             }
-            terminal.write(" ");
-        });
-        terminal.write("\r\n");
+        } else {
+            text
+        };
+        stdio.print(&text.replace('\n', "\r\n"));
+        if newline {
+            stdio.print("\r\n");
+        }
+        0
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terminal::Terminal;
     use std::collections::HashMap;
+    use std::rc::Rc;
 
     #[test]
     fn run() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![];
 
-        let program = Echo::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Echo;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
         assert_eq!("\r\n", &terminal.get());
 
         terminal.clear();
 
         let arguments = vec![
             Argument::Text("text".to_string()),
-            Argument::Switch("switch".to_string(), None),
-            Argument::Switch("key".to_string(), Some("value".to_string())),
+            Argument::Switch("switch".to_string(), None, Prefix::Long),
+            Argument::Switch("key".to_string(), Some("value".to_string()), Prefix::Long),
+        ];
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!("text --switch --key=value\r\n", &terminal.get());
+    }
+
+    #[test]
+    fn options() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+
+        let program = Echo;
+        let arguments = vec![
+            Argument::Switch("n".to_string(), None, Prefix::Short),
+            Argument::Text("a".to_string()),
+            Argument::Text("b".to_string()),
+        ];
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(stdio.captured(), "a b");
+
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let arguments = vec![
+            Argument::Switch("e".to_string(), None, Prefix::Short),
+            Argument::Text("a\\tb\\nc".to_string()),
+        ];
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(stdio.captured(), "a\tb\nc\n");
+
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let arguments = vec![
+            Argument::Switch("ne".to_string(), None, Prefix::Short),
+            Argument::Text("a\\cb".to_string()),
+        ];
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(stdio.captured(), "a");
+
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let arguments = vec![
+            Argument::Text("a\\tb".to_string()),
+            Argument::Switch("n".to_string(), None, Prefix::Short),
+        ];
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(stdio.captured(), "a\\tb -n\n");
+
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let arguments = vec![
+            Argument::Switch("n".to_string(), None, Prefix::Long),
+            Argument::Text("a".to_string()),
+        ];
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(stdio.captured(), "--n a\n");
+    }
+
+    #[test]
+    fn json() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+
+        let program = Echo;
+        let arguments = vec![
+            Argument::Switch("json".to_string(), None, Prefix::Long),
+            Argument::Text("{\"name\":\"kumiko\",\"ids\":[1]}".to_string()),
+        ];
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 0);
+        assert_eq!(
+            stdio.captured(),
+            "{\n  \"name\": \"kumiko\",\n  \"ids\": [\n    1\n  ]\n}\n"
+        );
+
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let arguments = vec![
+            Argument::Switch("json".to_string(), None, Prefix::Long),
+            Argument::Text("[".to_string()),
+            Argument::Switch("1".to_string(), None, Prefix::Short),
+            Argument::Text("]".to_string()),
+        ];
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 0);
+        assert_eq!(stdio.captured(), "[\n  -1\n]\n");
+
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let arguments = vec![
+            Argument::Switch("json".to_string(), None, Prefix::Long),
+            Argument::Text("{".to_string()),
         ];
-        program.run(&terminal, &mut executables, &mut globals, arguments);
-        assert_eq!("text switch key=value \r\n", &terminal.get());
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 1);
+        assert!(stdio.captured().starts_with("echo: invalid JSON: "));
     }
 }
@@ -1,36 +1,32 @@
 use crate::shell::{executable::Builtin, Argument, Arguments, Executables, Vars};
-use crate::terminal::Terminal;
+use crate::stdio::Stdio;
 use ansi_term::Color;
 
 pub struct Export;
 
 impl Export {
-    fn print_warning(&self, terminal: &Terminal, message: String) {
-        terminal.write(&format!("{}\r\n", Color::Yellow.paint(message)));
-    }
-}
-
-impl Default for Export {
-    fn default() -> Self {
-        Export {}
+    fn print_warning(&self, stdio: &Stdio, message: String) {
+        stdio.print(&format!("{}\r\n", Color::Yellow.paint(message)));
     }
 }
 
 impl Builtin for Export {
     fn run(
         &self,
-        terminal: &Terminal,
+        stdio: &Stdio,
         _: &mut Executables,
         globals: &mut Vars,
         arguments: Arguments,
-    ) {
+    ) -> i32 {
+        let mut status = 0;
         arguments.iter().for_each(|argument| {
             match argument {
                 Argument::Text(text) => {
                     let mut parts = text.splitn(2, '=');
                     let name = parts.next().unwrap_or_default();
-                    if name == "" {
-                        self.print_warning(terminal, "Missing variable name.".to_string());
+                    if name.is_empty() {
+                        self.print_warning(stdio, "Missing variable name.".to_string());
+                        status = 1;
                         return;
                     }
                     match parts.next() {
@@ -38,95 +34,109 @@ impl Builtin for Export {
                             globals.insert(name.to_string(), value.to_string());
                         }
                         None => {
-                            self.print_warning(terminal, "Missing variable value.".to_string());
+                            self.print_warning(stdio, "Missing variable value.".to_string());
+                            status = 1;
                         }
                     }
                 }
-                Argument::Switch(key, _) => {
-                    self.print_warning(terminal, format!("Invalid argument: {}", key));
+                Argument::Switch(key, ..) => {
+                    self.print_warning(stdio, format!("Invalid argument: {}", key));
+                    status = 1;
                 }
             };
-        })
+        });
+        status
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shell::transform::Prefix;
+    use crate::terminal::Terminal;
     use std::collections::HashMap;
+    use std::rc::Rc;
 
     #[test]
     fn no_arguments() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![];
 
-        let program = Export::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Export;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
     }
 
     #[test]
     fn pass_a_switch() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
-        let arguments = vec![Argument::Switch("s".to_string(), None)];
+        let arguments = vec![Argument::Switch("s".to_string(), None, Prefix::Short)];
 
-        let program = Export::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Export;
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 1);
         assert!(terminal.get().contains("Invalid argument: s"));
     }
 
     #[test]
     fn missing_name() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![Argument::Text("".to_string())];
 
-        let program = Export::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Export;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
         assert!(terminal.get().contains("Missing variable name."));
     }
 
     #[test]
     fn missing_value() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![Argument::Text("k".to_string())];
 
-        let program = Export::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Export;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
         assert!(terminal.get().contains("Missing variable value."));
     }
 
     #[test]
     fn allow_empty() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![Argument::Text("k=".to_string())];
 
-        let program = Export::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Export;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
         assert_eq!(Some(&"".to_string()), globals.get("k"));
     }
 
     #[test]
     fn insert() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
         let arguments = vec![Argument::Text("k=v1".to_string())];
 
-        let program = Export::default();
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        let program = Export;
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 0);
         assert_eq!(Some(&"v1".to_string()), globals.get("k"));
 
         let arguments = vec![Argument::Text("k=v2".to_string())];
-        program.run(&terminal, &mut executables, &mut globals, arguments);
+        program.run(&stdio, &mut executables, &mut globals, arguments);
         assert_eq!(Some(&"v2".to_string()), globals.get("k"));
     }
 }
@@ -0,0 +1,58 @@
+use crate::shell::{
+    executable::{Exit, Internal},
+    jobs::JobTable,
+    Argument, Arguments,
+};
+use crate::stdio::Stdio;
+use futures::channel::oneshot::Sender;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+
+pub struct Fg {
+    table: Rc<RefCell<JobTable>>,
+}
+
+impl Fg {
+    pub fn new(table: Rc<RefCell<JobTable>>) -> Self {
+        Fg { table }
+    }
+}
+
+impl Internal for Fg {
+    fn run(&self, stdio: Rc<Stdio>, arguments: Arguments, exit: Sender<Exit>) {
+        let spec = match arguments.first() {
+            Some(Argument::Text(spec)) => spec.clone(),
+            Some(Argument::Switch(key, ..)) => {
+                stdio.println(&format!("fg: invalid argument: {}", key));
+                let _ = exit.send(Exit::status(1));
+                return;
+            }
+            None => "%+".to_string(),
+        };
+
+        let job = self.table.borrow().get(&spec);
+        let job = match job {
+            Some(job) => job,
+            None => {
+                stdio.println(&format!("fg: {}: no such job", spec));
+                let _ = exit.send(Exit::status(1));
+                return;
+            }
+        };
+
+        // The job belongs to foreground now, so it won't be reported when done,
+        // and it will receive Ctrl+C.
+        let mut table = self.table.borrow_mut();
+        table.remove(job.id);
+        table.set_foreground(Some(Rc::clone(&job)));
+        stdio.println(&job.command);
+
+        let done = job.wait();
+        spawn_local(async move {
+            let _ = done.await;
+            // Nobody waits any more if `fg` itself has been terminated.
+            let _ = exit.send(Exit::status(job.status()));
+        });
+    }
+}
@@ -0,0 +1,188 @@
+use super::text::{read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use regex::RegexBuilder;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Print lines matching a pattern.
+pub struct Grep {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Grep {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Grep { fs }
+    }
+}
+
+/// Convert a basic regular expression to an extended one, where `\(`, `\)`, `\{`,
+/// `\}`, `\|`, `\+` and `\?` are special, and they're literal without backslash.
+fn basic_to_extended(pattern: &str) -> String {
+    let mut output = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if "(){}|+?".contains(c) => output.push(c),
+                Some(c) => {
+                    output.push('\\');
+                    output.push(c);
+                }
+                None => output.push_str("\\\\"),
+            },
+            '(' | ')' | '{' | '}' | '|' | '+' | '?' => {
+                output.push('\\');
+                output.push(c);
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+impl Builtin for Grep {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("grep", arguments, "ivcnEqF", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (pattern, files) = match options.operands.split_first() {
+            Some(operands) => operands,
+            None => {
+                stdio.println("grep: usage: grep [-ivcnEqF] <pattern> [file...]");
+                return 2;
+            }
+        };
+        let pattern = if options.flag('F') {
+            regex::escape(pattern)
+        } else if options.flag('E') {
+            pattern.clone()
+        } else {
+            basic_to_extended(pattern)
+        };
+        let regex = match RegexBuilder::new(&pattern)
+            .case_insensitive(options.flag('i'))
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(err) => {
+                stdio.println(&format!("grep: invalid pattern: {}", err));
+                return 2;
+            }
+        };
+        let inputs = match read_inputs("grep", stdio, &self.fs.borrow(), files) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+
+        let with_name = inputs.len() > 1;
+        let mut found = false;
+        for input in inputs {
+            let prefix = match &input.name {
+                Some(name) if with_name => format!("{}:", name),
+                _ => String::new(),
+            };
+            let lines = input
+                .text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| regex.is_match(line) != options.flag('v'))
+                .collect::<Vec<_>>();
+            found |= !lines.is_empty();
+            if options.flag('q') {
+                continue;
+            }
+            if options.flag('c') {
+                stdio.println(&format!("{}{}", prefix, lines.len()));
+                continue;
+            }
+            for (number, line) in lines {
+                if options.flag('n') {
+                    stdio.println(&format!("{}{}:{}", prefix, number + 1, line));
+                } else {
+                    stdio.println(&format!("{}{}", prefix, line));
+                }
+            }
+        }
+        if found {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    const USERS: &str = "kumiko\nReina\nhazuki\nsapphire\n";
+
+    #[test]
+    fn match_lines() {
+        let grep = Grep::new(file_system(&[]));
+        assert_eq!(
+            run(&grep, Some(USERS), &["i"]),
+            (0, "kumiko\nReina\nhazuki\nsapphire\n".to_string())
+        );
+        assert_eq!(
+            run(&grep, Some(USERS), &["re"]),
+            (0, "sapphire\n".to_string())
+        );
+        assert_eq!(
+            run(&grep, Some(USERS), &["-i", "re"]),
+            (0, "Reina\nsapphire\n".to_string())
+        );
+        assert_eq!(run(&grep, Some(USERS), &["-v", "i"]), (1, "".to_string()));
+        assert_eq!(
+            run(&grep, Some(USERS), &["-c", "k"]),
+            (0, "2\n".to_string())
+        );
+        assert_eq!(
+            run(&grep, Some(USERS), &["-in", "^r"]),
+            (0, "2:Reina\n".to_string())
+        );
+        assert_eq!(run(&grep, Some(USERS), &["-q", "z"]), (0, "".to_string()));
+    }
+
+    #[test]
+    fn patterns() {
+        let grep = Grep::new(file_system(&[]));
+        let input = Some("a+b\naab\n(x)\n");
+        assert_eq!(run(&grep, input, &["a+b"]), (0, "a+b\n".to_string()));
+        assert_eq!(run(&grep, input, &["-E", "a+b"]), (0, "aab\n".to_string()));
+        assert_eq!(run(&grep, input, &["a\\+b"]), (0, "aab\n".to_string()));
+        assert_eq!(run(&grep, input, &["(x)"]), (0, "(x)\n".to_string()));
+        assert_eq!(run(&grep, input, &["-F", "a+b"]), (0, "a+b\n".to_string()));
+        assert_eq!(run(&grep, input, &["-E", "("]).0, 2);
+    }
+
+    #[test]
+    fn files() {
+        let grep = Grep::new(file_system(&[
+            ("a.txt", "kumiko\nreina\n"),
+            ("b.txt", "kumiko\n"),
+        ]));
+        assert_eq!(
+            run(&grep, None, &["rei", "a.txt"]),
+            (0, "reina\n".to_string())
+        );
+        assert_eq!(
+            run(&grep, None, &["-c", "kumiko", "a.txt", "b.txt"]),
+            (0, "a.txt:1\nb.txt:1\n".to_string())
+        );
+        assert_eq!(
+            run(&grep, None, &["x", "c.txt"]),
+            (2, "grep: c.txt: No such file or directory\n".to_string())
+        );
+        assert_eq!(run(&grep, None, &[]).0, 2);
+    }
+}
@@ -0,0 +1,161 @@
+use super::text::{read_binary_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Print digests of files or standard input, like `sha256sum`.
+///
+/// - `hash sha256|sha1|md5 [file...]` prints digests of raw bytes.
+/// - `hash texture-hash [file...]` prints the hash which Blessing Skin uses
+///   to identify an uploaded texture.
+pub struct Hash {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Hash {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Hash { fs }
+    }
+}
+
+const USAGE: &str = "hash: usage: hash <sha256|sha1|md5|texture-hash> [file...]";
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Blessing Skin only accepts PNG textures, and stores them by
+/// SHA-256 of the uploaded file, so the same file always has the same hash.
+fn texture_hash(data: &[u8]) -> Result<String, String> {
+    if data.starts_with(PNG_SIGNATURE) {
+        Ok(hex(&Sha256::digest(data)))
+    } else {
+        Err("not a PNG image".to_string())
+    }
+}
+
+fn digest(algorithm: &str, data: &[u8]) -> Result<String, String> {
+    match algorithm {
+        "sha256" => Ok(hex(&Sha256::digest(data))),
+        "sha1" => Ok(hex(&Sha1::digest(data))),
+        "md5" => Ok(hex(&Md5::digest(data))),
+        _ => texture_hash(data),
+    }
+}
+
+impl Builtin for Hash {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("hash", arguments, "", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (algorithm, files) = match options.operands.split_first() {
+            Some((algorithm, files))
+                if ["sha256", "sha1", "md5", "texture-hash"].contains(&algorithm.as_str()) =>
+            {
+                (algorithm, files)
+            }
+            _ => {
+                stdio.println(USAGE);
+                return 2;
+            }
+        };
+        let inputs = match read_binary_inputs("hash", stdio, &self.fs.borrow(), files) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+
+        let mut status = 0;
+        for input in inputs {
+            let name = input.name.as_deref().unwrap_or("-");
+            match digest(algorithm, &input.data) {
+                Ok(hash) => stdio.println(&format!("{}  {}", hash, name)),
+                Err(message) => {
+                    stdio.println(&format!("hash: {}: {}", name, message));
+                    status = 1;
+                }
+            }
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run, run_binary};
+    use super::*;
+
+    #[test]
+    fn digests() {
+        let hash = Hash::new(file_system(&[("a.txt", "abc")]));
+        assert_eq!(
+            run(&hash, Some("abc"), &["sha256"]),
+            (
+                0,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  -\n".to_string()
+            )
+        );
+        assert_eq!(
+            run(&hash, None, &["sha1", "a.txt"]),
+            (
+                0,
+                "a9993e364706816aba3e25717850c26c9cd0d89d  a.txt\n".to_string()
+            )
+        );
+        assert_eq!(
+            run(&hash, Some(""), &["md5"]),
+            (0, "d41d8cd98f00b204e9800998ecf8427e  -\n".to_string())
+        );
+    }
+
+    #[test]
+    fn binary_input() {
+        let hash = Hash::new(file_system(&[]));
+        let (status, output) = run_binary(&hash, &[0xff, 0x00, 0x80], &["md5"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{}  -\n", hex(&Md5::digest([0xff, 0x00, 0x80])))
+        );
+    }
+
+    #[test]
+    fn texture() {
+        let hash = Hash::new(file_system(&[]));
+        let png = [PNG_SIGNATURE, b"IHDR"].concat();
+        let (status, output) = run_binary(&hash, &png, &["texture-hash"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{}  -\n", hex(&Sha256::digest(&png)))
+        );
+        assert_eq!(
+            run(&hash, Some("GIF89a"), &["texture-hash"]),
+            (1, "hash: -: not a PNG image\n".to_string())
+        );
+    }
+
+    #[test]
+    fn usage() {
+        let hash = Hash::new(file_system(&[]));
+        assert_eq!(run(&hash, None, &[]).0, 2);
+        assert_eq!(run(&hash, None, &["crc32"]).0, 2);
+        assert_eq!(run(&hash, None, &["-x", "md5"]).0, 2);
+        assert_eq!(
+            run(&hash, None, &["md5", "nope"]),
+            (1, "hash: nope: No such file or directory\n".to_string())
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use super::text::{parse_count, read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Print the first lines of input, which are 10 lines by default.
+pub struct Head {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Head {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Head { fs }
+    }
+}
+
+impl Builtin for Head {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = Options::parse("head", arguments, "", "n").and_then(|options| {
+            let count = parse_count("head", options.value('n').unwrap_or("10"))?;
+            Ok((options, count))
+        });
+        let (options, count) = match options {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let inputs = match read_inputs("head", stdio, &self.fs.borrow(), &options.operands) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+
+        let with_name = inputs.len() > 1;
+        for (i, input) in inputs.iter().enumerate() {
+            if let Some(name) = input.name.as_ref().filter(|_| with_name) {
+                let separator = if i > 0 { "\r\n" } else { "" };
+                stdio.println(&format!("{}==> {} <==", separator, name));
+            }
+            input
+                .text
+                .lines()
+                .take(count)
+                .for_each(|line| stdio.println(line));
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    #[test]
+    fn first_lines() {
+        let head = Head::new(file_system(&[]));
+        let input = (1..=12).map(|i| format!("{}\n", i)).collect::<String>();
+        assert_eq!(
+            run(&head, Some(&input), &[]),
+            (0, "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n".to_string())
+        );
+        assert_eq!(
+            run(&head, Some(&input), &["-n", "2"]),
+            (0, "1\n2\n".to_string())
+        );
+        assert_eq!(run(&head, Some(&input), &["-n1"]), (0, "1\n".to_string()));
+        assert_eq!(run(&head, Some("a"), &["-n", "0"]), (0, "".to_string()));
+    }
+
+    #[test]
+    fn files() {
+        let head = Head::new(file_system(&[("a.txt", "a\nb\n"), ("b.txt", "c\n")]));
+        assert_eq!(
+            run(&head, None, &["-n", "1", "a.txt", "b.txt"]),
+            (0, "==> a.txt <==\na\n\n==> b.txt <==\nc\n".to_string())
+        );
+        assert_eq!(run(&head, None, &["-n", "x"]).0, 2);
+        assert_eq!(run(&head, None, &["c.txt"]).0, 1);
+    }
+}
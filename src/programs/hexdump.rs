@@ -0,0 +1,169 @@
+use super::text::{parse_count, read_binary_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Print files or standard input in the canonical format of `hexdump -C`,
+/// where `-s` skips bytes, `-n` limits bytes and `-v` prints duplicated lines.
+pub struct Hexdump {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Hexdump {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Hexdump { fs }
+    }
+}
+
+/// A line with offset, 16 bytes in hex and printable characters.
+fn line(offset: usize, bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(49);
+    for (i, byte) in bytes.iter().enumerate() {
+        hex.push_str(&format!("{:02x} ", byte));
+        if i == 7 {
+            hex.push(' ');
+        }
+    }
+    let text = bytes
+        .iter()
+        .map(|byte| match byte {
+            0x20..=0x7e => *byte as char,
+            _ => '.',
+        })
+        .collect::<String>();
+    format!("{:08x}  {:<49} |{}|", offset, hex, text)
+}
+
+/// Lines of a dump, where duplicated lines are replaced by `*` unless `verbose`.
+fn dump(data: &[u8], start: usize, verbose: bool) -> Vec<String> {
+    let mut lines = vec![];
+    let mut previous: Option<&[u8]> = None;
+    let mut squeezed = false;
+    for (i, chunk) in data.chunks(16).enumerate() {
+        if !verbose && previous == Some(chunk) {
+            if !squeezed {
+                lines.push("*".to_string());
+                squeezed = true;
+            }
+            continue;
+        }
+        lines.push(line(start + i * 16, chunk));
+        previous = Some(chunk);
+        squeezed = false;
+    }
+    if !data.is_empty() {
+        lines.push(format!("{:08x}", start + data.len()));
+    }
+    lines
+}
+
+impl Builtin for Hexdump {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("hexdump", arguments, "Cv", "ns") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let count = |option| {
+            options
+                .value(option)
+                .map(|value| parse_count("hexdump", value))
+                .transpose()
+        };
+        let (skip, length) = match (count('s'), count('n')) {
+            (Ok(skip), Ok(length)) => (skip.unwrap_or(0), length),
+            (Err(message), _) | (_, Err(message)) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let inputs =
+            match read_binary_inputs("hexdump", stdio, &self.fs.borrow(), &options.operands) {
+                Ok(inputs) => inputs,
+                Err(message) => {
+                    stdio.println(&message);
+                    return 1;
+                }
+            };
+
+        let data = inputs
+            .into_iter()
+            .flat_map(|input| input.data)
+            .skip(skip)
+            .take(length.unwrap_or(usize::MAX))
+            .collect::<Vec<_>>();
+        dump(&data, skip, options.flag('v'))
+            .iter()
+            .for_each(|line| stdio.println(line));
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run, run_binary};
+    use super::*;
+
+    fn lines(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    #[test]
+    fn canonical() {
+        let hexdump = Hexdump::new(file_system(&[]));
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDRabc";
+        let (status, output) = run_binary(&hexdump, png, &["-C"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            lines(&[
+                "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|",
+                "00000010  61 62 63                                          |abc|",
+                "00000013",
+            ])
+        );
+        assert_eq!(run(&hexdump, Some(""), &[]), (0, "".to_string()));
+    }
+
+    #[test]
+    fn squeeze() {
+        let hexdump = Hexdump::new(file_system(&[("zeros", &"\0".repeat(40))]));
+        let zeros = "00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|";
+        assert_eq!(
+            run(&hexdump, None, &["zeros"]),
+            (
+                0,
+                lines(&[
+                    &format!("00000000  {}", zeros),
+                    "*",
+                    "00000020  00 00 00 00 00 00 00 00                           |........|",
+                    "00000028",
+                ])
+            )
+        );
+        assert!(!run(&hexdump, None, &["-v", "zeros"]).1.contains('*'));
+    }
+
+    #[test]
+    fn range() {
+        let hexdump = Hexdump::new(file_system(&[("a.txt", "kumiko\n")]));
+        assert_eq!(
+            run(&hexdump, None, &["-s", "2", "-n3", "a.txt"]),
+            (
+                0,
+                lines(&[
+                    "00000002  6d 69 6b                                          |mik|",
+                    "00000005",
+                ])
+            )
+        );
+        assert_eq!(run(&hexdump, None, &["-n", "x"]).0, 2);
+        assert_eq!(
+            run(&hexdump, None, &["b.txt"]),
+            (1, "hexdump: b.txt: No such file or directory\n".to_string())
+        );
+    }
+}
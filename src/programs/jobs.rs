@@ -0,0 +1,57 @@
+use crate::shell::{executable::Builtin, jobs::JobTable, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub struct Jobs {
+    table: Rc<RefCell<JobTable>>,
+}
+
+impl Jobs {
+    pub fn new(table: Rc<RefCell<JobTable>>) -> Self {
+        Jobs { table }
+    }
+}
+
+impl Builtin for Jobs {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, _: Arguments) -> i32 {
+        self.table
+            .borrow_mut()
+            .list()
+            .iter()
+            .for_each(|line| stdio.print(&format!("{}\r\n", line)));
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::executable::Exit;
+    use crate::terminal::Terminal;
+    use std::collections::HashMap;
+
+    #[test]
+    fn run() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Rc::new(Stdio::new(Rc::clone(&terminal)));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let table = Rc::new(RefCell::new(JobTable::new()));
+        table
+            .borrow_mut()
+            .add("curl a".to_string(), Rc::clone(&stdio));
+        table
+            .borrow_mut()
+            .add("curl b".to_string(), Rc::clone(&stdio))
+            .finish(Exit::default());
+
+        let program = Jobs::new(Rc::clone(&table));
+        program.run(&stdio, &mut executables, &mut globals, vec![]);
+        assert_eq!(
+            "[1]-  Running                 curl a &\r\n[2]+  Done                    curl b\r\n",
+            &terminal.get()
+        );
+        assert_eq!(table.borrow().running().len(), 1);
+    }
+}
@@ -0,0 +1,577 @@
+use super::parse::{BinaryOp, Filter};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Make a number without fraction an integer, so `1 + 1` prints `2` rather than `2.0`.
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        Value::from(value as i64)
+    } else {
+        Value::from(value)
+    }
+}
+
+/// Order of values, which is null < false < true < numbers < strings < arrays < objects.
+fn compare(left: &Value, right: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            let left = left.as_f64().unwrap_or_default();
+            let right = right.as_f64().unwrap_or_default();
+            left.partial_cmp(&right).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(left), Value::String(right)) => left.cmp(right),
+        (Value::Array(left), Value::Array(right)) => left
+            .iter()
+            .zip(right)
+            .map(|(left, right)| compare(left, right))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| left.len().cmp(&right.len())),
+        (Value::Object(left), Value::Object(right)) => {
+            let mut left_keys = left.keys().collect::<Vec<_>>();
+            let mut right_keys = right.keys().collect::<Vec<_>>();
+            left_keys.sort();
+            right_keys.sort();
+            left_keys.cmp(&right_keys).then_with(|| {
+                left_keys
+                    .iter()
+                    .map(|key| compare(&left[*key], &right[*key]))
+                    .find(|ordering| *ordering != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, String> {
+    let error = |verb: &str| {
+        Err(format!(
+            "{} ({}) and {} ({}) cannot be {}",
+            type_name(left),
+            left,
+            type_name(right),
+            right,
+            verb
+        ))
+    };
+    match (op, left, right) {
+        (BinaryOp::Add, Value::Null, value) | (BinaryOp::Add, value, Value::Null) => {
+            Ok(value.clone())
+        }
+        (_, Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (
+                a.as_f64().unwrap_or_default(),
+                b.as_f64().unwrap_or_default(),
+            );
+            match op {
+                BinaryOp::Add => Ok(number(a + b)),
+                BinaryOp::Sub => Ok(number(a - b)),
+                BinaryOp::Mul => Ok(number(a * b)),
+                BinaryOp::Div if b == 0.0 => error("divided because the divisor is zero"),
+                BinaryOp::Div => Ok(number(a / b)),
+                BinaryOp::Rem if b as i64 == 0 => error("divided because the divisor is zero"),
+                _ => Ok(number((a as i64 % b as i64) as f64)),
+            }
+        }
+        (BinaryOp::Add, Value::String(a), Value::String(b)) => {
+            Ok(Value::from(format!("{}{}", a, b)))
+        }
+        (BinaryOp::Add, Value::Array(a), Value::Array(b)) => {
+            Ok(Value::Array(a.iter().chain(b).cloned().collect()))
+        }
+        (BinaryOp::Add, Value::Object(a), Value::Object(b)) => {
+            let mut object = a.clone();
+            object.extend(b.clone());
+            Ok(Value::Object(object))
+        }
+        (BinaryOp::Sub, Value::Array(a), Value::Array(b)) => Ok(Value::Array(
+            a.iter().filter(|item| !b.contains(item)).cloned().collect(),
+        )),
+        (BinaryOp::Div, Value::String(a), Value::String(b)) => {
+            Ok(Value::Array(a.split(b.as_str()).map(Value::from).collect()))
+        }
+        (BinaryOp::Add, ..) => error("added"),
+        (BinaryOp::Sub, ..) => error("subtracted"),
+        (BinaryOp::Mul, ..) => error("multiplied"),
+        _ => error("divided"),
+    }
+}
+
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, String> {
+    let ordering = compare(left, right);
+    Ok(Value::Bool(match op {
+        BinaryOp::Eq => ordering == Ordering::Equal,
+        BinaryOp::Ne => ordering != Ordering::Equal,
+        BinaryOp::Lt => ordering == Ordering::Less,
+        BinaryOp::Le => ordering != Ordering::Greater,
+        BinaryOp::Gt => ordering == Ordering::Greater,
+        BinaryOp::Ge => ordering != Ordering::Less,
+        _ => return arithmetic(op, left, right),
+    }))
+}
+
+fn index(value: &Value, key: &Value) -> Result<Value, String> {
+    match (value, key) {
+        (Value::Object(object), Value::String(key)) => {
+            Ok(object.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(items), Value::Number(i)) => {
+            let i = i.as_f64().unwrap_or_default().floor() as i64;
+            let i = if i < 0 { i + items.len() as i64 } else { i };
+            Ok(Some(i)
+                .filter(|i| *i >= 0)
+                .and_then(|i| items.get(i as usize))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_)) | (Value::Null, Value::Number(_)) => Ok(Value::Null),
+        (value, Value::String(key)) => Err(format!(
+            "Cannot index {} with \"{}\"",
+            type_name(value),
+            key
+        )),
+        (value, key) => Err(format!(
+            "Cannot index {} with {}",
+            type_name(value),
+            type_name(key)
+        )),
+    }
+}
+
+fn iterate(value: &Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::Array(items) => Ok(items.clone()),
+        Value::Object(object) => Ok(object.values().cloned().collect()),
+        value => Err(format!("Cannot iterate over {}", type_name(value))),
+    }
+}
+
+fn length(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Null => Ok(Value::from(0)),
+        Value::Number(n) => Ok(number(n.as_f64().unwrap_or_default().abs())),
+        Value::String(text) => Ok(Value::from(text.chars().count())),
+        Value::Array(items) => Ok(Value::from(items.len())),
+        Value::Object(object) => Ok(Value::from(object.len())),
+        value => Err(format!("{} ({}) has no length", type_name(value), value)),
+    }
+}
+
+fn keys(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Object(object) => {
+            let mut keys = object.keys().cloned().collect::<Vec<_>>();
+            keys.sort();
+            Ok(Value::from(keys))
+        }
+        Value::Array(items) => Ok(Value::from((0..items.len()).collect::<Vec<_>>())),
+        value => Err(format!("{} ({}) has no keys", type_name(value), value)),
+    }
+}
+
+fn has(value: &Value, key: &Value) -> Result<Value, String> {
+    match (value, key) {
+        (Value::Object(object), Value::String(key)) => Ok(Value::Bool(object.contains_key(key))),
+        (Value::Array(items), Value::Number(i)) => {
+            let i = i.as_f64().unwrap_or_default();
+            Ok(Value::Bool(i >= 0.0 && (i as usize) < items.len()))
+        }
+        (value, key) => Err(format!(
+            "Cannot check whether {} has a {} key",
+            type_name(value),
+            type_name(key)
+        )),
+    }
+}
+
+fn to_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Names and numbers of arguments of built-in functions.
+const FUNCTIONS: [(&str, usize); 24] = [
+    ("empty", 0),
+    ("not", 0),
+    ("length", 0),
+    ("keys", 0),
+    ("type", 0),
+    ("tostring", 0),
+    ("tonumber", 0),
+    ("ascii_downcase", 0),
+    ("ascii_upcase", 0),
+    ("add", 0),
+    ("sort", 0),
+    ("reverse", 0),
+    ("first", 0),
+    ("last", 0),
+    ("to_entries", 0),
+    ("select", 1),
+    ("map", 1),
+    ("has", 1),
+    ("startswith", 1),
+    ("endswith", 1),
+    ("contains", 1),
+    ("join", 1),
+    ("min", 0),
+    ("max", 0),
+];
+
+/// Call a built-in function with outputs of each argument.
+fn call(name: &str, arguments: &[Filter], input: &Value) -> Result<Vec<Value>, String> {
+    let with_string = |f: &dyn Fn(&str, &str) -> bool| -> Result<Vec<Value>, String> {
+        eval(&arguments[0], input)?
+            .iter()
+            .map(|argument| match (input, argument) {
+                (Value::String(text), Value::String(argument)) => {
+                    Ok(Value::Bool(f(text, argument)))
+                }
+                _ => Err(format!("{}() requires string inputs", name)),
+            })
+            .collect()
+    };
+    let one = |value: Result<Value, String>| value.map(|value| vec![value]);
+    match (name, arguments.len(), input) {
+        ("empty", 0, _) => Ok(vec![]),
+        ("not", 0, _) => Ok(vec![Value::Bool(!is_truthy(input))]),
+        ("length", 0, _) => one(length(input)),
+        ("keys", 0, _) => one(keys(input)),
+        ("type", 0, _) => Ok(vec![Value::from(type_name(input))]),
+        ("tostring", 0, _) => Ok(vec![Value::from(to_string(input))]),
+        ("tonumber", 0, Value::Number(_)) => Ok(vec![input.clone()]),
+        ("tonumber", 0, Value::String(text)) => match text.trim().parse::<f64>() {
+            Ok(value) => Ok(vec![number(value)]),
+            Err(_) => Err(format!("Cannot parse '{}' as JSON", text)),
+        },
+        ("ascii_downcase", 0, Value::String(text)) => Ok(vec![Value::from(text.to_lowercase())]),
+        ("ascii_upcase", 0, Value::String(text)) => Ok(vec![Value::from(text.to_uppercase())]),
+        ("add", 0, Value::Array(items)) => one(items.iter().try_fold(Value::Null, |sum, item| {
+            arithmetic(BinaryOp::Add, &sum, item)
+        })),
+        ("sort", 0, Value::Array(items)) => {
+            let mut items = items.clone();
+            items.sort_by(compare);
+            Ok(vec![Value::Array(items)])
+        }
+        ("reverse", 0, Value::Array(items)) => {
+            Ok(vec![Value::Array(items.iter().rev().cloned().collect())])
+        }
+        ("reverse", 0, Value::String(text)) => {
+            Ok(vec![Value::from(text.chars().rev().collect::<String>())])
+        }
+        ("min", 0, Value::Array(items)) => Ok(vec![items
+            .iter()
+            .min_by(|a, b| compare(a, b))
+            .cloned()
+            .unwrap_or(Value::Null)]),
+        ("max", 0, Value::Array(items)) => Ok(vec![items
+            .iter()
+            .max_by(|a, b| compare(a, b))
+            .cloned()
+            .unwrap_or(Value::Null)]),
+        ("first", 0, _) => one(index(input, &Value::from(0))),
+        ("last", 0, _) => one(index(input, &Value::from(-1))),
+        ("to_entries", 0, Value::Object(object)) => Ok(vec![Value::Array(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    let mut entry = Map::new();
+                    entry.insert("key".to_string(), Value::from(key.as_str()));
+                    entry.insert("value".to_string(), value.clone());
+                    Value::Object(entry)
+                })
+                .collect(),
+        )]),
+        ("select", 1, _) => Ok(eval(&arguments[0], input)?
+            .iter()
+            .filter(|condition| is_truthy(condition))
+            .map(|_| input.clone())
+            .collect()),
+        ("map", 1, _) => {
+            let mut items = vec![];
+            for item in iterate(input)? {
+                items.extend(eval(&arguments[0], &item)?);
+            }
+            Ok(vec![Value::Array(items)])
+        }
+        ("has", 1, _) => eval(&arguments[0], input)?
+            .iter()
+            .map(|key| has(input, key))
+            .collect(),
+        ("startswith", 1, _) => with_string(&|text, prefix| text.starts_with(prefix)),
+        ("endswith", 1, _) => with_string(&|text, suffix| text.ends_with(suffix)),
+        ("contains", 1, Value::String(_)) => with_string(&|text, part| text.contains(part)),
+        ("join", 1, Value::Array(items)) => eval(&arguments[0], input)?
+            .iter()
+            .map(|separator| {
+                let separator = match separator {
+                    Value::String(separator) => separator,
+                    _ => return Err("join() requires a string separator".to_string()),
+                };
+                items
+                    .iter()
+                    .map(|item| match item {
+                        Value::Null => Ok(String::new()),
+                        Value::Array(_) | Value::Object(_) => {
+                            Err(format!("Cannot join with {}", type_name(item)))
+                        }
+                        item => Ok(to_string(item)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|parts| Value::from(parts.join(separator)))
+            })
+            .collect(),
+        _ if FUNCTIONS.contains(&(name, arguments.len())) => Err(format!(
+            "{} ({}) cannot be used with {}",
+            type_name(input),
+            input,
+            name
+        )),
+        _ => Err(format!("{}/{} is not defined", name, arguments.len())),
+    }
+}
+
+/// Run a filter on a value, and collect all outputs.
+pub fn eval(filter: &Filter, input: &Value) -> Result<Vec<Value>, String> {
+    match filter {
+        Filter::Identity => Ok(vec![input.clone()]),
+        Filter::Literal(value) => Ok(vec![value.clone()]),
+        Filter::Index(target, key) => {
+            let keys = eval(key, input)?;
+            let mut outputs = vec![];
+            for value in eval(target, input)? {
+                for key in &keys {
+                    outputs.push(index(&value, key)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Filter::Iterate(target) => {
+            let mut outputs = vec![];
+            for value in eval(target, input)? {
+                outputs.extend(iterate(&value)?);
+            }
+            Ok(outputs)
+        }
+        Filter::Try(filter) => Ok(eval(filter, input).unwrap_or_default()),
+        Filter::Array(None) => Ok(vec![Value::Array(vec![])]),
+        Filter::Array(Some(filter)) => Ok(vec![Value::Array(eval(filter, input)?)]),
+        Filter::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key, value) in entries {
+                let keys = eval(key, input)?;
+                let values = eval(value, input)?;
+                let mut next = vec![];
+                for object in &objects {
+                    for key in &keys {
+                        let key = match key {
+                            Value::String(key) => key,
+                            key => {
+                                return Err(format!(
+                                    "Object keys must be strings, not {}",
+                                    type_name(key)
+                                ))
+                            }
+                        };
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Filter::Pipe(left, right) => {
+            let mut outputs = vec![];
+            for value in eval(left, input)? {
+                outputs.extend(eval(right, &value)?);
+            }
+            Ok(outputs)
+        }
+        Filter::Comma(left, right) => {
+            let mut outputs = eval(left, input)?;
+            outputs.extend(eval(right, input)?);
+            Ok(outputs)
+        }
+        Filter::Binary(op @ (BinaryOp::And | BinaryOp::Or), left, right) => {
+            let mut outputs = vec![];
+            for left in eval(left, input)? {
+                match (op, is_truthy(&left)) {
+                    (BinaryOp::And, false) => outputs.push(Value::Bool(false)),
+                    (BinaryOp::Or, true) => outputs.push(Value::Bool(true)),
+                    _ => outputs.extend(
+                        eval(right, input)?
+                            .iter()
+                            .map(|right| Value::Bool(is_truthy(right))),
+                    ),
+                }
+            }
+            Ok(outputs)
+        }
+        Filter::Binary(op, left, right) => {
+            let lefts = eval(left, input)?;
+            let mut outputs = vec![];
+            for right in eval(right, input)? {
+                for left in &lefts {
+                    outputs.push(binary(*op, left, &right)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Filter::Call(name, arguments) => call(name, arguments, input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse::parse;
+    use super::*;
+    use serde_json::json;
+
+    fn run(filter: &str, input: Value) -> Result<Vec<Value>, String> {
+        eval(&parse(filter).unwrap(), &input)
+    }
+
+    #[test]
+    fn paths() {
+        let input = json!({"users": [{"name": "kumiko"}, {"name": "reina"}]});
+        assert_eq!(
+            run(".users[0].name", input.clone()),
+            Ok(vec![json!("kumiko")])
+        );
+        assert_eq!(
+            run(".users[-1].name", input.clone()),
+            Ok(vec![json!("reina")])
+        );
+        assert_eq!(run(".users[5]", input.clone()), Ok(vec![json!(null)]));
+        assert_eq!(run(".missing.name", input.clone()), Ok(vec![json!(null)]));
+        assert_eq!(
+            run(".users[].name", input.clone()),
+            Ok(vec![json!("kumiko"), json!("reina")])
+        );
+        assert_eq!(
+            run(".users.name", input.clone()),
+            Err("Cannot index array with \"name\"".to_string())
+        );
+        assert_eq!(run(".users.name?", input), Ok(vec![]));
+        assert_eq!(
+            run(".[]", json!(1)),
+            Err("Cannot iterate over number".to_string())
+        );
+    }
+
+    #[test]
+    fn functions() {
+        let input = json!({"b": [3, 1, 2], "a": "kumiko"});
+        assert_eq!(run("keys", input.clone()), Ok(vec![json!(["a", "b"])]));
+        assert_eq!(
+            run(".[] | length", input.clone()),
+            Ok(vec![json!(3), json!(6)])
+        );
+        assert_eq!(run(".b | sort", input.clone()), Ok(vec![json!([1, 2, 3])]));
+        assert_eq!(run(".b | add", input.clone()), Ok(vec![json!(6)]));
+        assert_eq!(
+            run(".b | map(. * 2)", input.clone()),
+            Ok(vec![json!([6, 2, 4])])
+        );
+        assert_eq!(
+            run("has(\"a\"), has(\"c\")", input.clone()),
+            Ok(vec![json!(true), json!(false)])
+        );
+        assert_eq!(
+            run(".a | startswith(\"kumi\")", input.clone()),
+            Ok(vec![json!(true)])
+        );
+        assert_eq!(
+            run(".b | join(\"-\")", input.clone()),
+            Ok(vec![json!("3-1-2")])
+        );
+        assert_eq!(run(".b | length", input.clone()), Ok(vec![json!(3)]));
+        assert_eq!(
+            run("true | keys", input.clone()),
+            Err("boolean (true) has no keys".to_string())
+        );
+        assert_eq!(
+            run("nope(1)", input),
+            Err("nope/1 is not defined".to_string())
+        );
+    }
+
+    #[test]
+    fn select() {
+        let input = json!([{"id": 1, "admin": true}, {"id": 2}, {"id": 3, "admin": false}]);
+        assert_eq!(
+            run(".[] | select(.id >= 2) | .id", input.clone()),
+            Ok(vec![json!(2), json!(3)])
+        );
+        assert_eq!(
+            run("[.[] | select(.admin | not) | .id]", input.clone()),
+            Ok(vec![json!([2, 3])])
+        );
+        assert_eq!(
+            run("map(select(.id == 1 or .id == 3) | .id)", input),
+            Ok(vec![json!([1, 3])])
+        );
+    }
+
+    #[test]
+    fn construct() {
+        let input = json!({"name": "kumiko", "ids": [1, 2]});
+        assert_eq!(
+            run("{name, id: .ids[]}", input.clone()),
+            Ok(vec![
+                json!({"name": "kumiko", "id": 1}),
+                json!({"name": "kumiko", "id": 2})
+            ])
+        );
+        assert_eq!(
+            run("[.ids[] + 0.5, .name + \"!\"]", input),
+            Ok(vec![json!([1.5, 2.5, "kumiko!"])])
+        );
+        assert_eq!(
+            run("1 / 2, 4 / 2", json!(null)),
+            Ok(vec![json!(0.5), json!(2)])
+        );
+        assert!(run("1 / 0", json!(null)).is_err());
+    }
+
+    #[test]
+    fn order() {
+        let values = json!([{"a": 1}, [1], "a", 2, true, false, null]);
+        assert_eq!(
+            run("sort", values),
+            Ok(vec![json!([null, false, true, 2, "a", [1], {"a": 1}])])
+        );
+    }
+}
@@ -0,0 +1,160 @@
+mod eval;
+mod parse;
+
+use super::text::Options;
+use crate::shell::{
+    executable::Builtin, transform::Prefix, Argument, Arguments, Executables, Vars,
+};
+use crate::stdio::Stdio;
+use serde_json::Value;
+
+/// Process JSON with a filter, like `jq`. JSON is read from standard input,
+/// or the argument after the filter.
+pub struct Jq;
+
+const USAGE: &str = "jq: usage: jq [-r] [-c] <filter> [json]";
+
+impl Jq {
+    fn print(stdio: &Stdio, value: &Value, raw: bool, compact: bool) {
+        match value {
+            Value::String(text) if raw => stdio.println(&text.replace('\n', "\r\n")),
+            value if compact => stdio.println(&value.to_string()),
+            value => stdio.print_json(value),
+        }
+    }
+}
+
+impl Builtin for Jq {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        // Long options are spelled out flags, so they are parsed as their short forms.
+        let arguments = arguments
+            .into_iter()
+            .map(|argument| match argument {
+                Argument::Switch(key, None, Prefix::Long) if key == "raw-output" => {
+                    Argument::Switch("r".to_string(), None, Prefix::Short)
+                }
+                Argument::Switch(key, None, Prefix::Long) if key == "compact-output" => {
+                    Argument::Switch("c".to_string(), None, Prefix::Short)
+                }
+                argument => argument,
+            })
+            .collect();
+        let options = match Options::parse("jq", arguments, "rc", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (raw, compact) = (options.flag('r'), options.flag('c'));
+        let texts = options.operands;
+        let (filter, input) = match texts.as_slice() {
+            [filter] => (filter, stdio.stdin().unwrap_or_default()),
+            [filter, json] => (filter, json.clone()),
+            _ => {
+                stdio.println(USAGE);
+                return 2;
+            }
+        };
+
+        let filter = match parse::parse(filter) {
+            Ok(filter) => filter,
+            Err(err) => {
+                stdio.println(&format!("jq: error: {}", err));
+                return 3;
+            }
+        };
+        for value in serde_json::Deserializer::from_str(&input).into_iter::<Value>() {
+            let value = match value {
+                Ok(value) => value,
+                Err(err) => {
+                    stdio.println(&format!("jq: error: invalid JSON: {}", err));
+                    return 2;
+                }
+            };
+            match eval::eval(&filter, &value) {
+                Ok(outputs) => outputs
+                    .iter()
+                    .for_each(|output| Jq::print(stdio, output, raw, compact)),
+                Err(err) => {
+                    stdio.println(&format!("jq: error: {}", err));
+                    return 5;
+                }
+            }
+        }
+        0
+    }
+
+    fn switches(&self) -> Option<&'static [&'static str]> {
+        Some(&["r", "raw-output", "c", "compact-output"])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::run;
+    use super::*;
+
+    #[test]
+    fn stdin() {
+        let input = r#"{"users": [{"name": "kumiko", "uid": 1}, {"name": "reina", "uid": 2}]}"#;
+        assert_eq!(
+            run(&Jq, Some(input), &[".users[0]"]),
+            (
+                0,
+                "{\n  \"name\": \"kumiko\",\n  \"uid\": 1\n}\n".to_string()
+            )
+        );
+        assert_eq!(
+            run(
+                &Jq,
+                Some(input),
+                &["-r", ".users[] | select(.uid > 1) | .name"]
+            ),
+            (0, "reina\n".to_string())
+        );
+        assert_eq!(
+            run(&Jq, Some("1 [2]"), &["-c", "[.]"]),
+            (0, "[1]\n[[2]]\n".to_string())
+        );
+        assert_eq!(
+            run(&Jq, Some(r#"["a", "b"]"#), &["-rc", ".[]"]),
+            (0, "a\nb\n".to_string())
+        );
+        assert_eq!(
+            run(&Jq, Some("[1]"), &["--compact-output", "."]),
+            (0, "[1]\n".to_string())
+        );
+    }
+
+    #[test]
+    fn argument() {
+        assert_eq!(
+            run(&Jq, None, &["keys", r#"{"b": 1, "a": 2}"#]),
+            (0, "[\n  \"a\",\n  \"b\"\n]\n".to_string())
+        );
+        assert_eq!(
+            run(&Jq, Some("[1]"), &["length", "\"kumiko\""]),
+            (0, "6\n".to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(run(&Jq, None, &[]), (2, format!("{}\n", USAGE)));
+        assert_eq!(
+            run(&Jq, None, &["-x", "."]),
+            (2, "jq: invalid option -- 'x'\n".to_string())
+        );
+        assert_eq!(run(&Jq, Some("{"), &["."]).0, 2);
+
+        let (status, output) = run(&Jq, Some("1"), &[".["]);
+        assert_eq!(status, 3);
+        assert!(output.starts_with("jq: error: syntax error at column 3"));
+
+        assert_eq!(
+            run(&Jq, Some("1"), &[".[]"]),
+            (5, "jq: error: Cannot iterate over number\n".to_string())
+        );
+    }
+}
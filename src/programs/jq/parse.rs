@@ -0,0 +1,462 @@
+use combine::{
+    between, eof, many, many1, not_followed_by, optional, parser,
+    parser::{
+        char::{alpha_num, digit, spaces, string},
+        choice::choice,
+        combinator::attempt,
+        repeat::{chainl1, sep_by},
+        token::{satisfy, token},
+        EasyParser,
+    },
+    stream::{self, Stream},
+    ParseError, Parser,
+};
+use serde_json::{Number, Value};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Filter {
+    /// `.`
+    Identity,
+    /// `.name`, `.["name"]` or `.[0]`. The key is evaluated with the original input.
+    Index(Box<Filter>, Box<Filter>),
+    /// `.[]`
+    Iterate(Box<Filter>),
+    /// `f?`, which ignores errors.
+    Try(Box<Filter>),
+    Literal(Value),
+    /// `[f]`, which collects all outputs of `f`.
+    Array(Option<Box<Filter>>),
+    /// `{key: f}`
+    Object(Vec<(Filter, Filter)>),
+    Pipe(Box<Filter>, Box<Filter>),
+    Comma(Box<Filter>, Box<Filter>),
+    Binary(BinaryOp, Box<Filter>, Box<Filter>),
+    /// Built-in function with arguments, like `select(.id > 1)`.
+    Call(String, Vec<Filter>),
+}
+
+fn lex<Input, P>(parser: P) -> impl Parser<Input, Output = P::Output>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    P: Parser<Input>,
+{
+    parser.skip(spaces())
+}
+
+fn identifier<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    (
+        satisfy(|c: char| c.is_ascii_alphabetic() || c == '_'),
+        many(satisfy(|c: char| c.is_ascii_alphanumeric() || c == '_')),
+    )
+        .map(|(head, tail): (char, String)| format!("{}{}", head, tail))
+}
+
+fn string_literal<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let escaped = token('\\').with(satisfy(|c| "\"\\/nrt".contains(c)).map(|c| match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        c => c,
+    }));
+    let plain = satisfy(|c| c != '"' && c != '\\');
+    between(token('"'), token('"'), many(choice((escaped, plain))))
+}
+
+fn number<Input>() -> impl Parser<Input, Output = Value>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let fraction = optional((token('.'), many1(digit())))
+        .map(|fraction: Option<(char, String)>| fraction.map(|(_, digits)| digits));
+    (optional(token('-')), many1(digit()), fraction).map(
+        |(sign, integer, fraction): (Option<char>, String, Option<String>)| {
+            let mut text = sign.map(String::from).unwrap_or_default();
+            text.push_str(&integer);
+            if let Some(fraction) = fraction {
+                text.push('.');
+                text.push_str(&fraction);
+            }
+            text.parse::<Number>()
+                .map(Value::Number)
+                .unwrap_or(Value::Null)
+        },
+    )
+}
+
+/// `.name`, `.[...]`, `[...]` or `?` after a term.
+enum Suffix {
+    Field(String),
+    Iterate,
+    Index(Filter),
+    Try,
+}
+
+fn suffix<Input>() -> impl Parser<Input, Output = Suffix>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let field =
+        attempt(token('.').with(choice((identifier(), string_literal())))).map(Suffix::Field);
+    let bracket = (
+        optional(attempt(token('.'))),
+        lex(token('[')),
+        optional(pipe()),
+        token(']'),
+    )
+        .map(|(_, _, index, _)| match index {
+            Some(index) => Suffix::Index(index),
+            None => Suffix::Iterate,
+        });
+    lex(choice((field, bracket, token('?').map(|_| Suffix::Try))))
+}
+
+fn apply_suffix(filter: Filter, suffix: Suffix) -> Filter {
+    match suffix {
+        Suffix::Field(name) => Filter::Index(
+            Box::new(filter),
+            Box::new(Filter::Literal(Value::String(name))),
+        ),
+        Suffix::Iterate => Filter::Iterate(Box::new(filter)),
+        Suffix::Index(index) => Filter::Index(Box::new(filter), Box::new(index)),
+        Suffix::Try => Filter::Try(Box::new(filter)),
+    }
+}
+
+fn dot<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    (
+        token('.'),
+        optional(choice((identifier(), string_literal()))),
+    )
+        .map(|(_, field)| match field {
+            Some(field) => apply_suffix(Filter::Identity, Suffix::Field(field)),
+            None => Filter::Identity,
+        })
+}
+
+fn call<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let arguments = between(lex(token('(')), token(')'), sep_by(pipe(), lex(token(';'))));
+    (lex(identifier()), optional(arguments)).map(|(name, arguments)| match name.as_str() {
+        "true" => Filter::Literal(Value::Bool(true)),
+        "false" => Filter::Literal(Value::Bool(false)),
+        "null" => Filter::Literal(Value::Null),
+        _ => Filter::Call(name, arguments.unwrap_or_default()),
+    })
+}
+
+fn object<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let key = lex(choice((identifier(), string_literal())));
+    let entry = (key, optional(lex(token(':')).with(alternative()))).map(|(key, value)| {
+        // `{name}` is short for `{name: .name}`
+        let value =
+            value.unwrap_or_else(|| apply_suffix(Filter::Identity, Suffix::Field(key.clone())));
+        (Filter::Literal(Value::String(key)), value)
+    });
+    between(lex(token('{')), token('}'), sep_by(entry, lex(token(',')))).map(Filter::Object)
+}
+
+fn term<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let array = between(lex(token('[')), token(']'), optional(pipe()))
+        .map(|filter| Filter::Array(filter.map(Box::new)));
+    let paren = between(lex(token('(')), token(')'), pipe());
+    let primary = choice((
+        dot(),
+        number().map(Filter::Literal),
+        string_literal().map(|text| Filter::Literal(Value::String(text))),
+        call(),
+        array,
+        object(),
+        paren,
+    ));
+    (lex(primary), many(suffix())).map(|(filter, suffixes): (Filter, Vec<Suffix>)| {
+        suffixes.into_iter().fold(filter, apply_suffix)
+    })
+}
+
+fn operator<Input>(symbol: &'static str, op: BinaryOp) -> impl Parser<Input, Output = BinaryOp>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    lex(attempt(string(symbol))).map(move |_| op)
+}
+
+fn keyword<Input>(word: &'static str, op: BinaryOp) -> impl Parser<Input, Output = BinaryOp>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    lex(attempt(string(word).skip(not_followed_by(alpha_num())))).map(move |_| op)
+}
+
+fn binary(op: BinaryOp) -> impl Fn(Filter, Filter) -> Filter {
+    move |left, right| Filter::Binary(op, Box::new(left), Box::new(right))
+}
+
+fn multiplicative<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let op = choice((
+        operator("*", BinaryOp::Mul),
+        operator("/", BinaryOp::Div),
+        operator("%", BinaryOp::Rem),
+    ));
+    chainl1(term(), op.map(binary))
+}
+
+fn additive<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let op = choice((operator("+", BinaryOp::Add), operator("-", BinaryOp::Sub)));
+    chainl1(multiplicative(), op.map(binary))
+}
+
+fn comparison<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let op = choice((
+        operator("==", BinaryOp::Eq),
+        operator("!=", BinaryOp::Ne),
+        operator("<=", BinaryOp::Le),
+        operator(">=", BinaryOp::Ge),
+        operator("<", BinaryOp::Lt),
+        operator(">", BinaryOp::Gt),
+    ));
+    (additive(), optional((op, additive()))).map(|(left, right)| match right {
+        Some((op, right)) => binary(op)(left, right),
+        None => left,
+    })
+}
+
+fn and<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    chainl1(comparison(), keyword("and", BinaryOp::And).map(binary))
+}
+
+parser! {
+    /// Expression without `,` and `|`, as used for values of objects.
+    fn alternative[Input]()(Input) -> Filter
+    where [
+        Input: Stream<Token = char>,
+        Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    ]
+    {
+        chainl1(and(), keyword("or", BinaryOp::Or).map(binary))
+    }
+}
+
+fn comma<Input>() -> impl Parser<Input, Output = Filter>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    chainl1(
+        alternative(),
+        lex(token(',')).map(|_| |left, right| Filter::Comma(Box::new(left), Box::new(right))),
+    )
+}
+
+parser! {
+    fn pipe[Input]()(Input) -> Filter
+    where [
+        Input: Stream<Token = char>,
+        Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    ]
+    {
+        chainl1(
+            comma(),
+            lex(token('|')).map(|_| |left, right| Filter::Pipe(Box::new(left), Box::new(right))),
+        )
+    }
+}
+
+/// Parse a filter, like `.users[] | select(.uid > 1) | .email`.
+pub fn parse(input: &str) -> Result<Filter, String> {
+    (spaces(), pipe(), eof())
+        .map(|(_, filter, _)| filter)
+        .easy_parse(stream::position::Stream::new(input))
+        .map(|(filter, _)| filter)
+        .map_err(|err| {
+            let message = err
+                .errors
+                .first()
+                .map(|error| error.to_string().to_ascii_lowercase())
+                .unwrap_or_default();
+            format!(
+                "syntax error at column {}, {}",
+                err.position.column, message
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(filter: Filter, name: &str) -> Filter {
+        Filter::Index(Box::new(filter), Box::new(Filter::Literal(name.into())))
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(parse("."), Ok(Filter::Identity));
+        assert_eq!(parse(".users"), Ok(field(Filter::Identity, "users")));
+        assert_eq!(
+            parse(".users[0].name"),
+            Ok(field(
+                Filter::Index(
+                    Box::new(field(Filter::Identity, "users")),
+                    Box::new(Filter::Literal(0.into()))
+                ),
+                "name"
+            ))
+        );
+        assert_eq!(
+            parse(r#".["a b"].c"#),
+            Ok(field(
+                Filter::Index(
+                    Box::new(Filter::Identity),
+                    Box::new(Filter::Literal("a b".into()))
+                ),
+                "c"
+            ))
+        );
+        assert_eq!(
+            parse(".[] ?"),
+            Ok(Filter::Try(Box::new(Filter::Iterate(Box::new(
+                Filter::Identity
+            )))))
+        );
+        assert_eq!(
+            parse(".a.[]"),
+            Ok(Filter::Iterate(Box::new(field(Filter::Identity, "a"))))
+        );
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(
+            parse(".[] | select(.a > 1 and .b) , 2"),
+            Ok(Filter::Pipe(
+                Box::new(Filter::Iterate(Box::new(Filter::Identity))),
+                Box::new(Filter::Comma(
+                    Box::new(Filter::Call(
+                        "select".to_string(),
+                        vec![Filter::Binary(
+                            BinaryOp::And,
+                            Box::new(Filter::Binary(
+                                BinaryOp::Gt,
+                                Box::new(field(Filter::Identity, "a")),
+                                Box::new(Filter::Literal(1.into()))
+                            )),
+                            Box::new(field(Filter::Identity, "b"))
+                        )]
+                    )),
+                    Box::new(Filter::Literal(2.into()))
+                ))
+            ))
+        );
+        assert_eq!(
+            parse("1 + 2 * 3"),
+            Ok(Filter::Binary(
+                BinaryOp::Add,
+                Box::new(Filter::Literal(1.into())),
+                Box::new(Filter::Binary(
+                    BinaryOp::Mul,
+                    Box::new(Filter::Literal(2.into())),
+                    Box::new(Filter::Literal(3.into()))
+                ))
+            ))
+        );
+        assert_eq!(parse(".order"), Ok(field(Filter::Identity, "order")));
+    }
+
+    #[test]
+    fn constructors() {
+        assert_eq!(
+            parse("{name, id: .uid}"),
+            Ok(Filter::Object(vec![
+                (
+                    Filter::Literal("name".into()),
+                    field(Filter::Identity, "name")
+                ),
+                (Filter::Literal("id".into()), field(Filter::Identity, "uid")),
+            ]))
+        );
+        assert_eq!(
+            parse("[.[] | -1.5, \"a\\n\", null]"),
+            Ok(Filter::Array(Some(Box::new(Filter::Pipe(
+                Box::new(Filter::Iterate(Box::new(Filter::Identity))),
+                Box::new(Filter::Comma(
+                    Box::new(Filter::Comma(
+                        Box::new(Filter::Literal((-1.5).into())),
+                        Box::new(Filter::Literal("a\n".into()))
+                    )),
+                    Box::new(Filter::Literal(Value::Null))
+                ))
+            )))))
+        );
+        assert_eq!(parse("[]"), Ok(Filter::Array(None)));
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert!(parse(".a |")
+            .unwrap_err()
+            .starts_with("syntax error at column 5"));
+        assert!(parse(".[").is_err());
+        assert!(parse("").is_err());
+    }
+}
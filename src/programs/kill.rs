@@ -0,0 +1,77 @@
+use crate::shell::{executable::Builtin, jobs::JobTable, Argument, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub struct Kill {
+    table: Rc<RefCell<JobTable>>,
+}
+
+impl Kill {
+    pub fn new(table: Rc<RefCell<JobTable>>) -> Self {
+        Kill { table }
+    }
+}
+
+impl Builtin for Kill {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        if arguments.is_empty() {
+            stdio.print("kill: usage: kill %job ...\r\n");
+            return 2;
+        }
+
+        let mut status = 0;
+        arguments.iter().for_each(|argument| match argument {
+            Argument::Text(spec) if spec.starts_with('%') => match self.table.borrow().get(spec) {
+                Some(job) => job.terminate(),
+                None => {
+                    stdio.print(&format!("kill: {}: no such job\r\n", spec));
+                    status = 1;
+                }
+            },
+            Argument::Text(spec) => {
+                stdio.print(&format!("kill: {}: arguments must be job IDs\r\n", spec));
+                status = 1;
+            }
+            Argument::Switch(key, ..) => {
+                stdio.print(&format!("kill: invalid argument: {}\r\n", key));
+                status = 1;
+            }
+        });
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::jobs::JobState;
+    use crate::terminal::Terminal;
+    use std::collections::HashMap;
+
+    #[test]
+    fn run() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Rc::new(Stdio::new(Rc::clone(&terminal)));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let table = Rc::new(RefCell::new(JobTable::new()));
+        let job = table
+            .borrow_mut()
+            .add("curl".to_string(), Rc::new(stdio.detach()));
+
+        let program = Kill::new(Rc::clone(&table));
+        let arguments = vec![
+            Argument::Text("%1".to_string()),
+            Argument::Text("%2".to_string()),
+            Argument::Text("1".to_string()),
+        ];
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 1);
+        assert_eq!(job.state(), JobState::Terminated);
+        assert_eq!(
+            "kill: %2: no such job\r\nkill: 1: arguments must be job IDs\r\n",
+            &terminal.get()
+        );
+    }
+}
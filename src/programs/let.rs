@@ -0,0 +1,112 @@
+use crate::parser;
+use crate::shell::{arithmetic, executable::Builtin, Argument, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+
+pub struct Let;
+
+impl Builtin for Let {
+    fn run(
+        &self,
+        stdio: &Stdio,
+        _: &mut Executables,
+        globals: &mut Vars,
+        arguments: Arguments,
+    ) -> i32 {
+        // Like bash, the status tells whether the last value is zero.
+        let mut last = 0;
+        for argument in arguments {
+            let text = match argument {
+                Argument::Text(text) => text,
+                Argument::Switch(key, ..) => {
+                    stdio.print(&format!("let: invalid argument: {}\r\n", key));
+                    return 1;
+                }
+            };
+
+            let assignment = match parser::parse_assignment(&text) {
+                Ok(assignment) => assignment,
+                Err(err) => {
+                    stdio.print(&format!("let: {}\r\n", parser::describe_error(&err)));
+                    return 1;
+                }
+            };
+            match arithmetic::evaluate(&assignment.expr, globals) {
+                Ok(value) => {
+                    last = value;
+                    if let Some(name) = assignment.name {
+                        globals.insert(name.name, value.to_string());
+                    }
+                }
+                Err(err) => {
+                    stdio.print(&format!("let: {}\r\n", err));
+                    return 1;
+                }
+            }
+        }
+        (last == 0) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Terminal;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    #[test]
+    fn assign() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let arguments = vec![
+            Argument::Text("a=1+2".to_string()),
+            Argument::Text("b = a * (a - 1)".to_string()),
+        ];
+
+        let program = Let;
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 0);
+        assert_eq!(Some(&"3".to_string()), globals.get("a"));
+        assert_eq!(Some(&"6".to_string()), globals.get("b"));
+        assert_eq!("", &terminal.get());
+
+        let arguments = vec![Argument::Text("c = a - 3".to_string())];
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 1);
+    }
+
+    #[test]
+    fn syntax_error() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let arguments = vec![Argument::Text("a=1+".to_string())];
+
+        let program = Let;
+        program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert!(terminal
+            .get()
+            .starts_with("let: syntax error at line 1, column 5"));
+        assert_eq!(None, globals.get("a"));
+    }
+
+    #[test]
+    fn evaluation_error() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let arguments = vec![Argument::Text("a=1/0".to_string())];
+
+        let program = Let;
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        assert_eq!(status, 1);
+        assert_eq!(
+            "let: division by zero at line 1, column 4\r\n",
+            &terminal.get()
+        );
+    }
+}
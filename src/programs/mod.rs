@@ -1,9 +1,70 @@
+mod base64;
 mod clear;
 mod curl;
+mod cut;
 mod echo;
 mod export;
+mod fg;
+mod grep;
+mod hash;
+mod head;
+mod hexdump;
+mod jobs;
+mod jq;
+mod kill;
+mod r#let;
+mod printf;
+mod set;
+mod skin;
+mod sort;
+mod tail;
+mod test;
+mod text;
+mod tr;
+mod uniq;
+mod url;
+mod uuid;
+mod wait;
+mod wc;
 
+pub(crate) use self::base64::Base64;
 pub(crate) use clear::Clear;
 pub(crate) use curl::Curl;
+pub(crate) use cut::Cut;
 pub(crate) use echo::Echo;
 pub(crate) use export::Export;
+pub(crate) use fg::Fg;
+pub(crate) use grep::Grep;
+pub(crate) use hash::Hash;
+pub(crate) use head::Head;
+pub(crate) use hexdump::Hexdump;
+pub(crate) use jobs::Jobs;
+pub(crate) use jq::Jq;
+pub(crate) use kill::Kill;
+pub(crate) use printf::Printf;
+pub(crate) use r#let::Let;
+pub(crate) use set::Set;
+pub(crate) use skin::Skin;
+pub(crate) use sort::Sort;
+pub(crate) use tail::Tail;
+pub(crate) use test::Test;
+pub(crate) use tr::Tr;
+pub(crate) use uniq::Uniq;
+pub(crate) use url::Url;
+pub(crate) use uuid::Uuid;
+pub(crate) use wait::Wait;
+pub(crate) use wc::Wc;
+
+use crate::shell::Argument;
+
+/// Write an argument back as it was typed, like `-42` or `--key=value`,
+/// for programs which take switches as text.
+fn argument_text(argument: Argument) -> String {
+    match argument {
+        Argument::Text(text) => text,
+        Argument::Switch(key, Some(value), prefix) => {
+            format!("{}{}={}", prefix.dashes(), key, value)
+        }
+        Argument::Switch(key, None, prefix) => format!("{}{}", prefix.dashes(), key),
+    }
+}
@@ -0,0 +1,405 @@
+use super::argument_text;
+use crate::format;
+use crate::shell::{executable::Builtin, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Print arguments with a format, like `printf '%-10s %05d\n' kumiko 42`.
+/// The format is reused until all arguments are consumed.
+pub struct Printf;
+
+/// Interpret backslash escapes like `\n`, `\t` and `\e`.
+/// The second value is `false` if `\c` is found, which means no more output.
+pub fn unescape(text: &str) -> (String, bool) {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => output.push('\\'),
+            Some('a') => output.push('\x07'),
+            Some('b') => output.push('\x08'),
+            Some('e') => output.push('\x1b'),
+            Some('f') => output.push('\x0c'),
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some('t') => output.push('\t'),
+            Some('v') => output.push('\x0b'),
+            Some('c') => return (output, false),
+            Some('x') => match take_digits(&mut chars, 16, 2) {
+                Some(code) => output.push(code),
+                None => output.push_str("\\x"),
+            },
+            Some('0') => output.push(take_digits(&mut chars, 8, 3).unwrap_or('\0')),
+            Some(c) => {
+                output.push('\\');
+                output.push(c);
+            }
+            None => output.push('\\'),
+        }
+    }
+    (output, true)
+}
+
+fn take_digits(chars: &mut Peekable<Chars>, radix: u32, max: usize) -> Option<char> {
+    let mut digits = String::new();
+    while let Some(c) = chars
+        .peek()
+        .filter(|c| c.is_digit(radix) && digits.len() < max)
+    {
+        digits.push(*c);
+        chars.next();
+    }
+    u32::from_str_radix(&digits, radix)
+        .ok()
+        .and_then(std::char::from_u32)
+}
+
+fn number(chars: &mut Peekable<Chars>) -> usize {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+        digits.push(*c);
+        chars.next();
+    }
+    digits.parse().unwrap_or(0)
+}
+
+/// Flags, width and precision of a directive like `%-10s` or `%05.2f`.
+/// Width and precision can be `*`, which are taken from arguments.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    fn parse(chars: &mut Peekable<Chars>, mut argument: impl FnMut() -> i64) -> Spec {
+        let mut spec = Spec::default();
+        while let Some(c) = chars.peek() {
+            match c {
+                '-' => spec.left = true,
+                '0' => spec.zero = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        if chars.next_if_eq(&'*').is_some() {
+            // A negative width means left alignment.
+            let width = argument();
+            spec.left |= width < 0;
+            spec.width = width.unsigned_abs() as usize;
+        } else {
+            spec.width = number(chars);
+        }
+        if chars.next_if_eq(&'.').is_some() {
+            spec.precision = if chars.next_if_eq(&'*').is_some() {
+                // A negative precision is ignored.
+                let precision = argument();
+                (precision >= 0).then_some(precision as usize)
+            } else {
+                Some(number(chars))
+            };
+        }
+        spec
+    }
+
+    fn pad(&self, text: String) -> String {
+        let padding = " ".repeat(self.width.saturating_sub(format::width(&text)));
+        if self.left {
+            text + &padding
+        } else {
+            padding + &text
+        }
+    }
+
+    /// Pad a number, where zeros are put after the sign.
+    fn pad_number(&self, negative: bool, digits: String) -> String {
+        let sign = match (negative, self.plus, self.space) {
+            (true, _, _) => "-",
+            (false, true, _) => "+",
+            (false, false, true) => " ",
+            _ => "",
+        };
+        if self.zero && !self.left {
+            let zeros = "0".repeat(self.width.saturating_sub(sign.len() + digits.len()));
+            format!("{}{}{}", sign, zeros, digits)
+        } else {
+            self.pad(format!("{}{}", sign, digits))
+        }
+    }
+}
+
+/// State of formatting, which consumes arguments.
+struct Formatter<'a> {
+    arguments: &'a [String],
+    next: usize,
+    errors: Vec<String>,
+}
+
+impl<'a> Formatter<'a> {
+    fn next_argument(&mut self) -> Option<&'a str> {
+        let argument = self.arguments.get(self.next).map(String::as_str);
+        self.next += 1;
+        argument
+    }
+
+    fn integer(&mut self) -> i64 {
+        let argument = self.next_argument().unwrap_or("0").trim();
+        let (negative, digits) = match argument.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, argument.strip_prefix('+').unwrap_or(argument)),
+        };
+        let value = match digits
+            .strip_prefix("0x")
+            .or_else(|| digits.strip_prefix("0X"))
+        {
+            Some(hex) => i64::from_str_radix(hex, 16),
+            None => digits.parse(),
+        };
+        match value {
+            Ok(value) if negative => -value,
+            Ok(value) => value,
+            Err(_) => {
+                self.errors
+                    .push(format!("printf: '{}': invalid number", argument));
+                0
+            }
+        }
+    }
+
+    fn float(&mut self) -> f64 {
+        let argument = self.next_argument().unwrap_or("0").trim();
+        argument.parse().unwrap_or_else(|_| {
+            self.errors
+                .push(format!("printf: '{}': invalid number", argument));
+            0.0
+        })
+    }
+
+    /// Format once, and return `false` if `\c` is found.
+    fn format(&mut self, format: &str, output: &mut String) -> bool {
+        let mut chars = format.chars().peekable();
+        let mut literal = String::new();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let (text, more) = unescape(&literal);
+            output.push_str(&text);
+            literal.clear();
+            if !more {
+                return false;
+            }
+
+            let spec = Spec::parse(&mut chars, || self.integer());
+            let text = match chars.next() {
+                Some('%') => "%".to_string(),
+                Some('s') => {
+                    let text = self.next_argument().unwrap_or_default();
+                    let text = match spec.precision {
+                        Some(precision) => text.chars().take(precision).collect(),
+                        None => text.to_string(),
+                    };
+                    spec.pad(text)
+                }
+                Some('b') => {
+                    let (text, more) = unescape(self.next_argument().unwrap_or_default());
+                    output.push_str(&spec.pad(text));
+                    if !more {
+                        return false;
+                    }
+                    continue;
+                }
+                Some('c') => {
+                    let text = self.next_argument().unwrap_or_default();
+                    spec.pad(text.chars().take(1).collect())
+                }
+                Some('d') | Some('i') => {
+                    let value = self.integer();
+                    spec.pad_number(value < 0, value.unsigned_abs().to_string())
+                }
+                Some('x') => {
+                    let value = self.integer();
+                    spec.pad_number(false, format!("{:x}", value))
+                }
+                Some('X') => {
+                    let value = self.integer();
+                    spec.pad_number(false, format!("{:X}", value))
+                }
+                Some('o') => {
+                    let value = self.integer();
+                    spec.pad_number(false, format!("{:o}", value))
+                }
+                Some('f') | Some('F') => {
+                    let value = self.float();
+                    let precision = spec.precision.unwrap_or(6);
+                    let digits = format!("{:.*}", precision, value.abs());
+                    spec.pad_number(value.is_sign_negative() && value != 0.0, digits)
+                }
+                Some(c) => {
+                    self.errors
+                        .push(format!("printf: %{}: invalid directive", c));
+                    continue;
+                }
+                None => {
+                    self.errors.push("printf: missing directive".to_string());
+                    continue;
+                }
+            };
+            output.push_str(&text);
+        }
+        let (text, more) = unescape(&literal);
+        output.push_str(&text);
+        more
+    }
+}
+
+impl Builtin for Printf {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let mut arguments = arguments.into_iter().map(argument_text);
+        let format = match arguments.next() {
+            Some(format) => format,
+            None => {
+                stdio.println("printf: usage: printf <format> [arguments...]");
+                return 2;
+            }
+        };
+        let arguments = arguments.collect::<Vec<_>>();
+
+        let mut formatter = Formatter {
+            arguments: &arguments,
+            next: 0,
+            errors: vec![],
+        };
+        let mut output = String::new();
+        loop {
+            let start = formatter.next;
+            if !formatter.format(&format, &mut output) {
+                break;
+            }
+            // Reuse the format only if it consumes arguments.
+            if formatter.next >= arguments.len() || formatter.next == start {
+                break;
+            }
+        }
+
+        stdio.print(&output.replace('\n', "\r\n"));
+        formatter
+            .errors
+            .iter()
+            .for_each(|error| stdio.println(error));
+        if formatter.errors.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::run;
+    use super::*;
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            unescape("a\\tb\\nc\\e[1m\\\\"),
+            ("a\tb\nc\x1b[1m\\".to_string(), true)
+        );
+        assert_eq!(unescape("\\x41\\0101\\q"), ("AA\\q".to_string(), true));
+        assert_eq!(unescape("a\\cb"), ("a".to_string(), false));
+    }
+
+    #[test]
+    fn directives() {
+        assert_eq!(
+            run(&Printf, None, &["%s=%d\\n", "a", "42"]),
+            (0, "a=42\n".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["[%-10s]", "kumiko"]),
+            (0, "[kumiko    ]".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["[%10s]", "久美子"]),
+            (0, "[    久美子]".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%05d|%-4d|%+d", "42", "7", "3"]),
+            (0, "00042|7   |+3".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%05d", "-42"]),
+            (0, "-0042".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%x %X %o", "255", "0xff", "8"]),
+            (0, "ff FF 10".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%f %.2f %6.1f", "1.5", "2", "-3.14"]),
+            (0, "1.500000 2.00   -3.1".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["100%% %.3s %c", "kumiko", "reina"]),
+            (0, "100% kum r".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%b", "a\\tb"]),
+            (0, "a\tb".to_string())
+        );
+        assert_eq!(
+            run(
+                &Printf,
+                None,
+                &["[%*d|%-*s|%.*f]", "5", "42", "4", "a", "1", "3.14159"]
+            ),
+            (0, "[   42|a   |3.1]".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["[%*d|%.*s]", "-3", "7", "-1", "ab"]),
+            (0, "[7  |ab]".to_string())
+        );
+    }
+
+    #[test]
+    fn reuse_format() {
+        assert_eq!(
+            run(&Printf, None, &["%s:%d\\n", "a", "1", "b", "2", "c"]),
+            (0, "a:1\nb:2\nc:0\n".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["hi\\n", "a", "b"]),
+            (0, "hi\n".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%s\\c%s", "a", "b"]),
+            (0, "a".to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(run(&Printf, None, &[]).0, 2);
+        assert_eq!(
+            run(&Printf, None, &["%d\\n", "abc"]),
+            (1, "0\nprintf: 'abc': invalid number\n".to_string())
+        );
+        assert_eq!(
+            run(&Printf, None, &["%y"]),
+            (1, "printf: %y: invalid directive\n".to_string())
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::shell::keymap::{self, Keymap};
+use crate::shell::{executable::Builtin, Argument, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Set options of the shell. Only `-o emacs` and `-o vi` are supported,
+/// which choose key bindings of line editing.
+pub struct Set {
+    keymap: Rc<RefCell<Box<dyn Keymap>>>,
+}
+
+impl Set {
+    pub fn new(keymap: Rc<RefCell<Box<dyn Keymap>>>) -> Self {
+        Set { keymap }
+    }
+
+    fn list(&self, stdio: &Stdio) {
+        let current = self.keymap.borrow().name();
+        keymap::NAMES.iter().for_each(|name| {
+            let state = if *name == current { "on" } else { "off" };
+            stdio.print(&format!("{:<15}\t{}\r\n", name, state));
+        });
+    }
+
+    fn set_option(&self, stdio: &Stdio, name: &str) -> i32 {
+        match keymap::named(name) {
+            Some(keymap) => {
+                self.keymap.replace(keymap);
+                0
+            }
+            None => {
+                stdio.print(&format!("set: {}: invalid option name\r\n", name));
+                1
+            }
+        }
+    }
+}
+
+impl Builtin for Set {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let mut arguments = arguments.into_iter();
+        match (arguments.next(), arguments.next()) {
+            (None, _) | (Some(Argument::Switch(_, None, _)), None) => {
+                self.list(stdio);
+                0
+            }
+            (Some(Argument::Switch(key, Some(name), _)), None) if key == "o" => {
+                self.set_option(stdio, &name)
+            }
+            (Some(Argument::Switch(key, None, _)), Some(Argument::Text(name))) if key == "o" => {
+                self.set_option(stdio, &name)
+            }
+            _ => {
+                stdio.print("set: usage: set [-o option]\r\n");
+                2
+            }
+        }
+    }
+
+    fn switches(&self) -> Option<&'static [&'static str]> {
+        Some(&["o"])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::run;
+    use super::*;
+
+    fn emacs() -> Rc<RefCell<Box<dyn Keymap>>> {
+        Rc::new(RefCell::new(Box::new(keymap::Emacs)))
+    }
+
+    #[test]
+    fn switch_keymap() {
+        let keymap = emacs();
+        let set = Set::new(Rc::clone(&keymap));
+        assert_eq!(run(&set, None, &["-o", "vi"]).0, 0);
+        assert_eq!(keymap.borrow().name(), "vi");
+
+        assert_eq!(run(&set, None, &["-o=emacs"]).0, 0);
+        assert_eq!(keymap.borrow().name(), "emacs");
+    }
+
+    #[test]
+    fn list_options() {
+        let set = Set::new(emacs());
+        assert_eq!(
+            run(&set, None, &["-o"]),
+            (0, "emacs          \ton\nvi             \toff\n".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_option() {
+        let keymap = emacs();
+        let set = Set::new(Rc::clone(&keymap));
+        assert_eq!(
+            run(&set, None, &["-o", "vim"]),
+            (1, "set: vim: invalid option name\n".to_string())
+        );
+        assert_eq!(keymap.borrow().name(), "emacs");
+
+        assert_eq!(run(&set, None, &["x"]).0, 2);
+    }
+}
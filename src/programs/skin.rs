@@ -0,0 +1,520 @@
+use super::text::{read_binary_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use ansi_term::{Color, Style};
+use png::{ColorType, Decoder, Transformations};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Inspect skin and cape textures, which are read from a file or standard input.
+///
+/// - `skin info [file]` prints dimensions, type, arm model and used overlays.
+/// - `skin face [file]` prints a preview of the face with colored half blocks.
+pub struct Skin {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Skin {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Skin { fs }
+    }
+}
+
+const USAGE: &str = "skin: usage: skin <info|face> [file]";
+
+type Pixel = [u8; 4];
+
+/// A rectangle in the 64x64 layout, as `(x, y, width, height)`.
+type Rect = (u32, u32, u32, u32);
+
+/// An image with 8-bit RGBA pixels.
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn decode(data: &[u8]) -> Result<Image, String> {
+        let mut decoder = Decoder::new(data);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            ColorType::Rgba => buffer,
+            ColorType::Rgb => buffer
+                .chunks(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            ColorType::GrayscaleAlpha => buffer
+                .chunks(2)
+                .flat_map(|gray| [gray[0], gray[0], gray[0], gray[1]])
+                .collect(),
+            _ => buffer
+                .iter()
+                .flat_map(|gray| [*gray, *gray, *gray, 255])
+                .collect(),
+        };
+        Ok(Image {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> Pixel {
+        let i = ((y * self.width + x) * 4) as usize;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[i..i + 4]);
+        pixel
+    }
+
+    /// Pixels in rectangles of the 64x64 layout, scaled for HD images.
+    fn pixels(&self, rects: &[Rect]) -> Vec<Pixel> {
+        let scale = self.width / 64;
+        rects
+            .iter()
+            .flat_map(|(x, y, width, height)| {
+                (y * scale..(y + height) * scale)
+                    .flat_map(move |y| (x * scale..(x + width) * scale).map(move |x| (x, y)))
+            })
+            .map(|(x, y)| self.pixel(x, y))
+            .collect()
+    }
+}
+
+/// Faces of a cuboid in the texture, where `(x, y)` is the top-left corner
+/// and the cuboid is `width` wide, `height` high and `depth` deep.
+fn cuboid(x: u32, y: u32, width: u32, height: u32, depth: u32) -> Vec<Rect> {
+    vec![
+        (x + depth, y, width * 2, depth),
+        (x, y + depth, (width + depth) * 2, height),
+    ]
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Skin,
+    /// The 64x32 layout before Minecraft 1.8, which is also valid as a cape.
+    Legacy,
+    Cape,
+    Invalid,
+}
+
+fn kind(image: &Image) -> Kind {
+    let (width, height) = (image.width, image.height);
+    if width > 0 && width % 64 == 0 && height == width {
+        Kind::Skin
+    } else if width > 0 && width % 64 == 0 && height * 2 == width {
+        Kind::Legacy
+    } else if width > 0 && width % 22 == 0 && height % 17 == 0 && width / 22 == height / 17 {
+        Kind::Cape
+    } else {
+        Kind::Invalid
+    }
+}
+
+/// Arms are 3 pixels wide in the Alex model, so the last column of arms is unused.
+/// Editors fill unused pixels with transparent, black or white.
+fn is_slim(image: &Image) -> bool {
+    let pixels = image.pixels(&[(50, 16, 2, 4), (54, 20, 2, 12)]);
+    pixels.iter().all(|pixel| pixel[3] == 0)
+        || pixels.iter().all(|pixel| *pixel == [0, 0, 0, 255])
+        || pixels.iter().all(|pixel| *pixel == [255, 255, 255, 255])
+}
+
+/// Names of overlay layers which have visible pixels.
+fn overlays(image: &Image, kind: &Kind) -> Vec<&'static str> {
+    let hat = image.pixels(&cuboid(32, 0, 8, 8, 8));
+    // Like Minecraft, a legacy hat without any transparent pixel is ignored.
+    let hat_used = match kind {
+        Kind::Legacy => {
+            hat.iter().any(|pixel| pixel[3] < 128) && hat.iter().any(|pixel| pixel[3] > 0)
+        }
+        _ => hat.iter().any(|pixel| pixel[3] > 0),
+    };
+    let mut overlays = vec![];
+    if hat_used {
+        overlays.push("hat");
+    }
+    if *kind != Kind::Skin {
+        return overlays;
+    }
+
+    let arm = if is_slim(image) { 3 } else { 4 };
+    let layers = [
+        ("jacket", vec![cuboid(16, 32, 8, 12, 4)]),
+        (
+            "sleeves",
+            vec![cuboid(40, 32, arm, 12, 4), cuboid(48, 48, arm, 12, 4)],
+        ),
+        (
+            "pants",
+            vec![cuboid(0, 32, 4, 12, 4), cuboid(0, 48, 4, 12, 4)],
+        ),
+    ];
+    for (name, parts) in layers.iter() {
+        if image
+            .pixels(&parts.concat())
+            .iter()
+            .any(|pixel| pixel[3] > 0)
+        {
+            overlays.push(name);
+        }
+    }
+    overlays
+}
+
+fn info(image: &Image) -> (i32, Vec<String>) {
+    let kind = kind(image);
+    let scale = image.width / 64;
+    let mut lines = vec![format!("dimensions: {}x{}", image.width, image.height)];
+    let description = match kind {
+        Kind::Skin if scale > 1 => format!("HD skin ({}x)", scale),
+        Kind::Skin => "skin".to_string(),
+        Kind::Legacy => "legacy skin, also valid as a cape".to_string(),
+        Kind::Cape => "cape".to_string(),
+        Kind::Invalid => "invalid, neither a skin nor a cape".to_string(),
+    };
+    lines.push(format!("type: {}", description));
+    match kind {
+        Kind::Invalid => return (1, lines),
+        Kind::Cape => return (0, lines),
+        Kind::Skin if is_slim(image) => lines.push("model: alex (slim arms)".to_string()),
+        _ => lines.push("model: steve".to_string()),
+    }
+    let overlays = overlays(image, &kind);
+    if overlays.is_empty() {
+        lines.push("overlay: none".to_string());
+    } else {
+        lines.push(format!("overlay: {}", overlays.join(", ")));
+    }
+    (0, lines)
+}
+
+/// Blend a pixel of the hat over a pixel of the head.
+fn blend(head: Pixel, hat: Pixel) -> Pixel {
+    let alpha = hat[3] as u32;
+    let mut pixel = [0, 0, 0, 255];
+    for i in 0..3 {
+        pixel[i] = ((hat[i] as u32 * alpha + head[i] as u32 * (255 - alpha)) / 255) as u8;
+    }
+    pixel
+}
+
+/// Render the face as 16x16 pixels, where a character shows two pixels
+/// by the foreground and background colors of `▀`.
+fn face(image: &Image) -> Vec<String> {
+    const SIZE: u32 = 16;
+    let hat = overlays(image, &kind(image)).contains(&"hat");
+    let scale = image.width / 64;
+    let pixel = |x: u32, y: u32| {
+        let (x, y) = (x * 8 * scale / SIZE, y * 8 * scale / SIZE);
+        let mut head = image.pixel(8 * scale + x, 8 * scale + y);
+        head[3] = 255;
+        if hat {
+            blend(head, image.pixel(40 * scale + x, 8 * scale + y))
+        } else {
+            head
+        }
+    };
+    let color = |pixel: Pixel| Color::RGB(pixel[0], pixel[1], pixel[2]);
+
+    (0..SIZE / 2)
+        .map(|row| {
+            (0..SIZE)
+                .map(|x| {
+                    let (top, bottom) = (pixel(x, row * 2), pixel(x, row * 2 + 1));
+                    Style::new()
+                        .fg(color(top))
+                        .on(color(bottom))
+                        .paint("▀")
+                        .to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl Builtin for Skin {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("skin", arguments, "", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (command, files) = match options.operands.split_first() {
+            Some((command, files)) if files.len() <= 1 => (command.as_str(), files),
+            _ => {
+                stdio.println(USAGE);
+                return 2;
+            }
+        };
+        if command != "info" && command != "face" {
+            stdio.println(USAGE);
+            return 2;
+        }
+        let input = match read_binary_inputs("skin", stdio, &self.fs.borrow(), files) {
+            Ok(mut inputs) => inputs.remove(0),
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+        let name = input.name.as_deref().unwrap_or("-");
+        let image = match Image::decode(&input.data) {
+            Ok(image) => image,
+            Err(message) => {
+                stdio.println(&format!("skin: {}: invalid PNG image: {}", name, message));
+                return 1;
+            }
+        };
+
+        if command == "info" {
+            let (status, lines) = info(&image);
+            lines.iter().for_each(|line| stdio.println(line));
+            return status;
+        }
+        match kind(&image) {
+            Kind::Skin | Kind::Legacy => {
+                face(&image).iter().for_each(|line| stdio.println(line));
+                0
+            }
+            _ => {
+                stdio.println(&format!("skin: {}: not a skin", name));
+                1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run, run_binary};
+    use super::*;
+    use png::{BitDepth, Encoder};
+
+    fn image(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    fn fill(image: &mut Image, (x, y, width, height): Rect, pixel: Pixel) {
+        for y in y..y + height {
+            for x in x..x + width {
+                let i = ((y * image.width + x) * 4) as usize;
+                image.pixels[i..i + 4].copy_from_slice(&pixel);
+            }
+        }
+    }
+
+    fn encode(image: &Image) -> Vec<u8> {
+        let mut data = vec![];
+        let mut encoder = Encoder::new(&mut data, image.width, image.height);
+        encoder.set_color(ColorType::Rgba);
+        encoder.set_depth(BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&image.pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    /// A skin with opaque body parts in the Steve model.
+    fn steve() -> Image {
+        let mut image = image(64, 64);
+        fill(&mut image, (0, 0, 32, 16), [200, 150, 100, 255]);
+        fill(&mut image, (0, 16, 56, 16), [0, 0, 255, 255]);
+        fill(&mut image, (16, 48, 32, 16), [0, 0, 255, 255]);
+        image
+    }
+
+    fn lines(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    #[test]
+    fn decode() {
+        let mut skin = steve();
+        fill(&mut skin, (40, 8, 8, 8), [255, 0, 0, 128]);
+        let data = encode(&skin);
+        let image = Image::decode(&data).unwrap();
+        assert_eq!((image.width, image.height), (64, 64));
+        assert_eq!(image.pixel(8, 8), [200, 150, 100, 255]);
+        assert_eq!(image.pixel(40, 8), [255, 0, 0, 128]);
+        assert!(Image::decode(b"\x89PNG\r\n\x1a\n").is_err());
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(kind(&image(64, 64)), Kind::Skin);
+        assert_eq!(kind(&image(128, 128)), Kind::Skin);
+        assert_eq!(kind(&image(64, 32)), Kind::Legacy);
+        assert_eq!(kind(&image(44, 34)), Kind::Cape);
+        assert_eq!(kind(&image(64, 48)), Kind::Invalid);
+        assert_eq!(kind(&image(0, 0)), Kind::Invalid);
+    }
+
+    #[test]
+    fn models() {
+        let steve = steve();
+        assert!(!is_slim(&steve));
+
+        let mut alex = steve;
+        fill(&mut alex, (50, 16, 2, 4), [0, 0, 0, 0]);
+        fill(&mut alex, (54, 20, 2, 12), [0, 0, 0, 0]);
+        assert!(is_slim(&alex));
+        fill(&mut alex, (50, 16, 2, 4), [0, 0, 0, 255]);
+        fill(&mut alex, (54, 20, 2, 12), [0, 0, 0, 255]);
+        assert!(is_slim(&alex));
+    }
+
+    #[test]
+    fn overlay_layers() {
+        let mut skin = steve();
+        assert!(overlays(&skin, &Kind::Skin).is_empty());
+        fill(&mut skin, (40, 8, 1, 1), [0, 0, 0, 255]);
+        fill(&mut skin, (52, 52, 1, 1), [0, 0, 0, 255]);
+        assert_eq!(overlays(&skin, &Kind::Skin), vec!["hat", "sleeves"]);
+
+        // Unused corners aren't counted.
+        let mut skin = steve();
+        fill(&mut skin, (0, 32, 4, 4), [0, 0, 0, 255]);
+        assert!(overlays(&skin, &Kind::Skin).is_empty());
+
+        let mut legacy = image(64, 32);
+        fill(&mut legacy, (32, 0, 32, 16), [0, 0, 0, 255]);
+        assert!(overlays(&legacy, &Kind::Legacy).is_empty());
+        fill(&mut legacy, (40, 0, 1, 1), [0, 0, 0, 0]);
+        assert_eq!(overlays(&legacy, &Kind::Legacy), vec!["hat"]);
+    }
+
+    #[test]
+    fn inspect() {
+        let mut skin = steve();
+        fill(&mut skin, (16, 32, 24, 16), [0, 255, 0, 255]);
+        let fs = file_system(&[("skin.txt", "steve")]);
+        fs.borrow_mut().write("skin.png", encode(&skin)).unwrap();
+        let program = Skin::new(fs);
+        assert_eq!(
+            run(&program, None, &["info", "skin.png"]),
+            (
+                0,
+                lines(&[
+                    "dimensions: 64x64",
+                    "type: skin",
+                    "model: steve",
+                    "overlay: jacket",
+                ])
+            )
+        );
+
+        let mut alex = image(128, 128);
+        fill(&mut alex, (0, 0, 128, 128), [0, 0, 255, 255]);
+        fill(&mut alex, (100, 32, 4, 8), [0, 0, 0, 0]);
+        fill(&mut alex, (108, 40, 4, 24), [0, 0, 0, 0]);
+        let (status, output) = run_binary(&program, &encode(&alex), &["info"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            lines(&[
+                "dimensions: 128x128",
+                "type: HD skin (2x)",
+                "model: alex (slim arms)",
+                "overlay: hat, jacket, sleeves, pants",
+            ])
+        );
+
+        let (status, output) = run_binary(&program, &encode(&image(22, 17)), &["info"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            lines(&["dimensions: 22x17", "type: cape"])
+        );
+        let (status, output) = run_binary(&program, &encode(&image(64, 48)), &["info"]);
+        assert_eq!(status, 1);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            lines(&[
+                "dimensions: 64x48",
+                "type: invalid, neither a skin nor a cape",
+            ])
+        );
+    }
+
+    #[test]
+    fn face_preview() {
+        let mut skin = steve();
+        fill(&mut skin, (8, 8, 8, 4), [255, 0, 0, 255]);
+        fill(&mut skin, (40, 15, 8, 1), [0, 0, 0, 255]);
+        let face = face(&skin);
+        assert_eq!(face.len(), 8);
+        let red = Color::RGB(255, 0, 0);
+        let skin_color = Color::RGB(200, 150, 100);
+        let black = Color::RGB(0, 0, 0);
+        assert_eq!(
+            face[0],
+            Style::new()
+                .fg(red)
+                .on(red)
+                .paint("▀")
+                .to_string()
+                .repeat(16)
+        );
+        assert_eq!(
+            face[4],
+            Style::new()
+                .fg(skin_color)
+                .on(skin_color)
+                .paint("▀")
+                .to_string()
+                .repeat(16)
+        );
+        assert_eq!(
+            face[7],
+            Style::new()
+                .fg(black)
+                .on(black)
+                .paint("▀")
+                .to_string()
+                .repeat(16)
+        );
+
+        let program = Skin::new(file_system(&[]));
+        let (status, output) = run_binary(&program, &encode(&skin), &["face"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            ("▀".repeat(16) + "\n").repeat(8)
+        );
+        let (status, output) = run_binary(&program, &encode(&image(22, 17)), &["face"]);
+        assert_eq!(
+            (status, String::from_utf8(output).unwrap()),
+            (1, "skin: -: not a skin\n".to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let program = Skin::new(file_system(&[("skin.txt", "steve")]));
+        assert_eq!(run(&program, None, &[]).0, 2);
+        assert_eq!(run(&program, None, &["render"]).0, 2);
+        assert_eq!(run(&program, None, &["info", "a.png", "b.png"]).0, 2);
+        assert_eq!(
+            run(&program, None, &["info", "a.png"]),
+            (1, "skin: a.png: No such file or directory\n".to_string())
+        );
+        let (status, output) = run(&program, None, &["info", "skin.txt"]);
+        assert_eq!(status, 1);
+        assert!(output.starts_with("skin: skin.txt: invalid PNG image: "));
+    }
+}
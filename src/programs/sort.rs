@@ -0,0 +1,120 @@
+use super::text::{read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// Sort lines of all inputs.
+pub struct Sort {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Sort {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Sort { fs }
+    }
+}
+
+/// Leading number of a line like `sort -n`, and lines without number are treated as 0.
+fn numeric_prefix(line: &str) -> f64 {
+    let line = line.trim_start();
+    let end = line
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || *c == '.' || (*i == 0 && *c == '-')))
+        .map(|(i, _)| i)
+        .unwrap_or_else(|| line.len());
+    line[..end].parse().unwrap_or_default()
+}
+
+impl Builtin for Sort {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("sort", arguments, "nruf", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let inputs = match read_inputs("sort", stdio, &self.fs.borrow(), &options.operands) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+
+        let compare = |a: &&str, b: &&str| {
+            if options.flag('n') {
+                let (a, b) = (numeric_prefix(a), numeric_prefix(b));
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            } else if options.flag('f') {
+                a.to_lowercase().cmp(&b.to_lowercase())
+            } else {
+                a.cmp(b)
+            }
+        };
+        let mut lines = inputs
+            .iter()
+            .flat_map(|input| input.text.lines())
+            .collect::<Vec<_>>();
+        // Lines which are equal by keys are still ordered by the whole line.
+        lines.sort_by(|a, b| compare(a, b).then_with(|| a.cmp(b)));
+        if options.flag('u') {
+            lines.dedup_by(|a, b| compare(a, b) == Ordering::Equal);
+        }
+        if options.flag('r') {
+            lines.reverse();
+        }
+        lines.iter().for_each(|line| stdio.println(line));
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    #[test]
+    fn sort_lines() {
+        let sort = Sort::new(file_system(&[]));
+        let input = Some("reina\nKumiko\nhazuki\nreina\n");
+        assert_eq!(
+            run(&sort, input, &[]),
+            (0, "Kumiko\nhazuki\nreina\nreina\n".to_string())
+        );
+        assert_eq!(
+            run(&sort, input, &["-ru"]),
+            (0, "reina\nhazuki\nKumiko\n".to_string())
+        );
+        assert_eq!(
+            run(&sort, input, &["-f"]),
+            (0, "hazuki\nKumiko\nreina\nreina\n".to_string())
+        );
+    }
+
+    #[test]
+    fn numeric() {
+        let sort = Sort::new(file_system(&[]));
+        let input = Some("10 a\n9 b\n-1 c\nx\n2.5\n");
+        assert_eq!(
+            run(&sort, input, &["-n"]),
+            (0, "-1 c\nx\n2.5\n9 b\n10 a\n".to_string())
+        );
+        assert_eq!(
+            run(&sort, Some("1\n01\n2\n"), &["-n", "-u"]),
+            (0, "01\n2\n".to_string())
+        );
+    }
+
+    #[test]
+    fn files() {
+        let sort = Sort::new(file_system(&[("a.txt", "b\na\n"), ("b.txt", "c\n")]));
+        assert_eq!(
+            run(&sort, None, &["-r", "a.txt", "b.txt"]),
+            (0, "c\nb\na\n".to_string())
+        );
+        assert_eq!(run(&sort, None, &["-k"]).0, 2);
+    }
+}
@@ -0,0 +1,103 @@
+use super::text::{parse_count, read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Print the last lines of input, which are 10 lines by default.
+/// `-n +N` prints lines starting from the N-th line.
+pub struct Tail {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Tail {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Tail { fs }
+    }
+}
+
+/// Which lines to print.
+enum Start {
+    Last(usize),
+    From(usize),
+}
+
+impl Builtin for Tail {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = Options::parse("tail", arguments, "", "n").and_then(|options| {
+            let start = match options.value('n').unwrap_or("10") {
+                count if count.starts_with('+') => Start::From(parse_count("tail", &count[1..])?),
+                count => Start::Last(parse_count("tail", count)?),
+            };
+            Ok((options, start))
+        });
+        let (options, start) = match options {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let inputs = match read_inputs("tail", stdio, &self.fs.borrow(), &options.operands) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+
+        let with_name = inputs.len() > 1;
+        for (i, input) in inputs.iter().enumerate() {
+            if let Some(name) = input.name.as_ref().filter(|_| with_name) {
+                let separator = if i > 0 { "\r\n" } else { "" };
+                stdio.println(&format!("{}==> {} <==", separator, name));
+            }
+            let lines = input.text.lines().collect::<Vec<_>>();
+            let skip = match start {
+                Start::Last(count) => lines.len().saturating_sub(count),
+                Start::From(line) => line.saturating_sub(1),
+            };
+            lines.iter().skip(skip).for_each(|line| stdio.println(line));
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    #[test]
+    fn last_lines() {
+        let tail = Tail::new(file_system(&[]));
+        let input = (1..=12).map(|i| format!("{}\n", i)).collect::<String>();
+        assert_eq!(
+            run(&tail, Some(&input), &[]),
+            (0, "3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n".to_string())
+        );
+        assert_eq!(
+            run(&tail, Some(&input), &["-n", "2"]),
+            (0, "11\n12\n".to_string())
+        );
+        assert_eq!(
+            run(&tail, Some("a\nb"), &["-n", "5"]),
+            (0, "a\nb\n".to_string())
+        );
+        assert_eq!(
+            run(&tail, Some(&input), &["-n", "+11"]),
+            (0, "11\n12\n".to_string())
+        );
+        assert_eq!(run(&tail, Some(&input), &["-n", "+x"]).0, 2);
+    }
+
+    #[test]
+    fn files() {
+        let tail = Tail::new(file_system(&[("a.txt", "a\nb\n"), ("b.txt", "c\n")]));
+        assert_eq!(
+            run(&tail, None, &["-n", "1", "a.txt", "b.txt"]),
+            (0, "==> a.txt <==\nb\n\n==> b.txt <==\nc\n".to_string())
+        );
+        assert_eq!(run(&tail, None, &["a.txt"]), (0, "a\nb\n".to_string()));
+    }
+}
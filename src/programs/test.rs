@@ -0,0 +1,254 @@
+use super::argument_text;
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Evaluate a conditional expression, and exit with 0 if it's true or 1 if it's false.
+/// It's also available as `[ ... ]`, which requires `]` at the end.
+pub struct Test {
+    fs: Rc<RefCell<FileSystem>>,
+    bracket: bool,
+}
+
+impl Test {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Test { fs, bracket: false }
+    }
+
+    /// The `[` form.
+    pub fn bracket(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Test { fs, bracket: true }
+    }
+}
+
+const UNARY: [&str; 6] = ["-z", "-n", "-e", "-f", "-d", "-s"];
+const BINARY: [&str; 11] = [
+    "=", "==", "!=", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge",
+];
+
+/// Recursive descent evaluator, where `-o` has lower precedence than `-a`.
+struct Evaluator<'a> {
+    tokens: &'a [String],
+    position: usize,
+    fs: &'a FileSystem,
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self, offset: usize) -> Option<&'a str> {
+        self.tokens.get(self.position + offset).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<&'a str, String> {
+        let token = self.peek(0).ok_or("argument expected")?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> Result<bool, String> {
+        let mut value = self.and()?;
+        while self.peek(0) == Some("-o") {
+            self.position += 1;
+            value |= self.and()?;
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut value = self.not()?;
+        while self.peek(0) == Some("-a") {
+            self.position += 1;
+            value &= self.not()?;
+        }
+        Ok(value)
+    }
+
+    fn not(&mut self) -> Result<bool, String> {
+        // A single `!` is a non-empty string.
+        if self.peek(0) == Some("!") && self.peek(1).is_some() {
+            self.position += 1;
+            return Ok(!self.not()?);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<bool, String> {
+        let token = self.next()?;
+        if let Some(operator) = self.peek(0).filter(|op| BINARY.contains(op)) {
+            if self.peek(1).is_some() {
+                self.position += 1;
+                let right = self.next()?;
+                return binary(operator, token, right);
+            }
+        }
+        if token == "(" && self.peek(1).is_some() {
+            let value = self.or()?;
+            return match self.next() {
+                Ok(")") => Ok(value),
+                _ => Err("')' expected".to_string()),
+            };
+        }
+        if UNARY.contains(&token) {
+            if let Some(operand) = self.peek(0) {
+                self.position += 1;
+                return Ok(self.unary(token, operand));
+            }
+        }
+        Ok(!token.is_empty())
+    }
+
+    fn unary(&self, operator: &str, operand: &str) -> bool {
+        match operator {
+            "-z" => operand.is_empty(),
+            "-n" => !operand.is_empty(),
+            "-e" => self.fs.exists(operand),
+            "-f" => self.fs.is_file(operand),
+            "-d" => self.fs.is_dir(operand),
+            _ => self.fs.read(operand).is_some_and(|data| !data.is_empty()),
+        }
+    }
+}
+
+fn integer(text: &str) -> Result<i64, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("{}: integer expression expected", text))
+}
+
+fn binary(operator: &str, left: &str, right: &str) -> Result<bool, String> {
+    Ok(match operator {
+        "=" | "==" => left == right,
+        "!=" => left != right,
+        "<" => left < right,
+        ">" => left > right,
+        operator => {
+            let (left, right) = (integer(left)?, integer(right)?);
+            match operator {
+                "-eq" => left == right,
+                "-ne" => left != right,
+                "-lt" => left < right,
+                "-le" => left <= right,
+                "-gt" => left > right,
+                _ => left >= right,
+            }
+        }
+    })
+}
+
+impl Builtin for Test {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let name = if self.bracket { "[" } else { "test" };
+        let mut tokens = arguments.into_iter().map(argument_text).collect::<Vec<_>>();
+        if self.bracket && tokens.pop().as_deref() != Some("]") {
+            stdio.println("[: missing ']'");
+            return 2;
+        }
+        if tokens.is_empty() {
+            return 1;
+        }
+
+        let fs = self.fs.borrow();
+        let mut evaluator = Evaluator {
+            tokens: &tokens,
+            position: 0,
+            fs: &fs,
+        };
+        let result = evaluator.or().and_then(|value| match evaluator.peek(0) {
+            Some(token) => Err(format!("{}: unexpected argument", token)),
+            None => Ok(value),
+        });
+        match result {
+            Ok(true) => 0,
+            Ok(false) => 1,
+            Err(message) => {
+                stdio.println(&format!("{}: {}", name, message));
+                2
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::run;
+    use super::*;
+
+    fn test() -> Test {
+        let mut fs = FileSystem::new();
+        fs.write("textures/skin.png", b"png".to_vec()).unwrap();
+        fs.write("empty.txt", vec![]).unwrap();
+        Test::new(Rc::new(RefCell::new(fs)))
+    }
+
+    #[test]
+    fn strings() {
+        let test = test();
+        assert_eq!(run(&test, None, &[]).0, 1);
+        assert_eq!(run(&test, None, &["a"]).0, 0);
+        assert_eq!(run(&test, None, &[""]).0, 1);
+        assert_eq!(run(&test, None, &["-n"]).0, 0);
+        assert_eq!(run(&test, None, &["-z", ""]).0, 0);
+        assert_eq!(run(&test, None, &["-n", ""]).0, 1);
+        assert_eq!(run(&test, None, &["kumiko", "=", "kumiko"]).0, 0);
+        assert_eq!(run(&test, None, &["kumiko", "!=", "kumiko"]).0, 1);
+        assert_eq!(run(&test, None, &["a", "<", "b"]).0, 0);
+        assert_eq!(run(&test, None, &["--a", "=", "--a"]).0, 0);
+        assert_eq!(run(&test, None, &["--a", "=", "-a"]).0, 1);
+    }
+
+    #[test]
+    fn integers() {
+        let test = test();
+        assert_eq!(run(&test, None, &["1", "-eq", "1"]).0, 0);
+        assert_eq!(run(&test, None, &["2", "-lt", "10"]).0, 0);
+        assert_eq!(run(&test, None, &["2", "-ge", "10"]).0, 1);
+        assert_eq!(run(&test, None, &["-3", "-ne", "3"]).0, 0);
+        assert_eq!(
+            run(&test, None, &["a", "-gt", "1"]),
+            (2, "test: a: integer expression expected\n".to_string())
+        );
+    }
+
+    #[test]
+    fn logical() {
+        let test = test();
+        assert_eq!(run(&test, None, &["!", "a", "=", "b"]).0, 0);
+        assert_eq!(run(&test, None, &["!"]).0, 0);
+        assert_eq!(run(&test, None, &["a", "-a", ""]).0, 1);
+        assert_eq!(run(&test, None, &["a", "-o", ""]).0, 0);
+        assert_eq!(run(&test, None, &["", "-o", "a", "-a", ""]).0, 1);
+        assert_eq!(run(&test, None, &["(", "", "-o", "a", ")", "-a", "b"]).0, 0);
+        assert_eq!(run(&test, None, &["(", "a"]).0, 2);
+        assert_eq!(
+            run(&test, None, &["a", "b"]),
+            (2, "test: b: unexpected argument\n".to_string())
+        );
+    }
+
+    #[test]
+    fn files() {
+        let test = test();
+        assert_eq!(run(&test, None, &["-e", "textures"]).0, 0);
+        assert_eq!(run(&test, None, &["-d", "textures"]).0, 0);
+        assert_eq!(run(&test, None, &["-f", "textures"]).0, 1);
+        assert_eq!(run(&test, None, &["-f", "/textures/skin.png"]).0, 0);
+        assert_eq!(run(&test, None, &["-e", "skin.png"]).0, 1);
+        assert_eq!(run(&test, None, &["-s", "textures/skin.png"]).0, 0);
+        assert_eq!(run(&test, None, &["-s", "empty.txt"]).0, 1);
+    }
+
+    #[test]
+    fn bracket() {
+        let test = Test::bracket(Rc::new(RefCell::new(FileSystem::new())));
+        assert_eq!(run(&test, None, &["a", "]"]).0, 0);
+        assert_eq!(run(&test, None, &["]"]).0, 1);
+        assert_eq!(
+            run(&test, None, &["a"]),
+            (2, "[: missing ']'\n".to_string())
+        );
+        assert_eq!(
+            run(&test, None, &["1", "-eq", "x", "]"]),
+            (2, "[: x: integer expression expected\n".to_string())
+        );
+    }
+}
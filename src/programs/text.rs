@@ -0,0 +1,314 @@
+//! Helpers shared by text-processing programs, like `grep` and `sort`.
+
+use super::argument_text;
+use crate::shell::{fs::FileSystem, transform::Prefix, Argument, Arguments};
+use crate::stdio::Stdio;
+use std::collections::HashMap;
+
+/// Options and operands of a program, parsed in the style of POSIX utilities.
+/// Flags can be combined like `-iv`, and a value is either attached like `-n5`,
+/// given after `=` or the next argument.
+#[derive(Debug, Default)]
+pub struct Options {
+    flags: Vec<char>,
+    values: HashMap<char, String>,
+    pub operands: Vec<String>,
+}
+
+impl Options {
+    /// Parse arguments with given flags, and options which take a value.
+    pub fn parse(
+        program: &str,
+        arguments: Arguments,
+        flags: &str,
+        valued: &str,
+    ) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            let (key, mut value) = match argument {
+                Argument::Text(text) => {
+                    options.operands.push(text);
+                    continue;
+                }
+                argument @ Argument::Switch(_, _, Prefix::Long) => {
+                    return Err(format!(
+                        "{}: unrecognized option '{}'",
+                        program,
+                        argument_text(argument)
+                    ))
+                }
+                Argument::Switch(key, value, Prefix::Short) => (key, value),
+            };
+            let mut chars = key.chars();
+            while let Some(c) = chars.next() {
+                if valued.contains(c) {
+                    let rest = chars.as_str();
+                    let value = match value.take() {
+                        Some(value) if rest.is_empty() => value,
+                        value if !rest.is_empty() => {
+                            let attached = value.map(|value| format!("={}", value));
+                            format!("{}{}", rest, attached.unwrap_or_default())
+                        }
+                        _ => match arguments.next() {
+                            Some(Argument::Text(text)) => text,
+                            // Values like `-2` in `cut -f -2` look like switches.
+                            Some(argument @ Argument::Switch(_, None, _)) => {
+                                argument_text(argument)
+                            }
+                            _ => {
+                                return Err(format!(
+                                    "{}: option requires an argument -- '{}'",
+                                    program, c
+                                ))
+                            }
+                        },
+                    };
+                    options.values.insert(c, value);
+                    break;
+                } else if flags.contains(c) {
+                    options.flags.push(c);
+                } else {
+                    return Err(format!("{}: invalid option -- '{}'", program, c));
+                }
+            }
+            if let Some(value) = value {
+                return Err(format!(
+                    "{}: option '{}' doesn't allow an argument: {}",
+                    program, key, value
+                ));
+            }
+        }
+        Ok(options)
+    }
+
+    pub fn flag(&self, flag: char) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    pub fn value(&self, option: char) -> Option<&str> {
+        self.values.get(&option).map(String::as_str)
+    }
+}
+
+/// Content to process, with the file name if it's from a file.
+pub struct Input {
+    pub name: Option<String>,
+    pub text: String,
+}
+
+/// Raw content to process, for programs which work on binary data like `hash`.
+pub struct BinaryInput {
+    pub name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Read given files from the virtual file system, or standard input if none is given.
+/// `-` also means standard input.
+pub fn read_inputs(
+    program: &str,
+    stdio: &Stdio,
+    fs: &FileSystem,
+    files: &[String],
+) -> Result<Vec<Input>, String> {
+    let inputs = read_binary_inputs(program, stdio, fs, files)?;
+    Ok(inputs
+        .into_iter()
+        .map(|input| Input {
+            name: input.name,
+            text: String::from_utf8_lossy(&input.data).into_owned(),
+        })
+        .collect())
+}
+
+/// Like `read_inputs`, but content is kept as bytes.
+pub fn read_binary_inputs(
+    program: &str,
+    stdio: &Stdio,
+    fs: &FileSystem,
+    files: &[String],
+) -> Result<Vec<BinaryInput>, String> {
+    if files.is_empty() {
+        let data = stdio.stdin_bytes().unwrap_or_default();
+        return Ok(vec![BinaryInput { name: None, data }]);
+    }
+    files
+        .iter()
+        .map(|file| match file.as_str() {
+            "-" => Ok(BinaryInput {
+                name: Some("(standard input)".to_string()),
+                data: stdio.stdin_bytes().unwrap_or_default(),
+            }),
+            path => match fs.read(path) {
+                Some(data) => Ok(BinaryInput {
+                    name: Some(path.to_string()),
+                    data: data.to_vec(),
+                }),
+                None if fs.is_dir(path) => Err(format!("{}: {}: Is a directory", program, path)),
+                None => Err(format!("{}: {}: No such file or directory", program, path)),
+            },
+        })
+        .collect()
+}
+
+/// Parse a count like `head -n 5`.
+pub fn parse_count(program: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{}: invalid number: '{}'", program, value))
+}
+
+#[cfg(test)]
+pub mod testing {
+    use crate::shell::{executable::Builtin, fs::FileSystem, transform::Prefix, Argument};
+    use crate::stdio::Stdio;
+    use crate::terminal::Terminal;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    fn parse_argument(argument: &str) -> Argument {
+        let (prefix, switch) = match argument.strip_prefix("--") {
+            Some(switch) => (Prefix::Long, Some(switch)),
+            None => (Prefix::Short, argument.strip_prefix('-')),
+        };
+        match switch {
+            Some(switch) if !switch.is_empty() => match switch.split_once('=') {
+                Some((key, value)) => {
+                    Argument::Switch(key.to_string(), Some(value.to_string()), prefix)
+                }
+                None => Argument::Switch(switch.to_string(), None, prefix),
+            },
+            _ => Argument::Text(argument.to_string()),
+        }
+    }
+
+    /// Run a program with arguments written like in a shell, and return status and output.
+    pub fn run(program: &dyn Builtin, input: Option<&str>, arguments: &[&str]) -> (i32, String) {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal))
+            .capture()
+            .with_input(input.map(String::from));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let arguments = arguments
+            .iter()
+            .map(|argument| parse_argument(argument))
+            .collect();
+
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        (status, stdio.captured())
+    }
+
+    pub fn file_system(files: &[(&str, &str)]) -> Rc<std::cell::RefCell<FileSystem>> {
+        let mut fs = FileSystem::new();
+        for (path, content) in files {
+            fs.write(path, content.as_bytes().to_vec()).unwrap();
+        }
+        Rc::new(std::cell::RefCell::new(fs))
+    }
+
+    /// Run a program with binary standard input.
+    pub fn run_binary(program: &dyn Builtin, input: &[u8], arguments: &[&str]) -> (i32, Vec<u8>) {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal))
+            .capture()
+            .with_input_bytes(Some(input.to_vec()));
+        let mut executables = HashMap::new();
+        let mut globals = HashMap::new();
+        let arguments = arguments
+            .iter()
+            .map(|argument| parse_argument(argument))
+            .collect();
+
+        let status = program.run(&stdio, &mut executables, &mut globals, arguments);
+        (status, stdio.captured_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn switch(key: &str, value: Option<&str>) -> Argument {
+        Argument::Switch(key.to_string(), value.map(String::from), Prefix::Short)
+    }
+
+    fn text(value: &str) -> Argument {
+        Argument::Text(value.to_string())
+    }
+
+    #[test]
+    fn parse_options() {
+        let arguments = vec![
+            switch("iv", None),
+            text("a"),
+            switch("n", None),
+            text("5"),
+            text("b"),
+        ];
+        let options = Options::parse("grep", arguments, "iv", "n").unwrap();
+        assert!(options.flag('i'));
+        assert!(options.flag('v'));
+        assert!(!options.flag('c'));
+        assert_eq!(options.value('n'), Some("5"));
+        assert_eq!(options.operands, vec!["a", "b"]);
+
+        let options = Options::parse("head", vec![switch("n3", None)], "", "n").unwrap();
+        assert_eq!(options.value('n'), Some("3"));
+        let options = Options::parse("cut", vec![switch("d", Some(","))], "", "d").unwrap();
+        assert_eq!(options.value('d'), Some(","));
+        let options = Options::parse("cut", vec![switch("sd", Some(":"))], "s", "d").unwrap();
+        assert_eq!(options.value('d'), Some(":"));
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert_eq!(
+            Options::parse("sort", vec![switch("x", None)], "nru", "").unwrap_err(),
+            "sort: invalid option -- 'x'"
+        );
+        assert_eq!(
+            Options::parse("head", vec![switch("n", None)], "", "n").unwrap_err(),
+            "head: option requires an argument -- 'n'"
+        );
+        assert!(Options::parse("sort", vec![switch("r", Some("1"))], "r", "").is_err());
+
+        let reverse = Argument::Switch("reverse".to_string(), None, Prefix::Long);
+        assert_eq!(
+            Options::parse("sort", vec![reverse], "nru", "").unwrap_err(),
+            "sort: unrecognized option '--reverse'"
+        );
+        let lines = Argument::Switch("lines".to_string(), Some("5".to_string()), Prefix::Long);
+        assert_eq!(
+            Options::parse("head", vec![lines], "", "n").unwrap_err(),
+            "head: unrecognized option '--lines=5'"
+        );
+    }
+
+    #[test]
+    fn read_files() {
+        let terminal = std::rc::Rc::new(crate::terminal::Terminal::new());
+        let stdio = Stdio::new(terminal).with_input(Some("piped".to_string()));
+        let mut fs = FileSystem::new();
+        fs.write("users/a.txt", b"kumiko".to_vec()).unwrap();
+
+        let inputs = read_inputs("wc", &stdio, &fs, &[]).unwrap();
+        assert_eq!(inputs[0].name, None);
+        assert_eq!(inputs[0].text, "piped");
+
+        let files = vec!["users/a.txt".to_string(), "-".to_string()];
+        let inputs = read_inputs("wc", &stdio, &fs, &files).unwrap();
+        assert_eq!(inputs[0].name.as_deref(), Some("users/a.txt"));
+        assert_eq!(inputs[0].text, "kumiko");
+        assert_eq!(inputs[1].text, "piped");
+
+        assert_eq!(
+            read_inputs("wc", &stdio, &fs, &["b.txt".to_string()]).err(),
+            Some("wc: b.txt: No such file or directory".to_string())
+        );
+        assert_eq!(
+            read_inputs("wc", &stdio, &fs, &["users".to_string()]).err(),
+            Some("wc: users: Is a directory".to_string())
+        );
+    }
+}
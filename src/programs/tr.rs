@@ -0,0 +1,175 @@
+use super::text::Options;
+use crate::shell::{executable::Builtin, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+
+/// Translate, delete or squeeze characters from standard input.
+pub struct Tr;
+
+type Class = fn(&char) -> bool;
+
+/// Expand a set like `a-z`, `\n` or `[:upper:]` to characters.
+fn expand(set: &str) -> Result<Vec<char>, String> {
+    let classes: [(&str, Class); 6] = [
+        ("[:lower:]", char::is_ascii_lowercase),
+        ("[:upper:]", char::is_ascii_uppercase),
+        ("[:digit:]", char::is_ascii_digit),
+        ("[:alpha:]", char::is_ascii_alphabetic),
+        ("[:alnum:]", char::is_ascii_alphanumeric),
+        ("[:space:]", |c| c.is_ascii_whitespace() || *c == '\x0b'),
+    ];
+
+    let mut chars = vec![];
+    let mut rest = set;
+    while !rest.is_empty() {
+        if let Some((name, class)) = classes.iter().find(|(name, _)| rest.starts_with(name)) {
+            chars.extend((0..128u8).map(char::from).filter(class));
+            rest = &rest[name.len()..];
+            continue;
+        }
+        if rest.starts_with("[:") {
+            let end = rest.find(":]").map(|end| end + 2).unwrap_or(rest.len());
+            return Err(format!("tr: invalid character class '{}'", &rest[..end]));
+        }
+
+        let mut iter = rest.chars();
+        let c = match iter.next() {
+            Some('\\') => match iter.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some(c) => c,
+                None => '\\',
+            },
+            Some(c) => c,
+            None => break,
+        };
+        rest = iter.as_str();
+
+        let mut range = rest.chars();
+        match (range.next(), range.next()) {
+            (Some('-'), Some(end)) => {
+                if end < c {
+                    return Err(format!(
+                        "tr: range-endpoints of '{}-{}' are in reverse collating sequence order",
+                        c, end
+                    ));
+                }
+                chars.extend(c..=end);
+                rest = range.as_str();
+            }
+            _ => chars.push(c),
+        }
+    }
+    Ok(chars)
+}
+
+impl Builtin for Tr {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("tr", arguments, "ds", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (delete, squeeze) = (options.flag('d'), options.flag('s'));
+        let sets = options
+            .operands
+            .iter()
+            .map(|set| expand(set))
+            .collect::<Result<Vec<_>, _>>();
+        let sets = match sets {
+            Ok(sets) => sets,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let (from, to) = match (sets.as_slice(), delete) {
+            ([from], true) => (from.clone(), vec![]),
+            ([from], false) if squeeze => (vec![], from.clone()),
+            ([from, to], true) if squeeze => (from.clone(), to.clone()),
+            ([from, to], false) if !to.is_empty() => (from.clone(), to.clone()),
+            _ => {
+                stdio.println("tr: usage: tr [-ds] <set1> [set2]");
+                return 2;
+            }
+        };
+
+        let mut output = String::new();
+        for c in stdio.stdin().unwrap_or_default().chars() {
+            let c = match from.iter().position(|from| *from == c) {
+                Some(_) if delete => continue,
+                // The last character of the second set is repeated if it's shorter.
+                Some(i) => to.get(i).or_else(|| to.last()).copied().unwrap_or(c),
+                None => c,
+            };
+            if squeeze && to.contains(&c) && output.ends_with(c) {
+                continue;
+            }
+            output.push(c);
+        }
+        stdio.print(&output.replace('\n', "\r\n"));
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::run;
+    use super::*;
+
+    #[test]
+    fn translate() {
+        assert_eq!(
+            run(&Tr, Some("kumiko\n"), &["a-z", "A-Z"]),
+            (0, "KUMIKO\n".to_string())
+        );
+        assert_eq!(
+            run(&Tr, Some("Reina\n"), &["[:upper:]", "[:lower:]"]),
+            (0, "reina\n".to_string())
+        );
+        assert_eq!(
+            run(&Tr, Some("a,b;c"), &[",;", "\\n"]),
+            (0, "a\nb\nc".to_string())
+        );
+        assert_eq!(
+            run(&Tr, Some("abcd"), &["a-d", "xy"]),
+            (0, "xyyy".to_string())
+        );
+    }
+
+    #[test]
+    fn delete_and_squeeze() {
+        assert_eq!(
+            run(&Tr, Some("a1b22c"), &["-d", "[:digit:]"]),
+            (0, "abc".to_string())
+        );
+        assert_eq!(
+            run(&Tr, Some("a  b   c"), &["-s", " "]),
+            (0, "a b c".to_string())
+        );
+        assert_eq!(
+            run(&Tr, Some("a1  b22"), &["-ds", "[:digit:]", " "]),
+            (0, "a b".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_sets() {
+        assert_eq!(run(&Tr, Some("a"), &[]).0, 2);
+        assert_eq!(run(&Tr, Some("a"), &["a", "b", "c"]).0, 2);
+        assert_eq!(
+            run(&Tr, Some("a"), &["z-a", "b"]),
+            (
+                2,
+                "tr: range-endpoints of 'z-a' are in reverse collating sequence order\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run(&Tr, Some("a"), &["[:foo:]", "b"]),
+            (2, "tr: invalid character class '[:foo:]'\n".to_string())
+        );
+    }
+}
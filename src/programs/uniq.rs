@@ -0,0 +1,106 @@
+use super::text::{read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Merge adjacent duplicated lines.
+pub struct Uniq {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Uniq {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Uniq { fs }
+    }
+}
+
+impl Builtin for Uniq {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("uniq", arguments, "cdui", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        if options.operands.len() > 1 {
+            stdio.println("uniq: usage: uniq [-cdui] [file]");
+            return 2;
+        }
+        let inputs = match read_inputs("uniq", stdio, &self.fs.borrow(), &options.operands) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+
+        let same = |a: &str, b: &str| {
+            if options.flag('i') {
+                a.to_lowercase() == b.to_lowercase()
+            } else {
+                a == b
+            }
+        };
+        let mut groups: Vec<(&str, usize)> = vec![];
+        for line in inputs.iter().flat_map(|input| input.text.lines()) {
+            match groups.last_mut() {
+                Some((first, count)) if same(first, line) => *count += 1,
+                _ => groups.push((line, 1)),
+            }
+        }
+        groups
+            .iter()
+            .filter(|(_, count)| !options.flag('d') || *count > 1)
+            .filter(|(_, count)| !options.flag('u') || *count == 1)
+            .for_each(|(line, count)| {
+                if options.flag('c') {
+                    stdio.println(&format!("{:>7} {}", count, line));
+                } else {
+                    stdio.println(line);
+                }
+            });
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    const INPUT: &str = "a\na\nb\nA\na\n";
+
+    #[test]
+    fn merge_lines() {
+        let uniq = Uniq::new(file_system(&[]));
+        assert_eq!(
+            run(&uniq, Some(INPUT), &[]),
+            (0, "a\nb\nA\na\n".to_string())
+        );
+        assert_eq!(
+            run(&uniq, Some(INPUT), &["-i"]),
+            (0, "a\nb\nA\n".to_string())
+        );
+        assert_eq!(run(&uniq, Some(INPUT), &["-d"]), (0, "a\n".to_string()));
+        assert_eq!(
+            run(&uniq, Some(INPUT), &["-u"]),
+            (0, "b\nA\na\n".to_string())
+        );
+        assert_eq!(
+            run(&uniq, Some(INPUT), &["-c"]),
+            (
+                0,
+                "      2 a\n      1 b\n      1 A\n      1 a\n".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn files() {
+        let uniq = Uniq::new(file_system(&[("a.txt", "x\nx\n")]));
+        assert_eq!(run(&uniq, None, &["a.txt"]), (0, "x\n".to_string()));
+        assert_eq!(run(&uniq, None, &["a.txt", "b.txt"]).0, 2);
+    }
+}
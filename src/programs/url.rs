@@ -0,0 +1,118 @@
+use super::argument_text;
+use crate::shell::{executable::Builtin, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+
+/// Percent-encode text, or decode it in the `urldecode` form.
+/// Text is given as arguments, or read from standard input line by line.
+pub struct Url {
+    decode: bool,
+}
+
+impl Url {
+    /// The `urlencode` form.
+    pub fn encoder() -> Self {
+        Url { decode: false }
+    }
+
+    /// The `urldecode` form.
+    pub fn decoder() -> Self {
+        Url { decode: true }
+    }
+}
+
+/// Encode all bytes except unreserved characters of RFC 3986.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decode percent-encoded bytes, and `+` as a space like query strings.
+/// Malformed sequences are kept as is.
+fn decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    decoded
+}
+
+impl Builtin for Url {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        // Switches are text to encode, like `--a=b`.
+        let mut texts = arguments.into_iter().map(argument_text).collect::<Vec<_>>();
+        if texts.is_empty() {
+            texts = stdio
+                .stdin()
+                .unwrap_or_default()
+                .lines()
+                .map(String::from)
+                .collect();
+        }
+
+        for text in texts {
+            if self.decode {
+                stdio.write(&decode(&text));
+                stdio.println("");
+            } else {
+                stdio.println(&encode(&text));
+            }
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{run, run_binary};
+    use super::*;
+
+    #[test]
+    fn encode_text() {
+        assert_eq!(
+            run(&Url::encoder(), None, &["a b&c=久", "x-._~"]),
+            (0, "a%20b%26c%3D%E4%B9%85\nx-._~\n".to_string())
+        );
+        assert_eq!(
+            run(&Url::encoder(), Some("a/b\nc d\n"), &[]),
+            (0, "a%2Fb\nc%20d\n".to_string())
+        );
+        assert_eq!(
+            run(&Url::encoder(), None, &["--a=b", "-c"]),
+            (0, "--a%3Db\n-c\n".to_string())
+        );
+    }
+
+    #[test]
+    fn decode_text() {
+        assert_eq!(
+            run(&Url::decoder(), None, &["a%20b+c%E4%B9%85", "%zz%4"]),
+            (0, "a b c久\n%zz%4\n".to_string())
+        );
+        assert_eq!(
+            run_binary(&Url::decoder(), b"%FF%00\n", &[]),
+            (0, b"\xff\x00\n".to_vec())
+        );
+    }
+}
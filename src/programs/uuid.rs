@@ -0,0 +1,200 @@
+use crate::shell::{executable::Builtin, Argument, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use md5::{Digest, Md5};
+
+/// Utilities of UUIDs for Minecraft players.
+///
+/// - `uuid offline <name...>` prints the UUID of an offline mode player.
+/// - `uuid random` prints a random UUID.
+/// - `uuid format <uuid...>` converts UUIDs between dashed and undashed forms.
+pub struct Uuid;
+
+const USAGE: &str = "uuid: usage: uuid offline <name...> | uuid random | uuid format <uuid...>";
+
+fn dashed(bytes: &[u8; 16]) -> String {
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Set version and the RFC 4122 variant.
+fn with_version(mut bytes: [u8; 16], version: u8) -> [u8; 16] {
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    bytes
+}
+
+/// UUID of an offline mode player, which is the same as
+/// `UUID.nameUUIDFromBytes("OfflinePlayer:<name>")` in Java.
+fn offline(name: &str) -> String {
+    let hash = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes());
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&hash);
+    dashed(&with_version(bytes, 3))
+}
+
+fn random() -> Result<String, String> {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes).map_err(|err| format!("uuid: {}", err))?;
+    Ok(dashed(&with_version(bytes, 4)))
+}
+
+/// Remove dashes from a dashed UUID, or insert dashes to an undashed one.
+fn convert(uuid: &str) -> Result<String, String> {
+    let invalid = || format!("uuid: invalid UUID: {}", uuid);
+    let hex = uuid.replace('-', "").to_ascii_lowercase();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    if !uuid.contains('-') {
+        let mut bytes = [0; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(dashed(&bytes))
+    } else if uuid.len() == 36 && [8, 13, 18, 23].iter().all(|i| &uuid[*i..*i + 1] == "-") {
+        Ok(hex)
+    } else {
+        Err(invalid())
+    }
+}
+
+impl Builtin for Uuid {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let mut texts = vec![];
+        for argument in arguments {
+            match argument {
+                Argument::Text(text) => texts.push(text),
+                Argument::Switch(..) => {
+                    stdio.println(USAGE);
+                    return 2;
+                }
+            }
+        }
+        let (command, mut operands) = match texts.split_first() {
+            Some((command, operands)) => (command.as_str(), operands.to_vec()),
+            None => {
+                stdio.println(USAGE);
+                return 2;
+            }
+        };
+        // Names and UUIDs can be piped, one per line.
+        if operands.is_empty() && command != "random" {
+            operands = stdio
+                .stdin()
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
+        match command {
+            "offline" if !operands.is_empty() => {
+                operands
+                    .iter()
+                    .for_each(|name| stdio.println(&offline(name)));
+                0
+            }
+            "random" if operands.is_empty() => match random() {
+                Ok(uuid) => {
+                    stdio.println(&uuid);
+                    0
+                }
+                Err(message) => {
+                    stdio.println(&message);
+                    1
+                }
+            },
+            "format" if !operands.is_empty() => {
+                let mut status = 0;
+                for uuid in operands {
+                    match convert(&uuid) {
+                        Ok(uuid) => stdio.println(&uuid),
+                        Err(message) => {
+                            stdio.println(&message);
+                            status = 1;
+                        }
+                    }
+                }
+                status
+            }
+            _ => {
+                stdio.println(USAGE);
+                2
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::run;
+    use super::*;
+
+    #[test]
+    fn offline_uuid() {
+        assert_eq!(offline("Notch"), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+        assert_eq!(
+            run(&Uuid, None, &["offline", "Notch", "jeb_"]),
+            (0, format!("{}\n{}\n", offline("Notch"), offline("jeb_")))
+        );
+        assert_eq!(
+            run(&Uuid, Some("Notch\n\n"), &["offline"]),
+            (0, "b50ad385-829d-3141-a216-7e7d7539ba7f\n".to_string())
+        );
+    }
+
+    #[test]
+    fn random_uuid() {
+        let (status, output) = run(&Uuid, None, &["random"]);
+        assert_eq!(status, 0);
+        let uuid = output.trim_end();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!("89ab".contains(&uuid[19..20]));
+        assert_ne!(run(&Uuid, None, &["random"]).1, output);
+    }
+
+    #[test]
+    fn format_uuid() {
+        assert_eq!(
+            run(&Uuid, None, &["format", "B50AD385829D3141A2167E7D7539BA7F"]),
+            (0, "b50ad385-829d-3141-a216-7e7d7539ba7f\n".to_string())
+        );
+        assert_eq!(
+            run(
+                &Uuid,
+                Some("b50ad385-829d-3141-a216-7e7d7539ba7f\n"),
+                &["format"]
+            ),
+            (0, "b50ad385829d3141a2167e7d7539ba7f\n".to_string())
+        );
+        assert_eq!(
+            run(&Uuid, None, &["format", "b50ad385-829d3141-a216-7e7d7539ba7f-", "xyz"]),
+            (
+                1,
+                "uuid: invalid UUID: b50ad385-829d3141-a216-7e7d7539ba7f-\nuuid: invalid UUID: xyz\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn usage() {
+        assert_eq!(run(&Uuid, None, &[]).0, 2);
+        assert_eq!(run(&Uuid, None, &["offline"]).0, 2);
+        assert_eq!(run(&Uuid, None, &["random", "a"]).0, 2);
+        assert_eq!(run(&Uuid, None, &["v5"]).0, 2);
+    }
+}
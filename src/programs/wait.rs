@@ -0,0 +1,57 @@
+use crate::shell::{
+    executable::{Exit, Internal},
+    jobs::JobTable,
+    Argument, Arguments,
+};
+use crate::stdio::Stdio;
+use futures::channel::oneshot::Sender;
+use futures::future::join_all;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+
+pub struct Wait {
+    table: Rc<RefCell<JobTable>>,
+}
+
+impl Wait {
+    pub fn new(table: Rc<RefCell<JobTable>>) -> Self {
+        Wait { table }
+    }
+}
+
+impl Internal for Wait {
+    fn run(&self, stdio: Rc<Stdio>, arguments: Arguments, exit: Sender<Exit>) {
+        let table = self.table.borrow();
+        let jobs = if arguments.is_empty() {
+            table.running()
+        } else {
+            arguments
+                .iter()
+                .filter_map(|argument| match argument {
+                    Argument::Text(spec) => {
+                        let job = table.get(spec);
+                        if job.is_none() {
+                            stdio.println(&format!("wait: {}: no such job", spec));
+                        }
+                        job
+                    }
+                    Argument::Switch(key, ..) => {
+                        stdio.println(&format!("wait: invalid argument: {}", key));
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        // Like bash, report the status of the last job waited for.
+        let last = jobs.last().cloned();
+        let waiters = jobs.iter().map(|job| job.wait()).collect::<Vec<_>>();
+        spawn_local(async move {
+            join_all(waiters).await;
+            let status = last.map(|job| job.status()).unwrap_or_default();
+            // Nobody waits any more if the job has been terminated.
+            let _ = exit.send(Exit::status(status));
+        });
+    }
+}
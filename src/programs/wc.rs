@@ -0,0 +1,122 @@
+use super::text::{read_inputs, Options};
+use crate::shell::{executable::Builtin, fs::FileSystem, Arguments, Executables, Vars};
+use crate::stdio::Stdio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Count lines, words and bytes.
+pub struct Wc {
+    fs: Rc<RefCell<FileSystem>>,
+}
+
+impl Wc {
+    pub fn new(fs: Rc<RefCell<FileSystem>>) -> Self {
+        Wc { fs }
+    }
+}
+
+/// Counts of lines, words, characters and bytes.
+#[derive(Clone, Copy, Default)]
+struct Counts([usize; 4]);
+
+impl Counts {
+    fn of(text: &str) -> Counts {
+        Counts([
+            text.matches('\n').count(),
+            text.split_whitespace().count(),
+            text.chars().count(),
+            text.len(),
+        ])
+    }
+
+    fn add(self, other: Counts) -> Counts {
+        let mut sum = self;
+        sum.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a += b);
+        sum
+    }
+}
+
+impl Builtin for Wc {
+    fn run(&self, stdio: &Stdio, _: &mut Executables, _: &mut Vars, arguments: Arguments) -> i32 {
+        let options = match Options::parse("wc", arguments, "lwmc", "") {
+            Ok(options) => options,
+            Err(message) => {
+                stdio.println(&message);
+                return 2;
+            }
+        };
+        let inputs = match read_inputs("wc", stdio, &self.fs.borrow(), &options.operands) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                stdio.println(&message);
+                return 1;
+            }
+        };
+
+        // Same order as the counts, and all except characters are shown by default.
+        let mut columns = "lwmc"
+            .chars()
+            .map(|flag| options.flag(flag))
+            .collect::<Vec<_>>();
+        if !columns.contains(&true) {
+            columns = vec![true, true, false, true];
+        }
+
+        let mut rows = inputs
+            .iter()
+            .map(|input| (Counts::of(&input.text), input.name.clone()))
+            .collect::<Vec<_>>();
+        if rows.len() > 1 {
+            let total = rows
+                .iter()
+                .fold(Counts::default(), |total, (counts, _)| total.add(*counts));
+            rows.push((total, Some("total".to_string())));
+        }
+
+        let width = rows
+            .iter()
+            .flat_map(|(counts, _)| counts.0.iter())
+            .map(|count| count.to_string().len())
+            .max()
+            .unwrap_or(1);
+        for (counts, name) in rows {
+            let mut fields = counts
+                .0
+                .iter()
+                .zip(&columns)
+                .filter(|(_, shown)| **shown)
+                .map(|(count, _)| format!("{:>width$}", count, width = width))
+                .collect::<Vec<_>>();
+            fields.extend(name);
+            stdio.println(&fields.join(" "));
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text::testing::{file_system, run};
+    use super::*;
+
+    #[test]
+    fn count() {
+        let wc = Wc::new(file_system(&[]));
+        let input = Some("kumiko reina\nhazuki\n");
+        assert_eq!(run(&wc, input, &[]), (0, " 2  3 20\n".to_string()));
+        assert_eq!(run(&wc, input, &["-l"]), (0, " 2\n".to_string()));
+        assert_eq!(run(&wc, input, &["-w"]), (0, " 3\n".to_string()));
+        assert_eq!(run(&wc, Some("久美子"), &["-mc"]), (0, "3 9\n".to_string()));
+        assert_eq!(run(&wc, None, &["-l"]), (0, "0\n".to_string()));
+    }
+
+    #[test]
+    fn files() {
+        let wc = Wc::new(file_system(&[("a.txt", "a b\n"), ("b.txt", "c\nd\n")]));
+        assert_eq!(
+            run(&wc, None, &["-lw", "a.txt", "b.txt"]),
+            (0, "1 2 a.txt\n2 2 b.txt\n3 4 total\n".to_string())
+        );
+        assert_eq!(run(&wc, None, &["-x"]).0, 2);
+    }
+}
@@ -0,0 +1,134 @@
+use super::Vars;
+use crate::parser::ast::*;
+use std::fmt::{self, Display};
+
+#[cfg_attr(test, derive(PartialEq, Debug))]
+pub struct Error {
+    pub message: String,
+    pub position: Position,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.position)
+    }
+}
+
+fn error(message: String, span: &Span) -> Error {
+    Error {
+        message,
+        position: span.start.clone(),
+    }
+}
+
+fn parse_number(text: &str, span: &Span) -> Result<i64, Error> {
+    text.trim()
+        .parse()
+        .map_err(|_| error(format!("invalid number: {}", text), span))
+}
+
+fn lookup(name: &str, span: &Span, variables: &Vars) -> Result<i64, Error> {
+    match variables.get(name) {
+        Some(value) if !value.trim().is_empty() => parse_number(value, span),
+        _ => Ok(0),
+    }
+}
+
+pub fn evaluate(expr: &Expr, variables: &Vars) -> Result<i64, Error> {
+    match expr {
+        Expr::Number(number) => parse_number(&number.value, &number.span),
+        Expr::Variable(var) => lookup(&var.id.name, &var.span, variables),
+        Expr::Name(id) => lookup(&id.name, &id.span, variables),
+        Expr::Paren(paren) => evaluate(&paren.expr, variables),
+        Expr::Unary(unary) => {
+            let operand = evaluate(&unary.operand, variables)?;
+            Ok(match unary.op {
+                UnaryOp::Plus => operand,
+                UnaryOp::Minus => operand.wrapping_neg(),
+                UnaryOp::Not => (operand == 0) as i64,
+            })
+        }
+        Expr::Binary(binary) => {
+            let left = evaluate(&binary.left, variables)?;
+            let right = evaluate(&binary.right, variables)?;
+            let value = match binary.op {
+                BinaryOp::Add => left.wrapping_add(right),
+                BinaryOp::Sub => left.wrapping_sub(right),
+                BinaryOp::Mul => left.wrapping_mul(right),
+                BinaryOp::Div | BinaryOp::Rem if right == 0 => {
+                    return Err(error("division by zero".to_string(), &binary.op_span));
+                }
+                BinaryOp::Div => left.wrapping_div(right),
+                BinaryOp::Rem => left.wrapping_rem(right),
+                BinaryOp::Lt => (left < right) as i64,
+                BinaryOp::Le => (left <= right) as i64,
+                BinaryOp::Gt => (left > right) as i64,
+                BinaryOp::Ge => (left >= right) as i64,
+                BinaryOp::Eq => (left == right) as i64,
+                BinaryOp::Ne => (left != right) as i64,
+            };
+            Ok(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_assignment;
+    use std::collections::HashMap;
+
+    fn eval(input: &str, variables: &Vars) -> Result<i64, String> {
+        let assignment = parse_assignment(input).map_err(|_| "syntax".to_string())?;
+        evaluate(&assignment.expr, variables).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn precedence() {
+        let variables = HashMap::new();
+        assert_eq!(eval("1 + 2 * 3", &variables), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3", &variables), Ok(9));
+        assert_eq!(eval("10 - 4 - 3", &variables), Ok(3));
+        assert_eq!(eval("7 % 4 + 8 / 3", &variables), Ok(5));
+        assert_eq!(eval("-2 * -(3)", &variables), Ok(6));
+        assert_eq!(eval("!0 + !5", &variables), Ok(1));
+    }
+
+    #[test]
+    fn comparisons() {
+        let variables = HashMap::new();
+        assert_eq!(eval("1 < 2", &variables), Ok(1));
+        assert_eq!(eval("2 <= 1", &variables), Ok(0));
+        assert_eq!(eval("3 > 2 == 1", &variables), Ok(1));
+        assert_eq!(eval("2 >= 2", &variables), Ok(1));
+        assert_eq!(eval("1 + 1 != 2", &variables), Ok(0));
+    }
+
+    #[test]
+    fn variables() {
+        let mut variables = HashMap::new();
+        variables.insert("kumiko".to_string(), "3".to_string());
+        variables.insert("empty".to_string(), "".to_string());
+        variables.insert("text".to_string(), "reina".to_string());
+
+        assert_eq!(eval("kumiko * 2", &variables), Ok(6));
+        assert_eq!(eval("$kumiko + nope + empty", &variables), Ok(3));
+        assert_eq!(
+            eval("1 + text", &variables),
+            Err("invalid number: reina at line 1, column 5".to_string())
+        );
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let variables = HashMap::new();
+        assert_eq!(
+            eval("1 + 4 / (2 - 2)", &variables),
+            Err("division by zero at line 1, column 7".to_string())
+        );
+        assert_eq!(
+            eval("1 % 0", &variables),
+            Err("division by zero at line 1, column 3".to_string())
+        );
+    }
+}
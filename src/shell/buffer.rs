@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 pub struct Buffer {
     text: String,
     cursor: usize,
@@ -21,15 +23,28 @@ impl Buffer {
     }
 
     pub fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
-        }
+        self.cursor = prev_char(&self.text, self.cursor);
     }
 
     pub fn move_right(&mut self) {
-        if self.cursor < self.len() {
-            self.cursor += 1;
-        }
+        self.cursor = next_char(&self.text, self.cursor);
+    }
+
+    /// Move cursor to the start of previous word.
+    pub fn move_word_left(&mut self) {
+        let before = self.text[..self.cursor].trim_end();
+        self.cursor = before
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| next_char(before, i));
+    }
+
+    /// Move cursor to the end of next word.
+    pub fn move_word_right(&mut self) {
+        let after = &self.text[self.cursor..];
+        let start = after.len() - after.trim_start().len();
+        self.cursor += after[start..]
+            .find(char::is_whitespace)
+            .map_or(after.len(), |i| start + i);
     }
 
     pub fn move_to_start(&mut self) {
@@ -53,22 +68,68 @@ impl Buffer {
         }
     }
 
+    fn char_before(&self) -> Option<char> {
+        self.text[..self.cursor].chars().next_back()
+    }
+
+    fn char_after(&self) -> Option<char> {
+        self.text[self.cursor..].chars().next()
+    }
+
+    /// Insert a pair of brackets or quotes, and put cursor between them.
+    /// If the closing quote is right after cursor, just move over it.
+    /// Quotes right after a word aren't paired, since it's likely an apostrophe.
+    pub fn insert_pair(&mut self, open: char, close: char) {
+        if open == close && self.char_after() == Some(close) {
+            self.move_right();
+        } else if open == close && self.char_before().is_some_and(char::is_alphanumeric) {
+            self.insert(&open.to_string());
+        } else {
+            self.insert(&open.to_string());
+            self.insert_without_moving(&close.to_string());
+        }
+    }
+
+    /// Insert a closing bracket, or move over it if it's right after cursor.
+    pub fn insert_close(&mut self, close: char) {
+        if self.char_after() == Some(close) {
+            self.move_right();
+        } else {
+            self.insert(&close.to_string());
+        }
+    }
+
+    /// Delete the character before cursor. If cursor is in an empty pair
+    /// of brackets or quotes, delete both of them.
+    pub fn delete_left_pair(&mut self) {
+        let pair = matches!(
+            (self.char_before(), self.char_after()),
+            (Some('"'), Some('"')) | (Some('\''), Some('\'')) | (Some('('), Some(')'))
+        );
+        if pair {
+            self.delete_right();
+        }
+        self.delete_left();
+    }
+
+    /// Remove text in the range, and put cursor at the start of it.
+    pub fn drain(&mut self, range: Range<usize>) -> String {
+        self.cursor = range.start;
+        self.text.drain(range).collect()
+    }
+
     pub fn clear(&mut self) {
         self.text.clear();
         self.cursor = 0;
     }
 
     pub fn delete_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
-            self.text.remove(self.cursor);
-        }
+        self.drain(prev_char(&self.text, self.cursor)..self.cursor);
     }
 
     pub fn delete_right(&mut self) {
-        if self.cursor < self.text.len() {
-            self.text.remove(self.cursor);
-        }
+        self.text
+            .drain(self.cursor..next_char(&self.text, self.cursor));
     }
 
     #[inline]
@@ -85,6 +146,26 @@ impl Buffer {
     pub fn get_cursor(&self) -> usize {
         self.cursor
     }
+
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.len());
+    }
+}
+
+/// Start of the character after `index`.
+pub fn next_char(text: &str, index: usize) -> usize {
+    text[index..]
+        .chars()
+        .next()
+        .map_or(index, |c| index + c.len_utf8())
+}
+
+/// Start of the character before `index`.
+pub fn prev_char(text: &str, index: usize) -> usize {
+    text[..index]
+        .chars()
+        .next_back()
+        .map_or(index, |c| index - c.len_utf8())
 }
 
 #[cfg(test)]
@@ -144,6 +225,51 @@ mod tests {
         assert_eq!(4, buffer.get_cursor());
     }
 
+    #[test]
+    fn multibyte() {
+        let mut buffer = Buffer::new();
+        buffer.insert("aé久");
+        buffer.move_left();
+        assert_eq!(3, buffer.get_cursor());
+        buffer.move_left();
+        buffer.insert_pair('"', '"');
+        assert_eq!("a\"é久", buffer.get());
+        buffer.delete_left_pair();
+        buffer.move_right();
+        buffer.delete_left();
+        assert_eq!("a久", buffer.get());
+        assert_eq!(1, buffer.get_cursor());
+        buffer.delete_right();
+        assert_eq!("a", buffer.get());
+        buffer.move_right();
+        assert_eq!(1, buffer.get_cursor());
+
+        buffer.set("ab\u{3000}久".to_string());
+        buffer.move_word_left();
+        assert_eq!(5, buffer.get_cursor());
+    }
+
+    #[test]
+    fn move_by_word() {
+        let mut buffer = Buffer::new();
+        buffer.insert("echo  a bc ");
+        buffer.move_word_left();
+        assert_eq!(8, buffer.get_cursor());
+        buffer.move_word_left();
+        assert_eq!(6, buffer.get_cursor());
+        buffer.move_word_left();
+        buffer.move_word_left();
+        assert_eq!(0, buffer.get_cursor());
+
+        buffer.move_word_right();
+        assert_eq!(4, buffer.get_cursor());
+        buffer.move_word_right();
+        assert_eq!(7, buffer.get_cursor());
+        buffer.move_word_right();
+        buffer.move_word_right();
+        assert_eq!(11, buffer.get_cursor());
+    }
+
     #[test]
     fn move_to_start() {
         let mut buffer = Buffer::new();
@@ -196,6 +322,81 @@ mod tests {
         assert_eq!(0, buffer.get_cursor());
     }
 
+    #[test]
+    fn insert_pair() {
+        let mut buffer = Buffer::new();
+        buffer.insert_pair('"', '"');
+        assert_eq!("\"\"", buffer.get());
+        assert_eq!(1, buffer.get_cursor());
+
+        buffer.insert("a");
+        buffer.insert_pair('"', '"');
+        assert_eq!("\"a\"", buffer.get());
+        assert_eq!(3, buffer.get_cursor());
+
+        buffer.insert(" it");
+        buffer.insert_pair('\'', '\'');
+        assert_eq!("\"a\" it'", buffer.get());
+
+        buffer.set("echo $".to_string());
+        buffer.insert_pair('(', ')');
+        buffer.insert_pair('(', ')');
+        assert_eq!("echo $(())", buffer.get());
+        assert_eq!(8, buffer.get_cursor());
+    }
+
+    #[test]
+    fn insert_close() {
+        let mut buffer = Buffer::new();
+        buffer.insert("$(");
+        buffer.insert_without_moving(")");
+        buffer.insert_close(')');
+        assert_eq!("$()", buffer.get());
+        assert_eq!(3, buffer.get_cursor());
+
+        buffer.insert_close(')');
+        assert_eq!("$())", buffer.get());
+        assert_eq!(4, buffer.get_cursor());
+    }
+
+    #[test]
+    fn delete_left_pair() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a''");
+        buffer.move_left();
+        buffer.delete_left_pair();
+        assert_eq!("a", buffer.get());
+        assert_eq!(1, buffer.get_cursor());
+
+        buffer.insert("(b)");
+        buffer.move_left();
+        buffer.delete_left_pair();
+        assert_eq!("a()", buffer.get());
+
+        buffer.move_to_end();
+        buffer.delete_left_pair();
+        assert_eq!("a(", buffer.get());
+    }
+
+    #[test]
+    fn drain() {
+        let mut buffer = Buffer::new();
+        buffer.insert("echo kumiko");
+        assert_eq!(buffer.drain(0..5), "echo ");
+        assert_eq!("kumiko", buffer.get());
+        assert_eq!(0, buffer.get_cursor());
+    }
+
+    #[test]
+    fn set_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.insert("kumiko");
+        buffer.set_cursor(2);
+        assert_eq!(2, buffer.get_cursor());
+        buffer.set_cursor(7);
+        assert_eq!(6, buffer.get_cursor());
+    }
+
     #[test]
     fn get_buffer_content() {
         let mut buffer = Buffer::new();
@@ -1,14 +1,40 @@
-use super::transform::Transformer;
+use super::arithmetic::Error;
+use super::jobs::{Job, JobTable};
+use super::pipeline::{self, Scope};
+use super::renderer::Signature;
+use super::transform::{Invocation, Transformer};
 use super::{Arguments, Executables, Vars};
-use crate::parser::ast::Parameters;
+use crate::parser::ast::{Parameters, Pipeline};
 use crate::stdio::Stdio;
-use crate::terminal::Terminal;
-use futures::channel::oneshot::{channel, Sender};
-use js_sys::{Function, Promise, Reflect};
-use std::cell::Cell;
+use futures::channel::oneshot::Sender;
+use futures::future::LocalBoxFuture;
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::AbortSignal;
+
+/// Exit status of a program which was interrupted by Ctrl+C.
+pub const INTERRUPTED: i32 = 130;
+
+/// What a program leaves after it exits.
+#[derive(Debug, Default)]
+pub struct Exit {
+    pub status: i32,
+    /// Variables which should be set in the shell.
+    pub env: Vars,
+}
+
+impl Exit {
+    pub fn status(status: i32) -> Self {
+        Exit {
+            status,
+            env: HashMap::new(),
+        }
+    }
+}
 
 pub enum Program {
     Builtin(Box<dyn Fn() -> Box<dyn Builtin>>),
@@ -17,66 +43,238 @@ pub enum Program {
 }
 
 pub trait Builtin {
+    /// Run the program and return its exit status.
     fn run(
         &self,
-        terminal: &Terminal,
+        stdio: &Stdio,
         executables: &mut Executables,
         globals: &mut Vars,
         arguments: Arguments,
-    );
+    ) -> i32;
+
+    /// Switches which the program accepts. `None` means anything is accepted.
+    fn switches(&self) -> Option<&'static [&'static str]> {
+        None
+    }
 }
 
 pub trait Internal {
-    fn run(&self, stdout: Rc<Stdio>, arguments: Arguments, exit: Sender<()>);
+    fn run(&self, stdout: Rc<Stdio>, arguments: Arguments, exit: Sender<Exit>);
+
+    /// Switches which the program accepts. `None` means anything is accepted.
+    fn switches(&self) -> Option<&'static [&'static str]> {
+        None
+    }
 }
 
+impl Signature for Program {
+    fn switches(&self) -> Option<Vec<String>> {
+        let names = |switches: &[&str]| switches.iter().map(|name| name.to_string()).collect();
+        match self {
+            Program::Builtin(program) => program().switches().map(names),
+            Program::Internal(program) => program().switches().map(names),
+            Program::External(external) => external.switches.clone(),
+        }
+    }
+}
+
+/// Details passed to external functions besides arguments.
+pub struct Context<'a> {
+    pub command_line: &'a str,
+    pub env: &'a Vars,
+    pub signal: AbortSignal,
+}
+
+fn error_message(error: &JsValue) -> String {
+    Reflect::get(error, &JsValue::from("message"))
+        .ok()
+        .and_then(|message| message.as_string())
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| "unknown error".to_string())
+}
+
+fn set(target: &Object, key: &str, value: &JsValue) {
+    Reflect::set(target, &JsValue::from(key), value).expect("failed to set property");
+}
+
+fn read_env(value: &JsValue) -> Vars {
+    Object::entries(&Object::from(value.clone()))
+        .iter()
+        .filter_map(|entry| {
+            let entry = Array::from(&entry);
+            let key = entry.get(0).as_string()?;
+            let value = entry.get(1);
+            let value = value.as_string().or_else(|| {
+                value
+                    .as_f64()
+                    .map(|n| n.to_string())
+                    .or_else(|| value.as_bool().map(|b| b.to_string()))
+            })?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Print a value returned from an external function.
+fn render(value: &JsValue, stdio: &Stdio) {
+    if value.is_undefined() || value.is_null() {
+        return;
+    }
+
+    let text = if let Some(text) = value.as_string() {
+        text
+    } else if Array::is_array(value) {
+        stdio.table(Array::from(value), None);
+        return;
+    } else if value.is_object() {
+        stdio.json(value);
+        return;
+    } else {
+        value
+            .as_f64()
+            .map(|n| n.to_string())
+            .or_else(|| value.as_bool().map(|b| b.to_string()))
+            .unwrap_or_default()
+    };
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    stdio.println(&text.replace('\n', "\r\n"));
+}
+
+/// Read the value resolved by an external function.
+///
+/// An object with `status` or `env` describes how the program exits,
+/// and its `value` is the output. Any other value is the output itself.
+fn read_result(value: &JsValue, stdio: &Stdio) -> Exit {
+    let get = |key: &str| {
+        Reflect::get(value, &JsValue::from(key))
+            .ok()
+            .filter(|value| !value.is_undefined())
+    };
+    let is_exit = value.is_object()
+        && !Array::is_array(value)
+        && (get("status").is_some() || get("env").is_some());
+
+    let (output, exit) = if is_exit {
+        let status = get("status")
+            .and_then(|status| status.as_f64())
+            .map(|status| status as i32)
+            .unwrap_or_default();
+        let env = get("env")
+            .filter(|env| env.is_object())
+            .map(|env| read_env(&env))
+            .unwrap_or_default();
+        (get("value").unwrap_or_default(), Exit { status, env })
+    } else {
+        (value.clone(), Exit::default())
+    };
+
+    render(&output, stdio);
+    exit
+}
+
+#[derive(Clone)]
 pub struct External {
     function: Function,
+    switches: Option<Vec<String>>,
 }
-
 impl External {
     pub fn new(function: Function) -> Self {
-        External { function }
+        External {
+            function,
+            switches: None,
+        }
     }
 
-    pub fn run(&self, terminal: Rc<Terminal>, arguments: Vec<String>, exit: Sender<()>) {
-        let stdio = Stdio::new(Rc::clone(&terminal));
+    /// Declare switches which the function accepts, so unknown ones can be highlighted.
+    pub fn with_switches(self, switches: Vec<String>) -> Self {
+        External {
+            switches: Some(switches),
+            ..self
+        }
+    }
+
+    fn context(&self, invocation: &Invocation, context: Context) -> Object {
+        let object = Object::new();
+
+        let args = invocation
+            .positional
+            .iter()
+            .map(JsValue::from)
+            .collect::<Array>();
+        set(&object, "args", &args);
+
+        let switches = Object::new();
+        invocation.switches.iter().for_each(|(key, value)| {
+            let value = value.as_ref().map(JsValue::from).unwrap_or(JsValue::TRUE);
+            set(&switches, key, &value);
+        });
+        set(&object, "switches", &switches);
+
+        let env = Object::new();
+        context
+            .env
+            .iter()
+            .for_each(|(key, value)| set(&env, key, &JsValue::from(value)));
+        set(&object, "env", &env);
+
+        set(&object, "commandLine", &JsValue::from(context.command_line));
+        set(&object, "signal", &context.signal);
+
+        object
+    }
+
+    pub fn run(
+        &self,
+        stdio: Rc<Stdio>,
+        invocation: Invocation,
+        context: Context,
+        exit: Sender<Exit>,
+    ) {
         let f = &self.function;
+        let handle = JsValue::from(stdio.as_ref().clone());
+        let context = self.context(&invocation, context);
         let arguments =
-            serde_wasm_bindgen::to_value(&arguments).expect("arguments conversion failed");
-        let result = match f.call2(&JsValue::NULL, &JsValue::from(stdio), &arguments) {
+            serde_wasm_bindgen::to_value(&invocation.texts).expect("arguments conversion failed");
+        let result = match f.call3(&JsValue::NULL, &handle, &arguments, &context) {
             Ok(value) => value,
             Err(e) => {
-                let message = Reflect::get(&e, &JsValue::from("message"))
-                    .ok()
-                    .and_then(|message| message.as_string())
-                    .unwrap_or_else(|| "unknown error".to_string());
-                terminal.write(&format!("{}\r\n", message));
-                if exit.send(()).is_err() {
-                    terminal.write("Program is hang up...Please refresh the page.\r\n");
+                stdio.println(&error_message(&e));
+                if exit.send(Exit::status(1)).is_err() {
+                    stdio.println("Program is hang up...Please refresh the page.");
                 }
                 return;
             }
         };
         spawn_local(async move {
             let future = JsFuture::from(Promise::resolve(&result));
-            if future.await.is_err() {
-                terminal.write("\r\n");
+            let status = match future.await {
+                Ok(value) => read_result(&value, &stdio),
+                Err(e) => {
+                    stdio.println(&error_message(&e));
+                    Exit::status(1)
+                }
             };
-            terminal.write("\u{001b}[?25h");
-            exit.send(()).expect("sender failure");
+            stdio.stop_widgets();
+            stdio.control("\u{001b}[?25h");
+            // Nobody waits any more if the job has been terminated.
+            let _ = exit.send(status);
         });
     }
 }
 
 pub struct Runner {
     running: Rc<Cell<bool>>,
+    jobs: Rc<RefCell<JobTable>>,
+    on_exit: Rc<dyn Fn(Exit)>,
 }
 
 impl Runner {
-    pub fn new() -> Self {
+    /// Create a runner. `on_exit` will be called when a foreground program exits.
+    pub fn new(on_exit: impl Fn(Exit) + 'static) -> Self {
         Runner {
             running: Rc::new(Cell::new(false)),
+            jobs: Rc::new(RefCell::new(JobTable::new())),
+            on_exit: Rc::new(on_exit),
         }
     }
 
@@ -84,70 +282,112 @@ impl Runner {
         self.running.get()
     }
 
+    pub fn jobs(&self) -> Rc<RefCell<JobTable>> {
+        Rc::clone(&self.jobs)
+    }
+
+    /// Print jobs which have finished in background.
+    pub fn report(&self, stdio: &Stdio) {
+        self.jobs
+            .borrow_mut()
+            .report()
+            .iter()
+            .for_each(|line| stdio.println(line));
+    }
+
+    /// Stop the foreground program, as Ctrl+C does.
+    pub fn interrupt(&self) {
+        if let Some(job) = self.jobs.borrow().foreground() {
+            job.terminate();
+        }
+    }
+
     pub fn run_builtin(
         &self,
         program: Box<dyn Builtin>,
         parameters: Option<Parameters>,
-        terminal: &Terminal,
+        stdio: &Stdio,
         executables: &mut Executables,
         globals: &mut Vars,
-    ) {
-        let transformer = Transformer::new(&globals, false);
+    ) -> Result<i32, Error> {
+        let transformer = Transformer::new(globals);
         let arguments = parameters
             .map(|p| transformer.transform(p))
+            .transpose()?
             .unwrap_or_default();
-        program.run(terminal, executables, globals, arguments);
+        Ok(program.run(stdio, executables, globals, arguments))
     }
 
-    pub fn run_internal(
+    /// Run a pipeline as a job.
+    /// If `background` is given, the job will be run in background with that name.
+    pub fn run(
         &mut self,
-        program: Box<dyn Internal>,
-        parameters: Option<Parameters>,
-        globals: &Vars,
+        scope: Scope,
+        pipeline: Pipeline,
+        source: Rc<str>,
         stdio: Rc<Stdio>,
+        background: Option<String>,
     ) {
-        self.running.set(true);
-
-        let transformer = Transformer::new(&globals, false);
-        let arguments = parameters
-            .map(|p| transformer.transform(p))
-            .unwrap_or_default();
-
-        let (sender, receiver) = channel::<()>();
-        program.run(Rc::clone(&stdio), arguments, sender);
-
-        let running = Rc::clone(&self.running);
-        spawn_local(async move {
-            receiver.await.expect("channel receiver failure");
-            running.set(false);
-            stdio.prompt();
-        });
+        let foreground = background.is_none();
+        let job = self.start_job(background, &stdio);
+        let task = pipeline::run(scope, pipeline, source, job.stdio(), Rc::clone(&job));
+        self.wait(task, job, foreground);
     }
 
-    pub fn run_external(
-        &mut self,
-        program: &External,
-        parameters: Option<Parameters>,
-        terminal: Rc<Terminal>,
-        globals: &Vars,
+    /// Run a pipeline out of job control, so it can't be listed or killed.
+    pub fn spawn(
+        &self,
+        scope: Scope,
+        pipeline: Pipeline,
+        source: Rc<str>,
         stdio: Rc<Stdio>,
-    ) {
-        self.running.set(true);
-
-        let transformer = Transformer::new(&globals, true);
-        let arguments = parameters
-            .map(|p| transformer.to_texts(transformer.transform(p)))
-            .unwrap_or_default();
+    ) -> LocalBoxFuture<'static, Exit> {
+        let job = Rc::new(Job::new(0, String::new(), Rc::clone(&stdio)));
+        pipeline::run(scope, pipeline, source, stdio, job)
+    }
 
-        let (exit_sender, exit_receiver) = channel::<()>();
-        program.run(terminal, arguments, exit_sender);
+    fn start_job(&mut self, background: Option<String>, stdio: &Stdio) -> Rc<Job> {
+        // Every program gets its own stdio, so it can be muted once being killed.
+        let output = Rc::new(stdio.detach());
+        let mut jobs = self.jobs.borrow_mut();
+        match background {
+            Some(command) => {
+                let job = jobs.add(command, output);
+                stdio.println(&format!("[{}] {}", job.id, job.command));
+                job
+            }
+            None => {
+                let job = Rc::new(Job::new(0, String::new(), output));
+                jobs.set_foreground(Some(Rc::clone(&job)));
+                job
+            }
+        }
+    }
 
-        let running = Rc::clone(&self.running);
+    fn wait(&mut self, task: LocalBoxFuture<'static, Exit>, job: Rc<Job>, foreground: bool) {
+        let done = job.wait();
+        let finished = Rc::clone(&job);
         spawn_local(async move {
-            exit_receiver.await.expect("channel receiver failure");
-            running.set(false);
-            stdio.prompt();
+            let exit = task.await;
+            finished.finish(exit);
         });
+
+        if foreground {
+            self.running.set(true);
+
+            let running = Rc::clone(&self.running);
+            let jobs = Rc::clone(&self.jobs);
+            let on_exit = Rc::clone(&self.on_exit);
+            spawn_local(async move {
+                let _ = done.await;
+                running.set(false);
+                jobs.borrow_mut().set_foreground(None);
+                on_exit(Exit {
+                    status: job.status(),
+                    env: job.take_env(),
+                });
+            });
+        }
     }
 }
 
@@ -155,21 +395,19 @@ impl Runner {
 mod tests {
     use super::*;
     use crate::programs::Echo;
+    use crate::terminal::Terminal;
     use std::collections::HashMap;
 
     #[test]
     fn run_builtin() {
-        let terminal = Terminal::new();
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
         let mut executables = HashMap::new();
         let mut globals = HashMap::new();
-        let runner = Runner::new();
-
-        runner.run_builtin(
-            Box::new(Echo::default()),
-            None,
-            &terminal,
-            &mut executables,
-            &mut globals,
-        );
+        let runner = Runner::new(|_| {});
+
+        let result =
+            runner.run_builtin(Box::new(Echo), None, &stdio, &mut executables, &mut globals);
+        assert_eq!(result, Ok(0));
     }
 }
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+/// An in-memory file system shared by programs of a shell.
+/// Directories aren't stored, and a directory exists if there're files in it.
+/// Relative paths are resolved from root.
+#[derive(Default)]
+pub struct FileSystem {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl FileSystem {
+    pub fn new() -> Self {
+        FileSystem::default()
+    }
+
+    /// Make a path absolute, and resolve `.` and `..` in it.
+    pub fn normalize(path: &str) -> String {
+        let mut parts = vec![];
+        for part in path.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        format!("/{}", parts.join("/"))
+    }
+
+    pub fn read(&self, path: &str) -> Option<&[u8]> {
+        self.files
+            .get(&FileSystem::normalize(path))
+            .map(Vec::as_slice)
+    }
+
+    /// Create a file or replace its content.
+    pub fn write(&mut self, path: &str, data: Vec<u8>) -> Result<(), String> {
+        let path = FileSystem::normalize(path);
+        if self.is_dir(&path) {
+            return Err(format!("{}: Is a directory", path));
+        }
+        let mut parent = path.as_str();
+        while let Some(end) = parent.rfind('/').filter(|end| *end > 0) {
+            parent = &parent[..end];
+            if self.files.contains_key(parent) {
+                return Err(format!("{}: Not a directory", parent));
+            }
+        }
+        self.files.insert(path, data);
+        Ok(())
+    }
+
+    pub fn is_file(&self, path: &str) -> bool {
+        self.files.contains_key(&FileSystem::normalize(path))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    pub fn is_dir(&self, path: &str) -> bool {
+        let path = FileSystem::normalize(path);
+        if path == "/" {
+            return true;
+        }
+        let prefix = format!("{}/", path);
+        self.files
+            .range(prefix.clone()..)
+            .next()
+            .is_some_and(|(name, _)| name.starts_with(&prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(FileSystem::normalize("a.png"), "/a.png");
+        assert_eq!(FileSystem::normalize("/a//b/./c/../d"), "/a/b/d");
+        assert_eq!(FileSystem::normalize("../.."), "/");
+    }
+
+    #[test]
+    fn read_and_write() {
+        let mut fs = FileSystem::new();
+        assert_eq!(fs.read("a.txt"), None);
+
+        fs.write("a.txt", b"kumiko".to_vec()).unwrap();
+        assert_eq!(fs.read("/a.txt"), Some(&b"kumiko"[..]));
+        fs.write("./a.txt", b"reina".to_vec()).unwrap();
+        assert_eq!(fs.read("a.txt"), Some(&b"reina"[..]));
+    }
+
+    #[test]
+    fn directories() {
+        let mut fs = FileSystem::new();
+        fs.write("textures/skin.png", vec![]).unwrap();
+        assert!(fs.is_dir("/"));
+        assert!(fs.is_dir("textures"));
+        assert!(!fs.is_dir("textures/skin.png"));
+        assert!(!fs.is_dir("texture"));
+        assert!(fs.is_file("textures/skin.png"));
+        assert!(!fs.is_file("textures"));
+        assert!(fs.exists("textures"));
+        assert!(!fs.exists("texture"));
+
+        assert!(fs.write("textures", vec![]).is_err());
+        assert!(fs.write("textures/skin.png/a", vec![]).is_err());
+        assert!(fs.write("/", vec![]).is_err());
+    }
+}
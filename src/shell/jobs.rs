@@ -0,0 +1,306 @@
+use super::executable::{Exit, INTERRUPTED};
+use super::Vars;
+use crate::stdio::Stdio;
+use futures::channel::oneshot::{channel, Receiver, Sender};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JobState {
+    Running,
+    Done,
+    Terminated,
+}
+
+pub struct Job {
+    pub id: usize,
+    pub command: String,
+    state: Cell<JobState>,
+    exit: RefCell<Exit>,
+    stdio: Rc<Stdio>,
+    waiters: RefCell<Vec<Sender<()>>>,
+    on_terminate: RefCell<Vec<Box<dyn FnOnce()>>>,
+}
+
+impl Job {
+    pub fn new(id: usize, command: String, stdio: Rc<Stdio>) -> Self {
+        Job {
+            id,
+            command,
+            state: Cell::new(JobState::Running),
+            exit: RefCell::new(Exit::default()),
+            stdio,
+            waiters: RefCell::new(vec![]),
+            on_terminate: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn state(&self) -> JobState {
+        self.state.get()
+    }
+
+    /// Exit status of this job. It's meaningless while running.
+    pub fn status(&self) -> i32 {
+        self.exit.borrow().status
+    }
+
+    /// Take away variables which the program asked to set.
+    pub fn take_env(&self) -> Vars {
+        std::mem::take(&mut self.exit.borrow_mut().env)
+    }
+
+    pub fn stdio(&self) -> Rc<Stdio> {
+        Rc::clone(&self.stdio)
+    }
+
+    /// Register a callback which will be called if this job is terminated.
+    pub fn on_terminate(&self, callback: impl FnOnce() + 'static) {
+        self.on_terminate.borrow_mut().push(Box::new(callback));
+    }
+
+    /// Called when the program of this job exits by itself.
+    pub fn finish(&self, exit: Exit) {
+        if self.state() == JobState::Running {
+            self.state.set(JobState::Done);
+            self.exit.replace(exit);
+            self.on_terminate.borrow_mut().clear();
+            self.wake();
+        }
+    }
+
+    /// Stop this job. Since a JavaScript promise can't be cancelled,
+    /// we mute its output, tell it to abort and stop waiting for it.
+    pub fn terminate(&self) {
+        if self.state() == JobState::Running {
+            self.state.set(JobState::Terminated);
+            self.exit.replace(Exit::status(INTERRUPTED));
+            self.stdio.stop_widgets();
+            self.stdio.close();
+            let callbacks = self.on_terminate.replace(vec![]);
+            callbacks.into_iter().for_each(|callback| callback());
+            self.wake();
+        }
+    }
+
+    fn describe_state(&self) -> String {
+        match self.state() {
+            JobState::Running => "Running".to_string(),
+            JobState::Done if self.status() != 0 => format!("Exit {}", self.status()),
+            JobState::Done => "Done".to_string(),
+            JobState::Terminated => "Terminated".to_string(),
+        }
+    }
+
+    /// Get a receiver which will be resolved once this job isn't running.
+    pub fn wait(&self) -> Receiver<()> {
+        let (sender, receiver) = channel();
+        if self.state() == JobState::Running {
+            self.waiters.borrow_mut().push(sender);
+        } else {
+            let _ = sender.send(());
+        }
+        receiver
+    }
+
+    fn wake(&self) {
+        self.waiters.borrow_mut().drain(..).for_each(|waiter| {
+            let _ = waiter.send(());
+        });
+    }
+}
+
+pub struct JobTable {
+    jobs: Vec<Rc<Job>>,
+    foreground: Option<Rc<Job>>,
+}
+
+impl JobTable {
+    pub fn new() -> Self {
+        JobTable {
+            jobs: vec![],
+            foreground: None,
+        }
+    }
+
+    pub fn add(&mut self, command: String, stdio: Rc<Stdio>) -> Rc<Job> {
+        let id = self.jobs.iter().map(|job| job.id).max().unwrap_or_default() + 1;
+        let job = Rc::new(Job::new(id, command, stdio));
+        self.jobs.push(Rc::clone(&job));
+        job
+    }
+
+    /// The job which is blocking the prompt, and will receive Ctrl+C.
+    pub fn foreground(&self) -> Option<Rc<Job>> {
+        self.foreground.clone()
+    }
+
+    pub fn set_foreground(&mut self, job: Option<Rc<Job>>) {
+        self.foreground = job;
+    }
+
+    /// Find a job by job spec, like `%1`, `%+`, `%%` or `%-`.
+    pub fn get(&self, spec: &str) -> Option<Rc<Job>> {
+        let spec = spec.strip_prefix('%').unwrap_or(spec);
+        let job = match spec {
+            "" | "+" | "%" => self.jobs.last(),
+            "-" => self.jobs.iter().rev().nth(1),
+            id => {
+                let id = id.parse::<usize>().ok()?;
+                self.jobs.iter().find(|job| job.id == id)
+            }
+        };
+        job.cloned()
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.jobs.retain(|job| job.id != id);
+    }
+
+    pub fn running(&self) -> Vec<Rc<Job>> {
+        self.jobs
+            .iter()
+            .filter(|job| job.state() == JobState::Running)
+            .cloned()
+            .collect()
+    }
+
+    fn marker(&self, index: usize) -> char {
+        let len = self.jobs.len();
+        if index + 1 == len {
+            '+'
+        } else if index + 2 == len {
+            '-'
+        } else {
+            ' '
+        }
+    }
+
+    fn describe(&self, index: usize) -> String {
+        let job = &self.jobs[index];
+        format!(
+            "[{}]{}  {:<24}{}{}",
+            job.id,
+            self.marker(index),
+            job.describe_state(),
+            job.command,
+            if job.state() == JobState::Running {
+                " &"
+            } else {
+                ""
+            }
+        )
+    }
+
+    /// Describe all jobs, then forget those which have finished.
+    pub fn list(&mut self) -> Vec<String> {
+        let lines = (0..self.jobs.len())
+            .map(|index| self.describe(index))
+            .collect();
+        self.jobs.retain(|job| job.state() == JobState::Running);
+        lines
+    }
+
+    /// Describe and forget jobs which have finished since last report.
+    pub fn report(&mut self) -> Vec<String> {
+        let lines = (0..self.jobs.len())
+            .filter(|index| self.jobs[*index].state() != JobState::Running)
+            .map(|index| self.describe(index))
+            .collect();
+        self.jobs.retain(|job| job.state() == JobState::Running);
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Terminal;
+
+    fn stdio() -> (Rc<Terminal>, Rc<Stdio>) {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Rc::new(Stdio::new(Rc::clone(&terminal)));
+        (terminal, stdio)
+    }
+
+    #[test]
+    fn find_by_spec() {
+        let (_, stdio) = stdio();
+        let mut table = JobTable::new();
+        table.add("a".to_string(), Rc::clone(&stdio));
+        table.add("b".to_string(), Rc::clone(&stdio));
+        table.add("c".to_string(), Rc::clone(&stdio));
+
+        assert_eq!(
+            table.get("%2").map(|job| job.command.clone()),
+            Some("b".into())
+        );
+        assert_eq!(
+            table.get("3").map(|job| job.command.clone()),
+            Some("c".into())
+        );
+        assert_eq!(table.get("%+").map(|job| job.id), Some(3));
+        assert_eq!(table.get("%%").map(|job| job.id), Some(3));
+        assert_eq!(table.get("%-").map(|job| job.id), Some(2));
+        assert!(table.get("%4").is_none());
+        assert!(table.get("%x").is_none());
+
+        table.remove(3);
+        assert_eq!(table.add("d".to_string(), stdio).id, 3);
+    }
+
+    #[test]
+    fn report_finished() {
+        let (_, stdio) = stdio();
+        let mut table = JobTable::new();
+        let first = table.add("sleep 1".to_string(), Rc::clone(&stdio));
+        table.add("sleep 2".to_string(), Rc::clone(&stdio));
+
+        assert!(table.report().is_empty());
+
+        first.finish(Exit::default());
+        assert_eq!(
+            table.report(),
+            vec!["[1]-  Done                    sleep 1".to_string()]
+        );
+        assert_eq!(table.running().len(), 1);
+        assert!(table.report().is_empty());
+
+        let third = table.add("false".to_string(), Rc::clone(&stdio));
+        third.finish(Exit::status(1));
+        assert_eq!(third.status(), 1);
+        assert_eq!(
+            table.report(),
+            vec!["[3]+  Exit 1                  false".to_string()]
+        );
+    }
+
+    #[test]
+    fn terminate() {
+        let (terminal, stdio) = stdio();
+        let mut table = JobTable::new();
+        let job = table.add("curl".to_string(), Rc::new(stdio.detach()));
+        let mut waiter = job.wait();
+        let aborted = Rc::new(Cell::new(false));
+        let flag = Rc::clone(&aborted);
+        job.on_terminate(move || flag.set(true));
+
+        job.terminate();
+        assert_eq!(job.state(), JobState::Terminated);
+        assert_eq!(job.status(), INTERRUPTED);
+        assert!(aborted.get());
+        assert_eq!(waiter.try_recv(), Ok(Some(())));
+
+        // Finishing after being killed doesn't change anything.
+        job.finish(Exit::status(0));
+        assert_eq!(job.state(), JobState::Terminated);
+        assert_eq!(job.status(), INTERRUPTED);
+
+        job.stdio().print("muted");
+        assert_eq!("", &terminal.get());
+        assert_eq!(
+            table.list(),
+            vec!["[1]+  Terminated              curl".to_string()]
+        );
+        assert!(table.list().is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use super::{Action, Keymap};
+use crate::shell::buffer::Buffer;
+use crate::shell::keys::{Key, KeyEvent, Modifiers};
+
+/// Default key bindings, which are like readline's.
+pub(crate) struct Emacs;
+
+impl Keymap for Emacs {
+    fn name(&self) -> &'static str {
+        "emacs"
+    }
+
+    fn handle(&mut self, event: KeyEvent, buffer: &mut Buffer) -> Action {
+        let KeyEvent { key, modifiers } = event;
+        let word = modifiers.ctrl || modifiers.alt;
+        match key {
+            Key::Enter => return Action::Submit,
+            // Ctrl+C, give up current line
+            Key::Char('c') if modifiers.ctrl => return Action::Cancel,
+            Key::Backspace => {
+                buffer.delete_left_pair();
+            }
+            Key::Left if word => {
+                buffer.move_word_left();
+            }
+            Key::Left => {
+                buffer.move_left();
+            }
+            Key::Right if word => {
+                buffer.move_word_right();
+            }
+            Key::Right => {
+                if buffer.is_empty() || buffer.get_cursor() == buffer.len() {
+                    return Action::AcceptSuggestion;
+                }
+                buffer.move_right();
+            }
+            Key::Up => return Action::HistoryUp,
+            Key::Down => return Action::HistoryDown,
+            Key::Delete => {
+                buffer.delete_right();
+            }
+            Key::Home => {
+                buffer.move_to_start();
+            }
+            Key::End => {
+                buffer.move_to_end();
+            }
+            // Alt+B and Alt+F, which are sent by Option+Arrow on macOS
+            Key::Char('b') if modifiers == Modifiers::ALT => {
+                buffer.move_word_left();
+            }
+            Key::Char('f') if modifiers == Modifiers::ALT => {
+                buffer.move_word_right();
+            }
+            _ if word => {}
+            Key::Char('"') => {
+                buffer.insert_pair('"', '"');
+            }
+            Key::Char('\'') => {
+                buffer.insert_pair('\'', '\'');
+            }
+            Key::Char('(') => {
+                buffer.insert_pair('(', ')');
+            }
+            Key::Char(')') => {
+                buffer.insert_close(')');
+            }
+            Key::Char(c) => {
+                buffer.insert(c.encode_utf8(&mut [0; 4]));
+            }
+            _ => {}
+        }
+        Action::Edit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::type_keys;
+    use super::*;
+
+    #[test]
+    fn edit_line() {
+        let mut buffer = Buffer::new();
+        let actions = type_keys(&mut Emacs, &mut buffer, "echo (a\u{001b}[D\u{001b}[3~\u{3}");
+        assert_eq!(buffer.get(), "echo ()");
+        assert_eq!(actions.last(), Some(&Action::Cancel));
+    }
+
+    #[test]
+    fn actions() {
+        let mut buffer = Buffer::new();
+        let actions = type_keys(&mut Emacs, &mut buffer, "a\u{001b}[C\u{001b}[A\u{001b}[B\r");
+        assert_eq!(
+            actions,
+            vec![
+                Action::Edit,
+                Action::AcceptSuggestion,
+                Action::HistoryUp,
+                Action::HistoryDown,
+                Action::Submit
+            ]
+        );
+    }
+}
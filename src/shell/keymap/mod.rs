@@ -0,0 +1,55 @@
+mod emacs;
+mod vi;
+
+use super::buffer::Buffer;
+use super::keys::KeyEvent;
+pub(crate) use emacs::Emacs;
+pub(crate) use vi::Vi;
+
+/// Names of all keymaps, which can be chosen by `set -o`.
+pub(crate) const NAMES: [&str; 2] = ["emacs", "vi"];
+
+/// What the shell should do after a key is handled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Action {
+    /// The buffer may be edited, and the line should be redrawn.
+    Edit,
+    /// Run current line.
+    Submit,
+    /// Give up current line.
+    Cancel,
+    /// Insert suggestion from history, if any.
+    AcceptSuggestion,
+    HistoryUp,
+    HistoryDown,
+}
+
+/// Key bindings of line editing.
+pub(crate) trait Keymap {
+    /// Name of the keymap, as used by `set -o`.
+    fn name(&self) -> &'static str;
+
+    /// Text shown before prompt, which tells current mode.
+    fn indicator(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn handle(&mut self, event: KeyEvent, buffer: &mut Buffer) -> Action;
+}
+
+/// Create a keymap by name.
+pub(crate) fn named(name: &str) -> Option<Box<dyn Keymap>> {
+    match name {
+        "emacs" => Some(Box::new(Emacs)),
+        "vi" => Some(Box::new(Vi::new())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+fn type_keys(keymap: &mut dyn Keymap, buffer: &mut Buffer, data: &str) -> Vec<Action> {
+    super::keys::split(data)
+        .into_iter()
+        .map(|sequence| keymap.handle(super::keys::parse(sequence), buffer))
+        .collect()
+}
@@ -0,0 +1,427 @@
+use super::{Action, Emacs, Keymap};
+use crate::shell::buffer::{next_char, prev_char, Buffer};
+use crate::shell::keys::{Key, KeyEvent};
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Mode {
+    Insert,
+    Normal,
+}
+
+/// Key bindings of vi. Lines start in insert mode, and Esc switches to normal mode.
+/// Words are separated by whitespace.
+pub(crate) struct Vi {
+    mode: Mode,
+    /// Operator which waits for a motion, like `d` of `dw`.
+    pending: Option<char>,
+    /// Text deleted last time, which is put by `p`.
+    register: String,
+    /// Previous lines with cursor, which are restored by `u`.
+    undo: Vec<(String, usize)>,
+    /// Line before entering insert mode, so a whole insertion is undone at once.
+    insert_start: Option<(String, usize)>,
+}
+
+impl Vi {
+    pub fn new() -> Self {
+        Vi {
+            mode: Mode::Insert,
+            pending: None,
+            register: String::new(),
+            undo: vec![],
+            insert_start: Some((String::new(), 0)),
+        }
+    }
+
+    /// Prepare for next line.
+    fn reset(&mut self) {
+        self.mode = Mode::Insert;
+        self.pending = None;
+        self.undo.clear();
+        self.insert_start = Some((String::new(), 0));
+    }
+
+    fn snapshot(buffer: &Buffer) -> (String, usize) {
+        (buffer.get().to_string(), buffer.get_cursor())
+    }
+
+    fn enter_insert(&mut self, buffer: &Buffer) {
+        self.mode = Mode::Insert;
+        self.insert_start = Some(Vi::snapshot(buffer));
+    }
+
+    fn enter_normal(&mut self, buffer: &mut Buffer) {
+        self.mode = Mode::Normal;
+        if let Some(start) = self.insert_start.take() {
+            if start.0 != buffer.get() {
+                self.undo.push(start);
+            }
+        }
+        let cursor = buffer.get_cursor();
+        buffer.set_cursor(prev_char(buffer.get(), cursor));
+    }
+
+    fn insert(&mut self, event: KeyEvent, buffer: &mut Buffer) -> Action {
+        match event.key {
+            Key::Escape => {
+                self.enter_normal(buffer);
+                Action::Edit
+            }
+            // Esc is sent with next key if they're typed quickly.
+            Key::Char(c) if event.modifiers.alt && !event.modifiers.ctrl => {
+                self.enter_normal(buffer);
+                self.normal(c, buffer)
+            }
+            _ => Emacs.handle(event, buffer),
+        }
+    }
+
+    fn normal(&mut self, c: char, buffer: &mut Buffer) -> Action {
+        if let Some(operator) = self.pending.take() {
+            self.operate(operator, c, buffer);
+            return Action::Edit;
+        }
+
+        let text = buffer.get().to_string();
+        let cursor = buffer.get_cursor();
+        match c {
+            'h' | 'l' | 'w' | 'b' | 'e' | '0' | '$' => {
+                buffer.set_cursor(motion(c, &text, cursor));
+            }
+            'i' => self.enter_insert(buffer),
+            'a' => {
+                buffer.set_cursor(next_char(&text, cursor));
+                self.enter_insert(buffer);
+            }
+            'I' => {
+                buffer.move_to_start();
+                self.enter_insert(buffer);
+            }
+            'A' => {
+                buffer.move_to_end();
+                self.enter_insert(buffer);
+            }
+            'x' if cursor < text.len() => {
+                self.delete(cursor..next_char(&text, cursor), buffer);
+            }
+            'd' | 'c' => self.pending = Some(c),
+            'p' | 'P' if !self.register.is_empty() => {
+                self.undo.push(Vi::snapshot(buffer));
+                let at = if c == 'p' {
+                    next_char(&text, cursor)
+                } else {
+                    cursor
+                };
+                buffer.set_cursor(at);
+                buffer.insert(&self.register);
+            }
+            'u' => {
+                if let Some((text, cursor)) = self.undo.pop() {
+                    buffer.set(text);
+                    buffer.set_cursor(cursor);
+                }
+            }
+            'k' => return Action::HistoryUp,
+            'j' => return Action::HistoryDown,
+            _ => {}
+        }
+
+        if self.mode == Mode::Normal {
+            let text = buffer.get();
+            buffer.set_cursor(buffer.get_cursor().min(last_char(text)));
+        }
+        Action::Edit
+    }
+
+    /// Run an operator like `d` with a motion, or itself for the whole line.
+    fn operate(&mut self, operator: char, c: char, buffer: &mut Buffer) {
+        let text = buffer.get().to_string();
+        let cursor = buffer.get_cursor();
+        let range = match c {
+            _ if c == operator => 0..text.len(),
+            // `cw` changes to the end of word, like `ce`.
+            'w' if operator == 'c' && !text[cursor..].starts_with(char::is_whitespace) => {
+                let rest = &text[cursor..];
+                cursor..cursor + rest.find(char::is_whitespace).unwrap_or(rest.len())
+            }
+            // `e` includes the character it lands on.
+            'e' => cursor..next_char(&text, word_end(&text, cursor)),
+            'h' | 'l' | 'w' | 'b' | '0' | '$' => {
+                let target = match c {
+                    'l' => next_char(&text, cursor),
+                    '$' => text.len(),
+                    _ => motion(c, &text, cursor),
+                };
+                cursor.min(target)..cursor.max(target)
+            }
+            _ => return,
+        };
+
+        if operator == 'c' {
+            let start = Vi::snapshot(buffer);
+            self.register = buffer.drain(range);
+            self.mode = Mode::Insert;
+            self.insert_start = Some(start);
+        } else {
+            self.delete(range, buffer);
+            let text = buffer.get();
+            buffer.set_cursor(buffer.get_cursor().min(last_char(text)));
+        }
+    }
+
+    fn delete(&mut self, range: Range<usize>, buffer: &mut Buffer) {
+        if range.is_empty() {
+            return;
+        }
+        self.undo.push(Vi::snapshot(buffer));
+        self.register = buffer.drain(range);
+    }
+}
+
+impl Keymap for Vi {
+    fn name(&self) -> &'static str {
+        "vi"
+    }
+
+    fn indicator(&self) -> Option<&'static str> {
+        match self.mode {
+            Mode::Insert => Some("(ins) "),
+            Mode::Normal => Some("(cmd) "),
+        }
+    }
+
+    fn handle(&mut self, event: KeyEvent, buffer: &mut Buffer) -> Action {
+        let action = match self.mode {
+            Mode::Insert => self.insert(event, buffer),
+            Mode::Normal => {
+                let KeyEvent { key, modifiers } = event;
+                match key {
+                    Key::Enter => Action::Submit,
+                    Key::Char('c') if modifiers.ctrl => Action::Cancel,
+                    Key::Escape => {
+                        self.pending = None;
+                        Action::Edit
+                    }
+                    _ if modifiers.ctrl || modifiers.alt => Action::Edit,
+                    Key::Char(c) => self.normal(c, buffer),
+                    Key::Left | Key::Backspace => self.normal('h', buffer),
+                    Key::Right => self.normal('l', buffer),
+                    Key::Up => self.normal('k', buffer),
+                    Key::Down => self.normal('j', buffer),
+                    Key::Home => self.normal('0', buffer),
+                    Key::End => self.normal('$', buffer),
+                    Key::Delete => self.normal('x', buffer),
+                    _ => Action::Edit,
+                }
+            }
+        };
+        if let Action::Submit | Action::Cancel = action {
+            self.reset();
+        }
+        action
+    }
+}
+
+/// Start of the last character, which is the rightmost position in normal mode.
+fn last_char(text: &str) -> usize {
+    prev_char(text, text.len())
+}
+
+/// Start of next word.
+fn next_word(text: &str, index: usize) -> usize {
+    let rest = &text[index..];
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let spaces = &rest[end..];
+    index + end + spaces.len() - spaces.trim_start().len()
+}
+
+/// Start of current word, or previous word if it's at the start already.
+fn word_start(text: &str, index: usize) -> usize {
+    text[..index]
+        .trim_end()
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// Last character of current word, or next word if it's at the end already.
+fn word_end(text: &str, index: usize) -> usize {
+    let start = next_char(text, index);
+    let rest = &text[start..];
+    let word = rest.trim_start();
+    let len = word.find(char::is_whitespace).unwrap_or(word.len());
+    if len == 0 {
+        return index;
+    }
+    prev_char(text, start + rest.len() - word.len() + len)
+}
+
+/// Where cursor goes by a motion in normal mode.
+fn motion(c: char, text: &str, index: usize) -> usize {
+    match c {
+        'h' => prev_char(text, index),
+        'l' => next_char(text, index).min(last_char(text)),
+        'w' => next_word(text, index),
+        'b' => word_start(text, index),
+        'e' => word_end(text, index),
+        '0' => 0,
+        '$' => last_char(text),
+        _ => index,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::type_keys;
+    use super::*;
+
+    fn normal(line: &str, keys: &str) -> (Vi, Buffer) {
+        let mut vi = Vi::new();
+        let mut buffer = Buffer::new();
+        type_keys(&mut vi, &mut buffer, line);
+        type_keys(&mut vi, &mut buffer, "\u{001b}");
+        type_keys(&mut vi, &mut buffer, keys);
+        (vi, buffer)
+    }
+
+    #[test]
+    fn words() {
+        let text = "echo  ab c";
+        assert_eq!(next_word(text, 0), 6);
+        assert_eq!(next_word(text, 9), 10);
+        assert_eq!(word_start(text, 7), 6);
+        assert_eq!(word_start(text, 6), 0);
+        assert_eq!(word_end(text, 0), 3);
+        assert_eq!(word_end(text, 3), 7);
+        assert_eq!(word_end(text, 9), 9);
+        assert_eq!(last_char("a久"), 1);
+        assert_eq!(prev_char("a久", 4), 1);
+        assert_eq!(next_char("a久", 1), 4);
+    }
+
+    #[test]
+    fn switch_modes() {
+        let mut vi = Vi::new();
+        let mut buffer = Buffer::new();
+        assert_eq!(vi.indicator(), Some("(ins) "));
+        type_keys(&mut vi, &mut buffer, "ab\u{001b}");
+        assert_eq!(vi.indicator(), Some("(cmd) "));
+        assert_eq!(buffer.get_cursor(), 1);
+
+        type_keys(&mut vi, &mut buffer, "ic");
+        assert_eq!(vi.indicator(), Some("(ins) "));
+        assert_eq!(buffer.get(), "acb");
+
+        type_keys(&mut vi, &mut buffer, "\u{001b}");
+        assert_eq!(type_keys(&mut vi, &mut buffer, "\r"), vec![Action::Submit]);
+        assert_eq!(vi.indicator(), Some("(ins) "));
+    }
+
+    #[test]
+    fn move_cursor() {
+        let (_, mut buffer) = normal("echo hello world", "0");
+        assert_eq!(buffer.get_cursor(), 0);
+
+        let mut vi = Vi::new();
+        vi.mode = Mode::Normal;
+        let mut moves = |keys| {
+            type_keys(&mut vi, &mut buffer, keys);
+            buffer.get_cursor()
+        };
+        assert_eq!(moves("w"), 5);
+        assert_eq!(moves("e"), 9);
+        assert_eq!(moves("l"), 10);
+        assert_eq!(moves("h"), 9);
+        assert_eq!(moves("b"), 5);
+        assert_eq!(moves("$"), 15);
+        assert_eq!(moves("l"), 15);
+        assert_eq!(moves("0"), 0);
+    }
+
+    #[test]
+    fn append() {
+        let (_, buffer) = normal("echo a", "0aX\u{001b}AY");
+        assert_eq!(buffer.get(), "eXcho aY");
+        let (_, buffer) = normal("echo a", "IZ");
+        assert_eq!(buffer.get(), "Zecho a");
+    }
+
+    #[test]
+    fn delete() {
+        let (_, buffer) = normal("echo hello world", "0wdw");
+        assert_eq!(buffer.get(), "echo world");
+        assert_eq!(buffer.get_cursor(), 5);
+
+        let (_, buffer) = normal("echo hello world", "x");
+        assert_eq!(buffer.get(), "echo hello worl");
+        assert_eq!(buffer.get_cursor(), 14);
+
+        let (_, buffer) = normal("echo hello", "bdw");
+        assert_eq!(buffer.get(), "echo ");
+        assert_eq!(buffer.get_cursor(), 4);
+
+        let (_, buffer) = normal("echo hello", "dd");
+        assert_eq!(buffer.get(), "");
+    }
+
+    #[test]
+    fn change() {
+        let (vi, buffer) = normal("echo hello world", "0wcwbye");
+        assert_eq!(buffer.get(), "echo bye world");
+        assert_eq!(vi.indicator(), Some("(ins) "));
+
+        let (_, buffer) = normal("echo hello", "0wcc");
+        assert_eq!(buffer.get(), "");
+    }
+
+    #[test]
+    fn put() {
+        let (_, buffer) = normal("echo hello world", "0wdwP");
+        assert_eq!(buffer.get(), "echo hello world");
+
+        let (_, buffer) = normal("ab", "0xp");
+        assert_eq!(buffer.get(), "ba");
+        assert_eq!(buffer.get_cursor(), 1);
+    }
+
+    #[test]
+    fn undo() {
+        let (_, buffer) = normal("echo hello world", "0wdwxu");
+        assert_eq!(buffer.get(), "echo world");
+        let (_, buffer) = normal("echo hello world", "0wdwxuu");
+        assert_eq!(buffer.get(), "echo hello world");
+
+        // A whole insertion is undone at once
+        let (_, buffer) = normal("echo a", "0cwprintf\u{001b}u");
+        assert_eq!(buffer.get(), "echo a");
+        let (_, buffer) = normal("echo a", "uu");
+        assert_eq!(buffer.get(), "");
+    }
+
+    #[test]
+    fn quick_escape() {
+        let mut vi = Vi::new();
+        let mut buffer = Buffer::new();
+        type_keys(&mut vi, &mut buffer, "echo a\u{001b}0");
+        assert_eq!(buffer.get_cursor(), 0);
+        assert_eq!(vi.indicator(), Some("(cmd) "));
+    }
+
+    #[test]
+    fn history() {
+        let mut vi = Vi::new();
+        let mut buffer = Buffer::new();
+        let actions = type_keys(&mut vi, &mut buffer, "\u{001b}kj\u{001b}[A\u{3}");
+        assert_eq!(
+            actions,
+            vec![
+                Action::HistoryUp,
+                Action::HistoryDown,
+                Action::HistoryUp,
+                Action::Cancel
+            ]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+/// Modifier keys held while pressing a key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        shift: false,
+        alt: false,
+        ctrl: false,
+    };
+
+    pub const CTRL: Modifiers = Modifiers {
+        shift: false,
+        alt: false,
+        ctrl: true,
+    };
+
+    pub const ALT: Modifiers = Modifiers {
+        shift: false,
+        alt: true,
+        ctrl: false,
+    };
+
+    /// Read the modifier parameter of CSI sequences, like `5` in `ESC[1;5D`.
+    fn from_param(param: u32) -> Modifiers {
+        let bits = param.saturating_sub(1);
+        Modifiers {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Key {
+    /// A character. Control characters like Ctrl+C are decoded as `Char('c')` with Ctrl.
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Insert,
+    Delete,
+    PageUp,
+    PageDown,
+    F(u8),
+    /// A sequence which isn't recognized.
+    Unknown(String),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        KeyEvent { key, modifiers }
+    }
+
+    pub fn plain(key: Key) -> Self {
+        KeyEvent::new(key, Modifiers::NONE)
+    }
+}
+
+/// Split input data into sequences which each represents one key.
+pub fn split(data: &str) -> Vec<&str> {
+    let mut keys = vec![];
+    let mut rest = data;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\u{001b}' {
+            escape_len(rest)
+        } else {
+            c.len_utf8()
+        };
+        keys.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    keys
+}
+
+/// Length of an escape sequence at the start of data, in bytes.
+fn escape_len(data: &str) -> usize {
+    let bytes = data.as_bytes();
+    match bytes.get(1) {
+        // CSI: parameters and intermediates, then a final byte
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        // SS3: one more byte
+        Some(b'O') if bytes.len() > 2 => 3,
+        // Alt with a character, or Esc followed by nothing
+        Some(_) => 1 + data[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// Decode a sequence of one key, as sent by xterm.js.
+pub fn parse(sequence: &str) -> KeyEvent {
+    let mut chars = sequence.chars();
+    match (chars.next(), chars.as_str()) {
+        (Some('\u{001b}'), "") => KeyEvent::plain(Key::Escape),
+        (Some('\u{001b}'), rest) if rest.starts_with('[') => csi(sequence, &rest[1..]),
+        (Some('\u{001b}'), rest) if rest.starts_with('O') && rest.len() > 1 => {
+            ss3(sequence, &rest[1..])
+        }
+        (Some('\u{001b}'), rest) => {
+            let mut event = parse(rest);
+            event.modifiers.alt = true;
+            event
+        }
+        (Some(c), "") => control(c),
+        _ => KeyEvent::plain(Key::Unknown(sequence.to_string())),
+    }
+}
+
+/// Decode a single character, which may be a control character.
+fn control(c: char) -> KeyEvent {
+    match c {
+        '\r' | '\n' => KeyEvent::plain(Key::Enter),
+        '\t' => KeyEvent::plain(Key::Tab),
+        '\u{7f}' => KeyEvent::plain(Key::Backspace),
+        '\u{8}' => KeyEvent::new(Key::Backspace, Modifiers::CTRL),
+        '\u{0}' => KeyEvent::new(Key::Char(' '), Modifiers::CTRL),
+        '\u{1}'..='\u{1a}' => {
+            let letter = (b'a' + c as u8 - 1) as char;
+            KeyEvent::new(Key::Char(letter), Modifiers::CTRL)
+        }
+        '\u{1c}'..='\u{1f}' => {
+            let symbol = (b'\\' + c as u8 - 0x1c) as char;
+            KeyEvent::new(Key::Char(symbol), Modifiers::CTRL)
+        }
+        c => KeyEvent::plain(Key::Char(c)),
+    }
+}
+
+/// Decode a CSI sequence, like `ESC[1;5D` or `ESC[3~`.
+fn csi(sequence: &str, body: &str) -> KeyEvent {
+    let unknown = || KeyEvent::plain(Key::Unknown(sequence.to_string()));
+    let (params, last) = match body.char_indices().last() {
+        Some((i, last)) => (&body[..i], last),
+        None => return unknown(),
+    };
+    let params = params
+        .split(';')
+        .map(|param| param.parse::<u32>().ok())
+        .collect::<Vec<_>>();
+    let modifiers = Modifiers::from_param(params.get(1).copied().flatten().unwrap_or(1));
+
+    let key = match last {
+        'A' => Key::Up,
+        'B' => Key::Down,
+        'C' => Key::Right,
+        'D' => Key::Left,
+        'H' => Key::Home,
+        'F' => Key::End,
+        'P' => Key::F(1),
+        'Q' => Key::F(2),
+        'R' => Key::F(3),
+        'S' => Key::F(4),
+        'Z' => {
+            return KeyEvent::new(
+                Key::Tab,
+                Modifiers {
+                    shift: true,
+                    ..modifiers
+                },
+            )
+        }
+        '~' => match params.first().copied().flatten() {
+            Some(1) | Some(7) => Key::Home,
+            Some(2) => Key::Insert,
+            Some(3) => Key::Delete,
+            Some(4) | Some(8) => Key::End,
+            Some(5) => Key::PageUp,
+            Some(6) => Key::PageDown,
+            Some(n @ 11..=15) => Key::F(n as u8 - 10),
+            Some(n @ 17..=21) => Key::F(n as u8 - 11),
+            Some(n @ 23..=24) => Key::F(n as u8 - 12),
+            _ => return unknown(),
+        },
+        _ => return unknown(),
+    };
+    KeyEvent::new(key, modifiers)
+}
+
+/// Decode an SS3 sequence, like `ESC O H`, which is sent in application cursor mode.
+fn ss3(sequence: &str, body: &str) -> KeyEvent {
+    let key = match body {
+        "A" => Key::Up,
+        "B" => Key::Down,
+        "C" => Key::Right,
+        "D" => Key::Left,
+        "H" => Key::Home,
+        "F" => Key::End,
+        "P" => Key::F(1),
+        "Q" => Key::F(2),
+        "R" => Key::F(3),
+        "S" => Key::F(4),
+        _ => Key::Unknown(sequence.to_string()),
+    };
+    KeyEvent::plain(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(data: &str) -> Vec<KeyEvent> {
+        split(data).into_iter().map(parse).collect()
+    }
+
+    fn ctrl(key: Key) -> KeyEvent {
+        KeyEvent::new(key, Modifiers::CTRL)
+    }
+
+    fn alt(key: Key) -> KeyEvent {
+        KeyEvent::new(key, Modifiers::ALT)
+    }
+
+    #[test]
+    fn split_keys() {
+        assert_eq!(
+            split("a久\u{001b}[1;5D\u{001b}OH\u{001b}b\r\u{001b}"),
+            vec![
+                "a",
+                "久",
+                "\u{001b}[1;5D",
+                "\u{001b}OH",
+                "\u{001b}b",
+                "\r",
+                "\u{001b}"
+            ]
+        );
+        assert_eq!(split("\u{001b}[3~\u{7f}"), vec!["\u{001b}[3~", "\u{7f}"]);
+        assert_eq!(split("\u{001b}[1;5"), vec!["\u{001b}[1;5"]);
+    }
+
+    #[test]
+    fn plain_keys() {
+        assert_eq!(decode("a"), vec![KeyEvent::plain(Key::Char('a'))]);
+        assert_eq!(decode("\r"), vec![KeyEvent::plain(Key::Enter)]);
+        assert_eq!(decode("\t"), vec![KeyEvent::plain(Key::Tab)]);
+        assert_eq!(decode("\u{7f}"), vec![KeyEvent::plain(Key::Backspace)]);
+        assert_eq!(decode("\u{001b}"), vec![KeyEvent::plain(Key::Escape)]);
+    }
+
+    #[test]
+    fn control_keys() {
+        assert_eq!(decode("\u{3}"), vec![ctrl(Key::Char('c'))]);
+        assert_eq!(decode("\u{1}"), vec![ctrl(Key::Char('a'))]);
+        assert_eq!(decode("\u{8}"), vec![ctrl(Key::Backspace)]);
+        assert_eq!(decode("\u{0}"), vec![ctrl(Key::Char(' '))]);
+        assert_eq!(decode("\u{1f}"), vec![ctrl(Key::Char('_'))]);
+    }
+
+    #[test]
+    fn alt_keys() {
+        assert_eq!(decode("\u{001b}b"), vec![alt(Key::Char('b'))]);
+        assert_eq!(decode("\u{001b}\u{7f}"), vec![alt(Key::Backspace)]);
+        assert_eq!(
+            decode("\u{001b}\u{1}"),
+            vec![KeyEvent::new(
+                Key::Char('a'),
+                Modifiers {
+                    alt: true,
+                    ctrl: true,
+                    shift: false
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn cursor_keys() {
+        assert_eq!(
+            decode("\u{001b}[A\u{001b}[B\u{001b}[C\u{001b}[D\u{001b}[H\u{001b}[F"),
+            vec![
+                KeyEvent::plain(Key::Up),
+                KeyEvent::plain(Key::Down),
+                KeyEvent::plain(Key::Right),
+                KeyEvent::plain(Key::Left),
+                KeyEvent::plain(Key::Home),
+                KeyEvent::plain(Key::End),
+            ]
+        );
+        // application cursor mode
+        assert_eq!(
+            decode("\u{001b}OA\u{001b}OH\u{001b}OF"),
+            vec![
+                KeyEvent::plain(Key::Up),
+                KeyEvent::plain(Key::Home),
+                KeyEvent::plain(Key::End),
+            ]
+        );
+    }
+
+    #[test]
+    fn modified_cursor_keys() {
+        assert_eq!(decode("\u{001b}[1;5D"), vec![ctrl(Key::Left)]);
+        assert_eq!(decode("\u{001b}[1;3C"), vec![alt(Key::Right)]);
+        assert_eq!(
+            decode("\u{001b}[1;2A"),
+            vec![KeyEvent::new(
+                Key::Up,
+                Modifiers {
+                    shift: true,
+                    ..Modifiers::NONE
+                }
+            )]
+        );
+        assert_eq!(
+            decode("\u{001b}[1;8H"),
+            vec![KeyEvent::new(
+                Key::Home,
+                Modifiers {
+                    shift: true,
+                    alt: true,
+                    ctrl: true
+                }
+            )]
+        );
+        assert_eq!(
+            decode("\u{001b}[Z"),
+            vec![KeyEvent::new(
+                Key::Tab,
+                Modifiers {
+                    shift: true,
+                    ..Modifiers::NONE
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn tilde_keys() {
+        assert_eq!(
+            decode("\u{001b}[2~\u{001b}[3~\u{001b}[5~\u{001b}[6~\u{001b}[1~\u{001b}[4~"),
+            vec![
+                KeyEvent::plain(Key::Insert),
+                KeyEvent::plain(Key::Delete),
+                KeyEvent::plain(Key::PageUp),
+                KeyEvent::plain(Key::PageDown),
+                KeyEvent::plain(Key::Home),
+                KeyEvent::plain(Key::End),
+            ]
+        );
+        assert_eq!(decode("\u{001b}[3;5~"), vec![ctrl(Key::Delete)]);
+    }
+
+    #[test]
+    fn function_keys() {
+        assert_eq!(
+            decode("\u{001b}OP\u{001b}OS\u{001b}[15~\u{001b}[21~\u{001b}[24~"),
+            vec![
+                KeyEvent::plain(Key::F(1)),
+                KeyEvent::plain(Key::F(4)),
+                KeyEvent::plain(Key::F(5)),
+                KeyEvent::plain(Key::F(10)),
+                KeyEvent::plain(Key::F(12)),
+            ]
+        );
+        assert_eq!(decode("\u{001b}[1;5P"), vec![ctrl(Key::F(1))]);
+    }
+
+    #[test]
+    fn unknown_sequences() {
+        assert_eq!(
+            decode("\u{001b}[99~"),
+            vec![KeyEvent::plain(Key::Unknown("\u{001b}[99~".to_string()))]
+        );
+        assert_eq!(
+            decode("\u{001b}[1;5"),
+            vec![KeyEvent::plain(Key::Unknown("\u{001b}[1;5".to_string()))]
+        );
+    }
+}
@@ -1,137 +1,403 @@
+pub(crate) mod arithmetic;
 mod buffer;
 pub(crate) mod executable;
+pub(crate) mod fs;
 mod history;
+pub(crate) mod jobs;
+pub(crate) mod keymap;
+mod keys;
+pub(crate) mod pipeline;
 mod renderer;
+pub(crate) mod theme;
 pub(crate) mod transform;
 
-use crate::parser::{self, ast::Command};
+use crate::format;
+use crate::parser::{self, ast::Statement};
 use crate::programs;
 use crate::stdio::Stdio;
+use crate::style::ColorMode;
 use crate::terminal::Terminal;
 use crate::utils;
 use ansi_term::Color;
 use buffer::Buffer;
-use executable::{Program, Runner};
+use executable::{Builtin, Exit, Program, Runner};
+use fs::FileSystem;
+use futures::future::LocalBoxFuture;
 use history::History;
-use js_sys::Function;
-use std::collections::HashMap;
-use std::rc::Rc;
+use js_sys::{Array, Error, Function, Object, Promise, Reflect};
+use keymap::{Action, Keymap};
+use keys::{Key, KeyEvent, Modifiers};
+use pipeline::Scope;
+use renderer::Context;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::{Rc, Weak};
+use theme::Theme;
 pub use transform::Argument;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+/// Pasted text is wrapped between these when bracketed paste mode is on.
+const PASTE_START: &str = "\u{001b}[200~";
+const PASTE_END: &str = "\u{001b}[201~";
 
 pub type Executables = HashMap<String, Program>;
 pub type Vars = HashMap<String, String>;
 pub type Arguments = Vec<transform::Argument>;
 
+/// Constructor of a builtin which works with files, like `grep` or `test`.
+type FileProgram = fn(Rc<RefCell<FileSystem>>) -> Box<dyn Builtin>;
+
 #[wasm_bindgen]
 pub struct Shell {
+    session: Rc<RefCell<Session>>,
+}
+
+#[wasm_bindgen]
+impl Shell {
+    #[wasm_bindgen(constructor)]
+    pub fn new(terminal: Terminal) -> Shell {
+        let session = Rc::new_cyclic(|session: &Weak<RefCell<Session>>| {
+            let session = Weak::clone(session);
+            let runner = Runner::new(move |exit| {
+                if let Some(session) = session.upgrade() {
+                    session.borrow_mut().resume(exit);
+                }
+            });
+            RefCell::new(Session::new(terminal, runner))
+        });
+
+        Shell { session }
+    }
+
+    /// Send input data to the Shell.
+    pub fn input(&mut self, data: &str) {
+        self.session.borrow_mut().input(data);
+    }
+
+    /// Run a command line, and resolve `{ stdout, status }` after it exits.
+    /// If `echo` is set, the command and its output will be shown in terminal,
+    /// otherwise it runs silently.
+    pub fn exec(&self, line: String, echo: Option<bool>) -> Promise {
+        let echo = echo.unwrap_or_default();
+        let session = Rc::clone(&self.session);
+        future_to_promise(async move {
+            let started = session.borrow_mut().exec(&line, echo);
+            let (stdio, task) = started.map_err(|message| Error::new(&message))?;
+            let exit = task.await;
+            let status = exit.status;
+            session.borrow_mut().finish_exec(exit, echo);
+
+            let result = Object::new();
+            Reflect::set(&result, &"stdout".into(), &stdio.captured().into())?;
+            Reflect::set(&result, &"status".into(), &status.into())?;
+            Ok(result.into())
+        })
+    }
+
+    /// Set when to use colors: `"always"`, `"never"` or `"auto"`.
+    /// In `"auto"` mode, colors are used only when writing to terminal,
+    /// and they're disabled if variable `NO_COLOR` is set.
+    #[wasm_bindgen(js_name = "setColorMode")]
+    pub fn set_color_mode(&self, mode: &str) -> Result<(), JsValue> {
+        let mode = mode.parse::<ColorMode>().map_err(|err| Error::new(&err))?;
+        self.session.borrow().stdio.colors().set_mode(mode);
+        Ok(())
+    }
+
+    /// Set theme of syntax highlighting and prompt. It can be `"dark"`, `"light"`,
+    /// or an object like `{ base: "light", program: "#00875f", string: { fg: "red", bold: true } }`.
+    #[wasm_bindgen(js_name = "setTheme")]
+    pub fn set_theme(&self, theme: JsValue) -> Result<(), JsValue> {
+        let theme = Theme::from_js(&theme).map_err(|err| Error::new(&err))?;
+        let mut session = self.session.borrow_mut();
+        session.stdio.colors().set_theme(theme);
+        if !session.runner.is_running() {
+            session.output();
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "addExternal")]
+    /// Register a new external JavaScript function.
+    /// Options can be `{ switches: ["o", "output"] }` to declare accepted switches.
+    pub fn add_external(&mut self, name: String, func: Function, options: Option<Object>) {
+        let mut external = executable::External::new(func);
+        let switches = options
+            .and_then(|options| Reflect::get(&options, &"switches".into()).ok())
+            .filter(Array::is_array)
+            .map(|switches| {
+                Array::from(&switches)
+                    .iter()
+                    .filter_map(|name| name.as_string())
+                    .collect()
+            });
+        if let Some(switches) = switches {
+            external = external.with_switches(switches);
+        }
+        let external = Program::External(external);
+        let session = self.session.borrow();
+        session
+            .scope
+            .executables
+            .borrow_mut()
+            .insert(name, external);
+    }
+
+    /// Write a file into the virtual file system, which can be read by programs like `grep`.
+    #[wasm_bindgen(js_name = "writeFile")]
+    pub fn write_file(&self, path: &str, data: &[u8]) -> Result<(), JsValue> {
+        let session = self.session.borrow();
+        let result = session.fs.borrow_mut().write(path, data.to_vec());
+        result.map_err(|err| Error::new(&err).into())
+    }
+
+    /// Read a file from the virtual file system, such as a file saved by `curl -o`.
+    #[wasm_bindgen(js_name = "readFile")]
+    pub fn read_file(&self, path: &str) -> Option<Vec<u8>> {
+        let session = self.session.borrow();
+        let data = session.fs.borrow().read(path).map(<[u8]>::to_vec);
+        data
+    }
+}
+
+struct Session {
     buffer: Buffer,
-    terminal: Rc<Terminal>,
-    executables: Executables,
-    globals: Vars,
+    scope: Scope,
     history: History,
     suggestion: Option<String>,
     runner: Runner,
     stdio: Rc<Stdio>,
+    typeahead: VecDeque<String>,
+    /// Text received so far, while a paste is arriving in chunks.
+    pasting: Option<String>,
+    /// Key bindings, which can be switched by `set -o`.
+    keymap: Rc<RefCell<Box<dyn Keymap>>>,
+    /// Virtual file system, which programs read from and write to.
+    fs: Rc<RefCell<FileSystem>>,
 }
 
-#[wasm_bindgen]
-impl Shell {
-    #[wasm_bindgen(constructor)]
-    pub fn new(terminal: Terminal) -> Shell {
+/// Check if input data has a Ctrl+C key, which isn't part of pasted text.
+fn interrupts(data: &str) -> bool {
+    let mut pasting = false;
+    keys::split(data).into_iter().any(|sequence| {
+        match sequence {
+            PASTE_START => pasting = true,
+            PASTE_END => pasting = false,
+            _ => {}
+        }
+        !pasting && keys::parse(sequence) == KeyEvent::new(Key::Char('c'), Modifiers::CTRL)
+    })
+}
+
+impl Session {
+    fn new(terminal: Terminal, runner: Runner) -> Session {
         let mut executables = HashMap::with_capacity(10);
+        executables.insert(
+            "base64".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Base64))),
+        );
         executables.insert(
             "clear".to_string(),
-            Program::Builtin(Box::new(|| Box::new(programs::Clear::default()))),
+            Program::Builtin(Box::new(|| Box::new(programs::Clear))),
         );
         executables.insert(
             "echo".to_string(),
-            Program::Builtin(Box::new(|| Box::new(programs::Echo::default()))),
+            Program::Builtin(Box::new(|| Box::new(programs::Echo))),
         );
         executables.insert(
             "export".to_string(),
-            Program::Builtin(Box::new(|| Box::new(programs::Export::default()))),
+            Program::Builtin(Box::new(|| Box::new(programs::Export))),
+        );
+        executables.insert(
+            "jq".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Jq))),
+        );
+        executables.insert(
+            "let".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Let))),
         );
         executables.insert(
-            "curl".to_string(),
-            Program::Internal(Box::new(|| Box::new(programs::Curl::default()))),
+            "printf".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Printf))),
         );
+        executables.insert(
+            "tr".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Tr))),
+        );
+        executables.insert(
+            "urldecode".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Url::decoder()))),
+        );
+        executables.insert(
+            "urlencode".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Url::encoder()))),
+        );
+        executables.insert(
+            "uuid".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Uuid))),
+        );
+        let fs = Rc::new(RefCell::new(FileSystem::new()));
+        let file_programs: [(&str, FileProgram); 12] = [
+            ("cut", |fs| Box::new(programs::Cut::new(fs))),
+            ("grep", |fs| Box::new(programs::Grep::new(fs))),
+            ("head", |fs| Box::new(programs::Head::new(fs))),
+            ("sort", |fs| Box::new(programs::Sort::new(fs))),
+            ("tail", |fs| Box::new(programs::Tail::new(fs))),
+            ("uniq", |fs| Box::new(programs::Uniq::new(fs))),
+            ("wc", |fs| Box::new(programs::Wc::new(fs))),
+            ("test", |fs| Box::new(programs::Test::new(fs))),
+            ("[", |fs| Box::new(programs::Test::bracket(fs))),
+            ("hash", |fs| Box::new(programs::Hash::new(fs))),
+            ("hexdump", |fs| Box::new(programs::Hexdump::new(fs))),
+            ("skin", |fs| Box::new(programs::Skin::new(fs))),
+        ];
+        for (name, program) in file_programs.iter().copied() {
+            let fs = Rc::clone(&fs);
+            executables.insert(
+                name.to_string(),
+                Program::Builtin(Box::new(move || program(Rc::clone(&fs)))),
+            );
+        }
+        {
+            let fs = Rc::clone(&fs);
+            executables.insert(
+                "curl".to_string(),
+                Program::Internal(Box::new(move || {
+                    Box::new(programs::Curl::new(Rc::clone(&fs)))
+                })),
+            );
+        }
 
-        let terminal = Rc::new(terminal);
-        let stdio = Rc::new(Stdio::new(Rc::clone(&terminal)));
+        let jobs = runner.jobs();
+        executables.insert(
+            "jobs".to_string(),
+            Program::Builtin(Box::new(move || {
+                Box::new(programs::Jobs::new(Rc::clone(&jobs)))
+            })),
+        );
+        let jobs = runner.jobs();
+        executables.insert(
+            "kill".to_string(),
+            Program::Builtin(Box::new(move || {
+                Box::new(programs::Kill::new(Rc::clone(&jobs)))
+            })),
+        );
+        let jobs = runner.jobs();
+        executables.insert(
+            "fg".to_string(),
+            Program::Internal(Box::new(move || {
+                Box::new(programs::Fg::new(Rc::clone(&jobs)))
+            })),
+        );
+        let jobs = runner.jobs();
+        executables.insert(
+            "wait".to_string(),
+            Program::Internal(Box::new(move || {
+                Box::new(programs::Wait::new(Rc::clone(&jobs)))
+            })),
+        );
 
-        let shell = Shell {
-            terminal,
+        let keymap: Rc<RefCell<Box<dyn Keymap>>> = Rc::new(RefCell::new(Box::new(keymap::Emacs)));
+        {
+            let keymap = Rc::clone(&keymap);
+            executables.insert(
+                "set".to_string(),
+                Program::Builtin(Box::new(move || {
+                    Box::new(programs::Set::new(Rc::clone(&keymap)))
+                })),
+            );
+        }
+
+        let stdio = Rc::new(Stdio::new(Rc::new(terminal)));
+        let scope = Scope {
+            executables: Rc::new(RefCell::new(executables)),
+            globals: Rc::new(RefCell::new(HashMap::with_capacity(3))),
+        };
+
+        let session = Session {
             buffer: Buffer::new(),
-            executables,
-            globals: HashMap::with_capacity(3),
+            scope,
             history: History::new(),
             suggestion: None,
-            runner: Runner::new(),
+            runner,
             stdio,
+            typeahead: VecDeque::new(),
+            pasting: None,
+            keymap,
+            fs,
         };
 
         let greet = Color::Fixed(127)
             .paint("Welcome to Blessing Skin Shell!\r\n")
             .to_string();
-        shell.stdio.println(&greet);
-        shell.stdio.prompt();
+        session.stdio.println(&greet);
+        // Enable bracketed paste mode
+        session.stdio.control("\u{001b}[?2004h");
+        session.prompt();
 
-        shell
+        session
     }
 
-    /// Send input data to the Shell.
-    pub fn input(&mut self, data: &str) {
+    fn input(&mut self, data: &str) {
         utils::set_panic_hook();
 
         if self.runner.is_running() {
+            if interrupts(data) {
+                // Ctrl+C shouldn't wait in queue, and it discards what's queued.
+                self.typeahead.clear();
+                self.runner.interrupt();
+                self.stdio.println("^C");
+            } else {
+                self.typeahead.push_back(data.to_string());
+            }
             return;
         }
 
-        match data.as_bytes() {
-            // line break
-            [10] | [13] => {
-                self.stdio.reset();
-                self.stdio.prompt();
-                match parser::parse_interactive(self.buffer.get()) {
-                    Ok((command, rest)) => {
-                        self.render_command(command);
-                        self.stdio.print(rest);
-                    }
-                    Err(_) => {
-                        self.stdio.print(self.buffer.get());
-                    }
-                }
+        if self.pasting.is_some() || data.starts_with(PASTE_START) {
+            self.receive_paste(data);
+            return;
+        }
 
-                self.commit();
+        let sequences = keys::split(data);
+        for (i, sequence) in sequences.iter().enumerate() {
+            if self.runner.is_running() {
+                // Keys after Enter are queued until the program exits.
+                self.typeahead.push_back(sequences[i..].concat());
+                return;
             }
-            // the key "Esc", do nothing
-            [27] => {}
-            // backspace
-            [127] => {
-                self.buffer.delete_left();
+            self.handle_key(keys::parse(sequence));
+        }
+
+        if !self.runner.is_running() {
+            self.output();
+        }
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) {
+        let action = self.keymap.borrow_mut().handle(event, &mut self.buffer);
+        match action {
+            Action::Edit => {}
+            Action::Submit => {
+                self.render_line();
+                self.commit();
             }
-            // arrow left
-            [27, 91, 68] => {
-                self.buffer.move_left();
+            Action::Cancel => {
+                self.render_line();
+                self.stdio.println("^C");
+                self.buffer.clear();
             }
-            // arrow right
-            [27, 91, 67] => {
-                if !self.buffer.is_empty() && self.buffer.get_cursor() < self.buffer.len() {
-                    self.buffer.move_right();
-                } else if let Some(suggestion) = &self.suggestion {
+            Action::AcceptSuggestion => {
+                if let Some(suggestion) = self.suggestion.take() {
                     self.buffer.insert(&suggestion);
-                    self.suggestion = None;
                 }
             }
-            // arrow up
-            [27, 91, 65] => {
+            Action::HistoryUp => {
                 if let Some(history) = self.history.up() {
                     self.buffer.set(history);
                 }
             }
-            // arrow down
-            [27, 91, 66] => match self.history.down() {
+            Action::HistoryDown => match self.history.down() {
                 Some(history) => {
                     self.buffer.set(history);
                 }
@@ -139,73 +405,200 @@ impl Shell {
                     self.buffer.clear();
                 }
             },
-            // the key "Delete"
-            [27, 91, 51, 126] => {
-                self.buffer.delete_right();
-            }
-            // the key "Home"
-            [27, 91, 72] => {
-                self.buffer.move_to_start();
-            }
-            // the key "End"
-            [27, 91, 70] => {
-                self.buffer.move_to_end();
-            }
-            // double quote
-            [34] => {
-                self.buffer.insert_without_moving("\"\"");
-                self.buffer.move_right();
+        }
+    }
+
+    /// Collect pasted text until the end of paste.
+    fn receive_paste(&mut self, data: &str) {
+        let mut pasted = self.pasting.take().unwrap_or_default();
+        pasted.push_str(data.strip_prefix(PASTE_START).unwrap_or(data));
+        match pasted.find(PASTE_END) {
+            Some(end) => {
+                let rest = pasted.split_off(end)[PASTE_END.len()..].to_string();
+                self.paste(&pasted);
+                // Keystrokes following the paste
+                if !rest.is_empty() {
+                    if self.runner.is_running() {
+                        self.typeahead.push_back(rest);
+                    } else {
+                        self.input(&rest);
+                    }
+                }
             }
-            // single quote
-            [39] => {
-                self.buffer.insert_without_moving("''");
-                self.buffer.move_right();
+            None => self.pasting = Some(pasted),
+        }
+    }
+
+    /// Insert pasted text as is, without auto-pairing.
+    /// Every complete line is run as if Enter is pressed after it.
+    fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let mut lines = text.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            self.buffer.insert(line);
+            if lines.peek().is_none() {
+                break;
             }
-            _ => {
-                self.buffer.insert(data);
+            self.render_line();
+            self.commit();
+
+            if self.runner.is_running() {
+                // Paste the rest after the program exits.
+                let rest = lines.collect::<Vec<_>>().join("\n");
+                if !rest.is_empty() {
+                    self.typeahead
+                        .push_back(format!("{}{}{}", PASTE_START, rest, PASTE_END));
+                }
+                return;
             }
         }
+        self.output();
+    }
 
-        if !self.runner.is_running() {
-            self.output();
+    /// Called when the foreground program exits.
+    fn resume(&mut self, exit: Exit) {
+        self.scope.globals.borrow_mut().extend(exit.env);
+        self.set_status(exit.status);
+        self.runner.report(&self.stdio);
+        self.prompt();
+
+        // Replay keystrokes typed while the program was running.
+        while !self.runner.is_running() {
+            match self.typeahead.pop_front() {
+                Some(data) => self.input(&data),
+                None => break,
+            }
         }
     }
 
-    fn output(&mut self) {
+    /// Write current line without suggestion, and leave cursor at the end.
+    /// If the line can't be parsed, the error is returned.
+    fn render_line(&self) -> Option<String> {
         self.stdio.reset();
-
-        // Write line
-        self.stdio.prompt();
-        match parser::parse_interactive(self.buffer.get()) {
-            Ok((command, rest)) => {
-                self.render_command(command);
-                self.stdio.print(rest);
+        // Clear the error shown below, if any
+        self.stdio.control("\u{001b}[0J");
+        self.prompt();
+        let line = self.buffer.get();
+        match parser::parse_interactive(line) {
+            Ok(statement) => {
+                self.render_statement(&statement);
+                None
+            }
+            Err(_) if line.trim().is_empty() => {
+                self.stdio.print(line);
+                None
             }
-            Err(_) => {
-                self.stdio.print(self.buffer.get());
+            Err(err) => {
+                let recovery = parser::recover(line, &err);
+                let output = self.render(|cx| renderer::recovery(line, &recovery, cx));
+                self.stdio.print(&output);
+                Some(parser::describe_error(&err))
             }
         }
+    }
+
+    fn output(&mut self) {
+        let error = self.render_line();
 
         if self.buffer.is_empty() {
             self.suggestion = None;
         } else if let Some(history) = self.history.find(self.buffer.get()) {
             let rest = history.trim_start_matches(self.buffer.get());
-            self.stdio.print(&Color::Fixed(8).paint(rest).to_string());
+            self.stdio
+                .print(&self.theme().suggestion.paint(rest).to_string());
             self.suggestion = Some(rest.to_string());
         }
 
+        // Show syntax error on the line below, then go back
+        if let Some(error) = error {
+            self.stdio.print("\r\n");
+            self.stdio.print(
+                &self
+                    .theme()
+                    .error
+                    .paint(format!("bsh: {}", error))
+                    .to_string(),
+            );
+            self.stdio.print("\u{001b}[1A");
+        }
+
         // Move cursor to left edge again
         self.stdio.print("\u{001b}[1000D");
         // Move cursor to current position
-        self.stdio
-            .print(&format!("\u{001b}[{}C", self.buffer.get_cursor() + 2));
+        let column = format::width(&self.prompt_text()) + self.buffer.get_cursor();
+        self.stdio.print(&format!("\u{001b}[{}C", column));
+    }
+
+    fn theme(&self) -> Theme {
+        self.stdio.colors().theme()
+    }
+
+    /// Prompt with mode indicator of the keymap, if any.
+    fn prompt_text(&self) -> String {
+        let indicator = self.keymap.borrow().indicator().unwrap_or_default();
+        format!("{}❯ ", indicator)
     }
 
-    fn render_command(&self, command: Command) {
+    fn prompt(&self) {
+        let prompt = self.prompt_text();
         self.stdio
-            .print(&renderer::command(&command, &self.executables));
-        if command.parameters.is_none() {
-            self.white_space(self.buffer.len() - command.program.span.end.index);
+            .print(&self.theme().prompt.paint(prompt).to_string());
+    }
+
+    /// Highlight with programs, variables and theme of this session.
+    fn render(&self, render: impl FnOnce(&Context<Program>) -> String) -> String {
+        let executables = self.scope.executables.borrow();
+        let globals = self.scope.globals.borrow();
+        let cx = Context {
+            executables: &executables,
+            globals: &globals,
+            theme: self.theme(),
+        };
+        render(&cx)
+    }
+
+    fn render_statement(&self, statement: &Statement) {
+        let output = self.render(|cx| renderer::statement(statement, cx));
+        self.stdio.print(&output);
+    }
+
+    /// Start running a command line from JavaScript, out of job control.
+    fn exec(
+        &mut self,
+        line: &str,
+        echo: bool,
+    ) -> Result<(Rc<Stdio>, LocalBoxFuture<'static, Exit>), String> {
+        let statement = parser::parse_interactive(line)
+            .map_err(|err| format!("bsh: {}", parser::describe_error(&err)))?;
+
+        let stdio = if echo {
+            self.stdio.reset();
+            self.prompt();
+            self.render_statement(&statement);
+            self.stdio.println("");
+            Rc::new(self.stdio.tee())
+        } else {
+            Rc::new(self.stdio.capture())
+        };
+        let task = self.runner.spawn(
+            self.scope.clone(),
+            statement.pipeline,
+            Rc::from(line),
+            Rc::clone(&stdio),
+        );
+        Ok((stdio, task))
+    }
+
+    /// Called when a command line from JavaScript exits.
+    fn finish_exec(&mut self, exit: Exit, echo: bool) {
+        self.scope.globals.borrow_mut().extend(exit.env);
+        self.update_colors();
+        // When echoed, it looks like being typed, so put the prompt back.
+        if echo {
+            self.set_status(exit.status);
+            if !self.runner.is_running() {
+                self.output();
+            }
         }
     }
 
@@ -218,81 +611,264 @@ impl Shell {
         if !self.buffer.is_empty() {
             self.history.commit(self.buffer.get().to_string());
 
-            match parser::parse_interactive(&self.buffer.get()) {
-                Ok((command, _)) => {
-                    self.run_command(command);
+            match parser::parse_interactive(self.buffer.get()) {
+                Ok(statement) => {
+                    self.run_statement(statement);
                 }
                 Err(err) => {
-                    let unexpected = err
-                        .errors
-                        .iter()
-                        .take(1)
-                        .map(|e| {
-                            let mut msg = format!("{}", e);
-                            msg.make_ascii_lowercase();
-                            msg
-                        })
-                        .fold(String::new(), |output, msg| output + &msg);
-                    self.stdio.println(&format!(
-                        "bsh: syntax error at {}, {}",
-                        err.position, unexpected
-                    ));
+                    self.stdio
+                        .println(&format!("bsh: {}", parser::describe_error(&err)));
                 }
             }
         }
 
         self.buffer.clear();
+        if !self.runner.is_running() {
+            self.runner.report(&self.stdio);
+        }
     }
 
-    fn white_space(&self, size: usize) {
-        self.stdio.print(&" ".repeat(size));
+    /// Save exit status of last command, which can be read as `$?`.
+    fn set_status(&mut self, status: i32) {
+        self.scope
+            .globals
+            .borrow_mut()
+            .insert("?".to_string(), status.to_string());
+        self.update_colors();
     }
 
-    fn run_command(&mut self, command: Command) {
-        let name = &command.program.id.name;
-        let program = self.executables.get(name);
-        match program {
-            Some(program) => match program {
-                Program::Builtin(program) => {
-                    self.runner.run_builtin(
-                        program(),
-                        command.parameters,
-                        &Rc::clone(&self.terminal),
-                        &mut self.executables,
-                        &mut self.globals,
-                    );
-                }
-                Program::Internal(program) => {
-                    self.runner.run_internal(
-                        program(),
-                        command.parameters,
-                        &self.globals,
-                        Rc::clone(&self.stdio),
-                    );
-                }
-                Program::External(program) => {
-                    self.runner.run_external(
-                        &program,
-                        command.parameters,
-                        Rc::clone(&self.terminal),
-                        &self.globals,
-                        Rc::clone(&self.stdio),
-                    );
+    /// Disable colors if `NO_COLOR` is set to a non-empty value.
+    fn update_colors(&self) {
+        let globals = self.scope.globals.borrow();
+        let no_color = globals
+            .get("NO_COLOR")
+            .is_some_and(|value| !value.is_empty());
+        self.stdio.colors().set_no_color(no_color);
+    }
+
+    fn run_statement(&mut self, statement: Statement) {
+        let Statement {
+            mut pipeline,
+            background,
+            ..
+        } = statement;
+        let source = self.buffer.get();
+        let background = background.map(|_| pipeline::source_text(source, &pipeline.span));
+        let is_background = background.is_some();
+
+        // A lone builtin is run immediately, without going through a job.
+        let builtin = match pipeline.commands.as_slice() {
+            [command] if !is_background && !pipeline::has_substitution(command) => {
+                let executables = self.scope.executables.borrow();
+                match executables.get(&command.program.id.name) {
+                    Some(Program::Builtin(program)) => Some(program()),
+                    _ => None,
                 }
-            },
+            }
+            _ => None,
+        };
+        let program = match builtin {
+            Some(program) => program,
             None => {
-                self.stdio.println(&format!(
-                    "bsh: command not found: {}",
-                    Color::Red.paint(name)
-                ));
+                self.runner.run(
+                    self.scope.clone(),
+                    pipeline,
+                    Rc::from(source),
+                    Rc::clone(&self.stdio),
+                    background,
+                );
+                if is_background {
+                    self.set_status(0);
+                }
+                return;
+            }
+        };
+
+        let command = pipeline.commands.remove(0);
+        let result = self.runner.run_builtin(
+            program,
+            command.parameters,
+            &self.stdio,
+            &mut self.scope.executables.borrow_mut(),
+            &mut self.scope.globals.borrow_mut(),
+        );
+        match result {
+            Ok(status) => self.set_status(status),
+            Err(err) => {
+                self.stdio.println(&format!("bsh: {}", err));
+                self.set_status(1);
             }
         }
     }
+}
 
-    #[wasm_bindgen(js_name = "addExternal")]
-    /// Register a new external JavaScript function.
-    pub fn add_external(&mut self, name: String, func: Function) {
-        let external = Program::External(executable::External::new(func));
-        self.executables.insert(name, external);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn exec() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+
+        let (stdio, task) = session.exec("export a=kumiko", false).unwrap();
+        let exit = block_on(task);
+        session.finish_exec(exit, false);
+        assert_eq!("", &stdio.captured());
+
+        let (stdio, task) = session.exec("echo $a | echo $a", true).unwrap();
+        let exit = block_on(task);
+        assert_eq!(exit.status, 0);
+        session.finish_exec(exit, true);
+        assert_eq!("kumiko\n", &stdio.captured());
+        assert_eq!(
+            Some(&"0".to_string()),
+            session.scope.globals.borrow().get("?")
+        );
+
+        let error = session.exec("echo $((1+))", false).err().unwrap();
+        assert!(error.starts_with("bsh: syntax error"));
+
+        let error = session
+            .exec("export b=1 & export c=2", false)
+            .err()
+            .unwrap();
+        assert!(error.starts_with("bsh: syntax error at line 1, column 14"));
+        assert_eq!(session.scope.globals.borrow().get("b"), None);
+    }
+
+    #[test]
+    fn switches_as_text() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+        let mut output = |line| {
+            let (stdio, task) = session.exec(line, false).unwrap();
+            block_on(task);
+            stdio.captured()
+        };
+        assert_eq!(output("printf '%d %s\\n' -42 --a=b"), "-42 --a=b\n");
+        assert_eq!(output("printf '%5.1f' -0.04"), " -0.0");
+        assert_eq!(output("urlencode --a=b"), "--a%3Db\n");
+    }
+
+    #[test]
+    fn commit_whole_line() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+        session.stdio = Rc::new(session.stdio.tee());
+
+        session.buffer.set("export a=1 && export b=2".to_string());
+        session.commit();
+        assert!(session
+            .stdio
+            .captured()
+            .contains("bsh: syntax error at line 1, column 13"));
+        let globals = session.scope.globals.borrow();
+        assert_eq!(globals.get("a"), None);
+        assert_eq!(globals.get("b"), None);
+    }
+
+    #[test]
+    fn conditional() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+        session.fs.borrow_mut().write("a.txt", vec![]).unwrap();
+
+        let (_, task) = session
+            .exec("[ -f a.txt -a $((1+1)) -eq 2 ]", false)
+            .unwrap();
+        assert_eq!(block_on(task).status, 0);
+        let (_, task) = session.exec("test -d a.txt", false).unwrap();
+        assert_eq!(block_on(task).status, 1);
+    }
+
+    #[test]
+    fn no_color() {
+        let terminal = Terminal::new();
+        let mut session = Session::new(terminal, Runner::new(|_| {}));
+        assert!(session.stdio.is_colored());
+
+        session.buffer.set("export NO_COLOR=1".to_string());
+        session.commit();
+        assert!(!session.stdio.is_colored());
+
+        session.buffer.set("export NO_COLOR=".to_string());
+        session.commit();
+        assert!(session.stdio.is_colored());
+    }
+
+    #[test]
+    fn render_syntax_error() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+        assert_eq!(session.render_line(), None);
+
+        session.buffer.set("  ".to_string());
+        assert_eq!(session.render_line(), None);
+
+        session.buffer.set("echo $((1+".to_string());
+        let error = session.render_line().unwrap();
+        assert!(error.starts_with("syntax error at line 1, column 11"));
+
+        session.buffer.set("echo :)".to_string());
+        let error = session.render_line().unwrap();
+        assert!(error.starts_with("syntax error at line 1, column 7"));
+    }
+
+    #[test]
+    fn bracketed_paste() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+        session.input("\u{001b}[200~\"\u{001b}[201~");
+        assert_eq!(session.buffer.get(), "\"");
+
+        session.buffer.clear();
+        session.input("\u{001b}[200~export a=1\r\nexport \"b=(x\"\r");
+        assert!(session.pasting.is_some());
+        session.input("echo $a\u{001b}[201~(");
+        assert!(session.pasting.is_none());
+        let globals = session.scope.globals.borrow();
+        assert_eq!(globals.get("a"), Some(&"1".to_string()));
+        assert_eq!(globals.get("b"), Some(&"(x".to_string()));
+        assert_eq!(session.buffer.get(), "echo $a()");
+    }
+
+    #[test]
+    fn interrupt_keys() {
+        assert!(interrupts("\u{3}"));
+        assert!(interrupts("ab\u{3}"));
+        assert!(interrupts("\u{001b}[200~a\u{001b}[201~\u{3}"));
+        assert!(!interrupts("abc"));
+        assert!(!interrupts("\u{001b}\u{3}"));
+        assert!(!interrupts("\u{001b}[200~a\u{3}b\u{001b}[201~"));
+    }
+
+    #[test]
+    fn decode_keys() {
+        let mut session = Session::new(Terminal::new(), Runner::new(|_| {}));
+        session.input("echo a b\u{001b}[1;5D\u{001b}[1;5D\u{001b}b(");
+        assert_eq!(session.buffer.get(), "()echo a b");
+
+        session.input("\u{001b}OF\u{001b}[3;5~\u{7f}\u{001b}[1;3A\u{1}");
+        assert_eq!(session.buffer.get(), "()echo a ");
+        assert_eq!(session.buffer.get_cursor(), 9);
+
+        session.input("\u{3}");
+        assert!(session.buffer.is_empty());
+    }
+
+    #[test]
+    fn vi_mode() {
+        let terminal = Terminal::new();
+        let mut session = Session::new(terminal, Runner::new(|_| {}));
+        session.input("set -o vi\r");
+        assert_eq!(session.prompt_text(), "(ins) ❯ ");
+
+        session.input("echo hello");
+        session.input("\u{001b}");
+        session.input("bcwbye");
+        assert_eq!(session.buffer.get(), "echo bye");
+        assert_eq!(session.prompt_text(), "(ins) ❯ ");
+
+        session.input("\u{001b}");
+        assert_eq!(session.prompt_text(), "(cmd) ❯ ");
+        session.input("dd");
+        session.input("iset -o emacs\r");
+        assert_eq!(session.prompt_text(), "❯ ");
     }
 }
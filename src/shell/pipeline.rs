@@ -0,0 +1,390 @@
+use super::arithmetic::Error;
+use super::executable::{Builtin, Context, Exit, External, Internal, Program, INTERRUPTED};
+use super::jobs::{Job, JobState};
+use super::transform::Transformer;
+use super::{Executables, Vars};
+use crate::parser::ast::*;
+use crate::stdio::Stdio;
+use ansi_term::Color;
+use futures::channel::oneshot::{channel, Receiver};
+use futures::future::{select, Either, FutureExt, LocalBoxFuture};
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::AbortController;
+
+/// State which programs can read or change while running.
+#[derive(Clone)]
+pub struct Scope {
+    pub executables: Rc<RefCell<Executables>>,
+    pub globals: Rc<RefCell<Vars>>,
+}
+
+enum Runnable {
+    Builtin(Box<dyn Builtin>),
+    Internal(Box<dyn Internal>),
+    External(External),
+}
+
+/// Get text of a node from the command line.
+pub fn source_text(source: &str, span: &Span) -> String {
+    source
+        .chars()
+        .skip(span.start.index)
+        .take(span.end.index - span.start.index)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn template_body(param: &mut Param) -> Option<&mut TemplateBody> {
+    let template = match param {
+        Param::Literal(literal) => Some(&mut literal.literal),
+        Param::LongSwitch(switch) | Param::ShortSwitch(switch) => switch.value.as_mut(),
+    };
+    match template {
+        Some(Template::Unquoted(body)) | Some(Template::Double(body)) => Some(body),
+        _ => None,
+    }
+}
+
+/// Whether a command contains command substitutions,
+/// which must be run before the command itself.
+pub fn has_substitution(command: &Command) -> bool {
+    command
+        .parameters
+        .iter()
+        .flat_map(|parameters| parameters.params.iter())
+        .filter_map(|parameter| match &parameter.param {
+            Param::Literal(literal) => Some(&literal.literal),
+            Param::LongSwitch(switch) | Param::ShortSwitch(switch) => switch.value.as_ref(),
+        })
+        .any(|template| match template {
+            Template::Unquoted(body) | Template::Double(body) => body
+                .parts
+                .iter()
+                .any(|part| matches!(part, TemplatePart::Substitution(_))),
+            Template::Single(_) => false,
+        })
+}
+
+/// Run a pipeline as part of `job`.
+/// Every program reads what the previous one printed as its standard input.
+pub fn run(
+    scope: Scope,
+    pipeline: Pipeline,
+    source: Rc<str>,
+    stdio: Rc<Stdio>,
+    job: Rc<Job>,
+) -> LocalBoxFuture<'static, Exit> {
+    async move {
+        let last = pipeline.commands.len() - 1;
+        let mut input = stdio.stdin_bytes();
+        let mut exit = Exit::default();
+
+        for (i, command) in pipeline.commands.into_iter().enumerate() {
+            if job.state() != JobState::Running {
+                return Exit::status(INTERRUPTED);
+            }
+
+            let output = if i == last {
+                stdio.with_input_bytes(input.take())
+            } else {
+                stdio.capture().with_input_bytes(input.take())
+            };
+            let output = Rc::new(output);
+            // Like subshells, programs in a pipeline can't set variables,
+            // so each of them gets a copy.
+            let scope = if last > 0 {
+                Scope {
+                    executables: Rc::clone(&scope.executables),
+                    globals: Rc::new(RefCell::new(scope.globals.borrow().clone())),
+                }
+            } else {
+                scope.clone()
+            };
+            exit = run_command(&scope, command, &source, Rc::clone(&output), &job).await;
+            if i < last {
+                input = Some(output.captured_bytes());
+            }
+        }
+
+        // Nor can external programs in a pipeline.
+        if last > 0 {
+            exit.env.clear();
+        }
+        exit
+    }
+    .boxed_local()
+}
+
+/// Run command substitutions and replace them with their output.
+async fn expand(
+    scope: &Scope,
+    parameters: &mut Parameters,
+    source: &Rc<str>,
+    stdio: &Stdio,
+    job: &Rc<Job>,
+) {
+    for parameter in parameters.params.iter_mut() {
+        let body = match template_body(&mut parameter.param) {
+            Some(body) => body,
+            None => continue,
+        };
+        for part in body.parts.iter_mut() {
+            let substitution = match part {
+                TemplatePart::Substitution(substitution) => substitution,
+                _ => continue,
+            };
+            let span = substitution.span.clone();
+            let pipeline = std::mem::replace(
+                &mut substitution.pipeline,
+                Pipeline {
+                    commands: vec![],
+                    span: span.clone(),
+                },
+            );
+
+            let output = Rc::new(stdio.capture());
+            run(
+                scope.clone(),
+                pipeline,
+                Rc::clone(source),
+                Rc::clone(&output),
+                Rc::clone(job),
+            )
+            .await;
+
+            // Like bash, trailing line breaks are removed.
+            let value = output.captured().trim_end_matches('\n').to_string();
+            *part = TemplatePart::Raw(TemplateLiteral { value, span });
+        }
+    }
+}
+
+fn fail(stdio: &Stdio, err: Error) -> Exit {
+    stdio.eprintln(&format!("bsh: {}", err));
+    Exit::status(1)
+}
+
+/// Wait for a program, unless the job is terminated before it exits.
+async fn wait(exit: Receiver<Exit>, job: &Job) -> Exit {
+    match select(exit, job.wait()).await {
+        // A dropped sender means the program went wrong.
+        Either::Left((exit, _)) => exit.unwrap_or_else(|_| Exit::status(1)),
+        Either::Right(_) => Exit::status(INTERRUPTED),
+    }
+}
+
+async fn run_command(
+    scope: &Scope,
+    mut command: Command,
+    source: &Rc<str>,
+    stdio: Rc<Stdio>,
+    job: &Rc<Job>,
+) -> Exit {
+    if let Some(parameters) = &mut command.parameters {
+        expand(scope, parameters, source, &stdio, job).await;
+    }
+
+    let name = &command.program.id.name;
+    let program = scope
+        .executables
+        .borrow()
+        .get(name)
+        .map(|program| match program {
+            Program::Builtin(program) => Runnable::Builtin(program()),
+            Program::Internal(program) => Runnable::Internal(program()),
+            Program::External(program) => Runnable::External(program.clone()),
+        });
+    let program = match program {
+        Some(program) => program,
+        None => {
+            stdio.eprintln(&format!(
+                "bsh: command not found: {}",
+                Color::Red.paint(name)
+            ));
+            return Exit::status(127);
+        }
+    };
+
+    let globals = scope.globals.borrow().clone();
+    let transformer = Transformer::new(&globals);
+    let receiver = match program {
+        Runnable::Builtin(program) => {
+            let arguments = match command.parameters.map(|p| transformer.transform(p)) {
+                Some(Err(err)) => return fail(&stdio, err),
+                Some(Ok(arguments)) => arguments,
+                None => vec![],
+            };
+            let status = program.run(
+                &stdio,
+                &mut scope.executables.borrow_mut(),
+                &mut scope.globals.borrow_mut(),
+                arguments,
+            );
+            return Exit::status(status);
+        }
+        Runnable::Internal(program) => {
+            let arguments = match command.parameters.map(|p| transformer.transform(p)) {
+                Some(Err(err)) => return fail(&stdio, err),
+                Some(Ok(arguments)) => arguments,
+                None => vec![],
+            };
+            let (sender, receiver) = channel();
+            program.run(stdio, arguments, sender);
+            receiver
+        }
+        Runnable::External(program) => {
+            let command_line = source_text(source, &command.span);
+            let invocation = match command.parameters.map(|p| transformer.invocation(p)) {
+                Some(Err(err)) => return fail(&stdio, err),
+                Some(Ok(invocation)) => invocation,
+                None => Default::default(),
+            };
+
+            let controller = AbortController::new().expect("failed to create AbortController");
+            let context = Context {
+                command_line: &command_line,
+                env: &globals,
+                signal: controller.signal(),
+            };
+            job.on_terminate(move || controller.abort());
+
+            let (sender, receiver) = channel();
+            program.run(stdio, invocation, context, sender);
+            receiver
+        }
+    };
+
+    wait(receiver, job).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_interactive;
+    use crate::programs;
+    use crate::shell::Arguments;
+    use crate::terminal::Terminal;
+    use futures::channel::oneshot::Sender;
+    use futures::executor::block_on;
+    use futures::task::{noop_waker_ref, Context as TaskContext};
+    use std::collections::HashMap;
+
+    fn scope() -> Scope {
+        let mut executables: Executables = HashMap::new();
+        executables.insert(
+            "echo".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Echo))),
+        );
+        executables.insert(
+            "export".to_string(),
+            Program::Builtin(Box::new(|| Box::new(programs::Export))),
+        );
+        Scope {
+            executables: Rc::new(RefCell::new(executables)),
+            globals: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    fn execute(scope: &Scope, line: &str) -> (Exit, String) {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Rc::new(Stdio::new(Rc::clone(&terminal)));
+        let job = Rc::new(Job::new(0, String::new(), Rc::clone(&stdio)));
+        let statement = parse_interactive(line).unwrap();
+        let exit = block_on(run(
+            scope.clone(),
+            statement.pipeline,
+            Rc::from(line),
+            stdio,
+            job,
+        ));
+        (exit, terminal.get())
+    }
+
+    #[test]
+    fn pipe() {
+        let scope = scope();
+        let (exit, output) = execute(&scope, "echo a | echo b");
+        assert_eq!(exit.status, 0);
+        assert_eq!(output, "b\r\n");
+
+        let (exit, output) = execute(&scope, "echo a | nope");
+        assert_eq!(exit.status, 127);
+        assert!(output.contains("command not found"));
+
+        let (exit, _) = execute(&scope, "export a=1 | export b=2");
+        assert_eq!(exit.status, 0);
+        assert!(scope.globals.borrow().is_empty());
+    }
+
+    #[test]
+    fn substitute() {
+        let scope = scope();
+        let (exit, output) = execute(&scope, "export a=$(echo b)x \"$(echo $(echo c))=d\"");
+        assert_eq!(exit.status, 0);
+        assert_eq!(output, "");
+
+        let globals = scope.globals.borrow();
+        assert_eq!(globals.get("a"), Some(&"bx".to_string()));
+        assert_eq!(globals.get("c"), Some(&"d".to_string()));
+    }
+
+    /// A program which keeps its sender, so it exits only when the test says.
+    struct Hang(Rc<RefCell<Option<Sender<Exit>>>>);
+
+    impl Internal for Hang {
+        fn run(&self, _: Rc<Stdio>, _: Arguments, exit: Sender<Exit>) {
+            self.0.replace(Some(exit));
+        }
+    }
+
+    #[test]
+    fn terminate_before_exit() {
+        let scope = scope();
+        let sender = Rc::new(RefCell::new(None));
+        let hang = Rc::clone(&sender);
+        scope.executables.borrow_mut().insert(
+            "hang".to_string(),
+            Program::Internal(Box::new(move || Box::new(Hang(Rc::clone(&hang))))),
+        );
+        let stdio = Rc::new(Stdio::new(Rc::new(Terminal::new())));
+        let job = Rc::new(Job::new(0, String::new(), Rc::clone(&stdio)));
+        let statement = parse_interactive("hang").unwrap();
+        let mut task = run(
+            scope,
+            statement.pipeline,
+            Rc::from("hang"),
+            stdio,
+            Rc::clone(&job),
+        );
+
+        let mut cx = TaskContext::from_waker(noop_waker_ref());
+        assert!(task.poll_unpin(&mut cx).is_pending());
+        job.terminate();
+        assert_eq!(block_on(task).status, INTERRUPTED);
+
+        // The program exits later, when nobody waits for it.
+        let sender = sender.take().unwrap();
+        assert!(sender.send(Exit::default()).is_err());
+    }
+
+    #[test]
+    fn detect_substitution() {
+        let statement = parse_interactive("echo '$(a)' \"$(b)\"").unwrap();
+        assert!(has_substitution(&statement.pipeline.commands[0]));
+
+        let statement = parse_interactive("echo '$(a)' --k=$x").unwrap();
+        assert!(!has_substitution(&statement.pipeline.commands[0]));
+    }
+
+    #[test]
+    fn text_of_span() {
+        let statement = parse_interactive("echo a |  export b=c &").unwrap();
+        let command = &statement.pipeline.commands[1];
+        assert_eq!(
+            source_text("echo a |  export b=c &", &command.span),
+            "export b=c"
+        );
+    }
+}
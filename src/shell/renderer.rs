@@ -1,12 +1,34 @@
+use super::theme::Theme;
+use super::Vars;
 use crate::parser::ast::*;
-use ansi_term::Color;
+use crate::parser::Recovery;
+use ansi_term::Style;
 use std::collections::HashMap;
 
+/// What's needed to highlight a command line.
+pub(super) struct Context<'a, T> {
+    pub executables: &'a HashMap<String, T>,
+    pub globals: &'a Vars,
+    pub theme: Theme,
+}
+
+/// What renderer needs to know about a program.
+pub(super) trait Signature {
+    /// Switches which the program accepts, if it declares them.
+    fn switches(&self) -> Option<Vec<String>>;
+}
+
+impl Signature for () {
+    fn switches(&self) -> Option<Vec<String>> {
+        None
+    }
+}
+
 fn white_space(size: usize) -> String {
     " ".repeat(size)
 }
 
-fn template(template: &Template) -> String {
+fn template<T: Signature>(template: &Template, cx: &Context<T>) -> String {
     match template {
         Template::Unquoted(body) => {
             body.parts.iter().enumerate().fold(
@@ -17,17 +39,19 @@ fn template(template: &Template) -> String {
                             TemplatePart::Raw(literal) => {
                                 // User may input a switch later, so paint it with light blue.
                                 if &literal.value == "-" && i == 0 {
-                                    Color::Fixed(39).paint("-").to_string()
+                                    cx.theme.switch.paint("-").to_string()
                                 } else {
                                     literal.value.to_owned()
                                 }
                             }
-                            TemplatePart::Variable(var) => variable(var),
+                            TemplatePart::Variable(var) => variable(var, cx),
+                            TemplatePart::Arithmetic(arith) => arithmetic(arith, cx),
+                            TemplatePart::Substitution(sub) => substitution(sub, cx),
                         }
                 },
             )
         }
-        Template::Single(raw) => Color::Yellow.paint(format!("'{}'", raw.text)).to_string(),
+        Template::Single(raw) => cx.theme.string.paint(format!("'{}'", raw.text)).to_string(),
         Template::Double(body) => {
             let middle = body.parts.iter().fold(
                 String::with_capacity(body.parts.len() * 5),
@@ -35,85 +59,271 @@ fn template(template: &Template) -> String {
                     output
                         + &match part {
                             TemplatePart::Raw(literal) => {
-                                Color::Yellow.paint(&literal.value).to_string()
+                                cx.theme.string.paint(&literal.value).to_string()
                             }
-                            TemplatePart::Variable(var) => variable(var),
+                            TemplatePart::Variable(var) => variable(var, cx),
+                            TemplatePart::Arithmetic(arith) => arithmetic(arith, cx),
+                            TemplatePart::Substitution(sub) => substitution(sub, cx),
                         }
                 },
             );
 
-            let quote = Color::Yellow.paint("\"");
+            let quote = cx.theme.string.paint("\"");
             format!("{}{}{}", quote, middle, quote)
         }
     }
 }
 
-fn variable(variable: &Variable) -> String {
-    Color::Fixed(93)
+fn variable_style<T: Signature>(name: &str, cx: &Context<T>) -> Style {
+    if cx.globals.contains_key(name) {
+        cx.theme.variable
+    } else {
+        cx.theme.undefined_variable
+    }
+}
+
+fn variable<T: Signature>(variable: &Variable, cx: &Context<T>) -> String {
+    variable_style(&variable.id.name, cx)
         .paint(format!("${}", variable.id.name))
         .to_string()
 }
 
-fn switch(switch: &Switch) -> String {
+fn arithmetic<T: Signature>(arithmetic: &Arithmetic, cx: &Context<T>) -> String {
+    let expr = arithmetic.expr.span();
+    format!(
+        "{}{}{}{}{}",
+        cx.theme.variable.paint("$(("),
+        white_space(expr.start.index - arithmetic.span.start.index - 3),
+        expression(&arithmetic.expr, cx),
+        white_space(arithmetic.span.end.index - expr.end.index - 2),
+        cx.theme.variable.paint("))"),
+    )
+}
+
+fn substitution<T: Signature>(substitution: &Substitution, cx: &Context<T>) -> String {
+    let inner = &substitution.pipeline.span;
+    format!(
+        "{}{}{}{}",
+        cx.theme.variable.paint("$("),
+        pipeline(
+            &substitution.pipeline,
+            substitution.span.start.index + 2,
+            cx
+        ),
+        white_space(substitution.span.end.index - inner.end.index - 1),
+        cx.theme.variable.paint(")"),
+    )
+}
+
+fn expression<T: Signature>(expr: &Expr, cx: &Context<T>) -> String {
+    match expr {
+        Expr::Number(number) => number.value.to_owned(),
+        Expr::Variable(var) => variable(var, cx),
+        Expr::Name(id) => variable_style(&id.name, cx).paint(&id.name).to_string(),
+        Expr::Unary(unary) => {
+            let op = unary.op.as_str();
+            let gap = unary.operand.span().start.index - unary.span.start.index - op.len();
+            format!(
+                "{}{}{}",
+                op,
+                white_space(gap),
+                expression(&unary.operand, cx)
+            )
+        }
+        Expr::Binary(binary) => format!(
+            "{}{}{}{}{}",
+            expression(&binary.left, cx),
+            white_space(binary.op_span.start.index - binary.left.span().end.index),
+            binary.op.as_str(),
+            white_space(binary.right.span().start.index - binary.op_span.end.index),
+            expression(&binary.right, cx),
+        ),
+        Expr::Paren(paren) => {
+            let inner = paren.expr.span();
+            format!(
+                "({}{}{})",
+                white_space(inner.start.index - paren.span.start.index - 1),
+                expression(&paren.expr, cx),
+                white_space(paren.span.end.index - inner.end.index - 1),
+            )
+        }
+    }
+}
+
+fn switch<T: Signature>(switch: &Switch, style: Style, cx: &Context<T>) -> String {
     let mut output = String::with_capacity(3);
 
-    output.push_str(&Color::Fixed(39).paint(&switch.name.name).to_string());
+    output.push_str(&style.paint(&switch.name.name).to_string());
     if let Some(value) = &switch.value {
-        output.push_str(&Color::Fixed(39).paint("=").to_string());
-        output.push_str(&template(&value));
+        output.push_str(&style.paint("=").to_string());
+        output.push_str(&template(value, cx));
     }
 
     output
 }
 
-fn parameter(parameter: &Parameter) -> String {
+/// Render a parameter. If the program declares its switches,
+/// those which aren't declared are marked as unknown.
+fn parameter<T: Signature>(
+    parameter: &Parameter,
+    switches: Option<&[String]>,
+    cx: &Context<T>,
+) -> String {
+    let style = |sw: &Switch| match switches {
+        Some(switches) if !switches.contains(&sw.name.name) => cx.theme.unknown_switch,
+        _ => cx.theme.switch,
+    };
     match &parameter.param {
-        Param::Literal(literal) => template(&literal.literal),
-        Param::LongSwitch(sw) => format!("{}{}", Color::Fixed(39).paint("--"), switch(&sw)),
-        Param::ShortSwitch(sw) => format!("{}{}", Color::Fixed(39).paint("-"), switch(&sw)),
+        Param::Literal(literal) => template(&literal.literal, cx),
+        Param::LongSwitch(sw) => {
+            let style = style(sw);
+            format!("{}{}", style.paint("--"), switch(sw, style, cx))
+        }
+        Param::ShortSwitch(sw) => {
+            let style = style(sw);
+            format!("{}{}", style.paint("-"), switch(sw, style, cx))
+        }
     }
 }
 
-fn parameters(parameters: &Parameters, prefix_idx: usize) -> String {
+fn parameters<T: Signature>(
+    parameters: &Parameters,
+    prefix_idx: usize,
+    switches: Option<&[String]>,
+    cx: &Context<T>,
+) -> String {
     parameters
         .params
         .iter()
         .fold((prefix_idx, String::new()), |(pos, output), param| {
             (
                 param.span.end.index,
-                output + &white_space(param.span.start.index - pos) + &parameter(param),
+                output
+                    + &white_space(param.span.start.index - pos)
+                    + &parameter(param, switches, cx),
             )
         })
         .1
 }
 
-fn program<T>(program: &Program, executables: &HashMap<String, T>) -> String {
-    if executables.keys().any(|exec| exec == &program.id.name) {
-        Color::Green.paint(&program.id.name).to_string()
+fn program<T: Signature>(program: &Program, cx: &Context<T>) -> String {
+    if cx.executables.contains_key(&program.id.name) {
+        cx.theme.program.paint(&program.id.name).to_string()
     } else {
-        Color::Red.paint(&program.id.name).to_string()
+        cx.theme.unknown_program.paint(&program.id.name).to_string()
     }
 }
 
-pub(super) fn command<T>(command: &Command, executables: &HashMap<String, T>) -> String {
-    let mut output = format!(
-        "{}{}",
-        white_space(command.span.start.index),
-        program(&command.program, executables)
-    );
+fn command<T: Signature>(command: &Command, prefix_idx: usize, cx: &Context<T>) -> String {
+    let mut output = white_space(command.span.start.index - prefix_idx);
+    output.push_str(&program(&command.program, cx));
 
     if let Some(params) = &command.parameters {
-        output.push_str(&parameters(&params, command.program.span.end.index));
+        let switches = cx
+            .executables
+            .get(&command.program.id.name)
+            .and_then(Signature::switches);
+        output.push_str(&parameters(
+            params,
+            command.program.span.end.index,
+            switches.as_deref(),
+            cx,
+        ));
+    }
+
+    output
+}
+
+/// Where the visible content of a command ends, excluding trailing white spaces.
+fn command_end(command: &Command) -> usize {
+    command
+        .parameters
+        .as_ref()
+        .and_then(|params| params.params.last())
+        .map(|param| param.span.end.index)
+        .unwrap_or(command.program.span.end.index)
+}
+
+fn pipeline<T: Signature>(pipeline: &Pipeline, prefix_idx: usize, cx: &Context<T>) -> String {
+    let last = pipeline.commands.len() - 1;
+    pipeline
+        .commands
+        .iter()
+        .enumerate()
+        .fold((prefix_idx, String::new()), |(pos, output), (i, item)| {
+            let mut output = output
+                + &command(item, pos, cx)
+                + &white_space(item.span.end.index - command_end(item));
+            if i < last {
+                output.push_str(&cx.theme.operator.paint("|").to_string());
+            }
+            (item.span.end.index + 1, output)
+        })
+        .1
+}
+
+pub(super) fn statement<T: Signature>(statement: &Statement, cx: &Context<T>) -> String {
+    let mut output = pipeline(&statement.pipeline, 0, cx);
+
+    if let Some(background) = &statement.background {
+        output.push_str(&white_space(
+            background.start.index - statement.pipeline.span.end.index,
+        ));
+        output.push('&');
     }
 
     output
 }
 
+/// Render a line which can't be parsed. The valid prefix is highlighted as usual,
+/// and the part causing the error is underlined.
+pub(super) fn recovery<T: Signature>(input: &str, recovery: &Recovery, cx: &Context<T>) -> String {
+    let chars = input.chars().collect::<Vec<_>>();
+    let text = |start: usize, end: usize| chars[start..end].iter().collect::<String>();
+
+    let mut output = recovery
+        .statement
+        .as_ref()
+        .map(|statement| self::statement(statement, cx))
+        .unwrap_or_default();
+    output.push_str(&text(recovery.valid, recovery.error.start));
+    output.push_str(
+        &cx.theme
+            .error
+            .underline()
+            .paint(text(recovery.error.start, recovery.error.end))
+            .to_string(),
+    );
+    output.push_str(&text(recovery.error.end, chars.len()));
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ansi_term::Color;
     use itertools::Itertools;
 
+    fn no_executables() -> HashMap<String, ()> {
+        HashMap::new()
+    }
+
+    /// Variables used in tests, so they're painted as defined.
+    fn globals() -> Vars {
+        ["var", "v", "a", "b"]
+            .iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect()
+    }
+
+    fn context<'a, T>(executables: &'a HashMap<String, T>, globals: &'a Vars) -> Context<'a, T> {
+        Context {
+            executables,
+            globals,
+            theme: Theme::default(),
+        }
+    }
+
     #[test]
     fn spaces() {
         assert_eq!("  ".to_string(), white_space(2));
@@ -128,7 +338,7 @@ mod tests {
             })],
             span: Span::default(),
         });
-        let output = template(&unquoted);
+        let output = template(&unquoted, &context(&no_executables(), &globals()));
         assert_eq!(output, Color::Fixed(39).paint("-").to_string());
 
         let unquoted = Template::Unquoted(TemplateBody {
@@ -138,7 +348,7 @@ mod tests {
             })],
             span: Span::default(),
         });
-        let output = template(&unquoted);
+        let output = template(&unquoted, &context(&no_executables(), &globals()));
         assert_eq!(output, "text".to_string());
 
         let unquoted = Template::Unquoted(TemplateBody {
@@ -157,7 +367,7 @@ mod tests {
             ],
             span: Span::default(),
         });
-        let output = template(&unquoted);
+        let output = template(&unquoted, &context(&no_executables(), &globals()));
         assert_eq!(output, format!("text{}", Color::Fixed(93).paint("$var")));
     }
 
@@ -168,7 +378,7 @@ mod tests {
             span: Span::default(),
         });
 
-        let output = template(&single);
+        let output = template(&single, &context(&no_executables(), &globals()));
         assert_eq!(output, Color::Yellow.paint("'raw'").to_string())
     }
 
@@ -190,7 +400,7 @@ mod tests {
             ],
             span: Span::default(),
         });
-        let output = template(&double);
+        let output = template(&double, &context(&no_executables(), &globals()));
 
         let content = format!(
             "{}{}",
@@ -211,10 +421,48 @@ mod tests {
             span: Span::default(),
         };
 
-        let output = variable(&var);
+        let output = variable(&var, &context(&no_executables(), &globals()));
         assert_eq!(output, Color::Fixed(93).paint("$v").to_string());
     }
 
+    #[test]
+    fn render_arithmetic() {
+        let input = "$(( a+ (1 ) *-$b))";
+        let mut statement = crate::parser::parse_interactive(&format!("x {}", input)).unwrap();
+        let params = statement.pipeline.commands.remove(0).parameters.unwrap();
+        let output = match &params.params[0].param {
+            Param::Literal(literal) => {
+                template(&literal.literal, &context(&no_executables(), &globals()))
+            }
+            _ => unreachable!(),
+        };
+
+        let purple = Color::Fixed(93);
+        assert_eq!(
+            output,
+            format!(
+                "{} {}+ (1 ) *-{}{}",
+                purple.paint("$(("),
+                purple.paint("a"),
+                purple.paint("$b"),
+                purple.paint("))")
+            )
+        );
+    }
+
+    #[test]
+    fn render_statement() {
+        let c = crate::parser::parse_interactive("curl  a  & ").unwrap();
+        let mut executables = HashMap::new();
+        executables.insert("curl".to_string(), ());
+        let output = statement(&c, &context(&executables, &globals()));
+        assert_eq!(output, format!("{}  a  &", Color::Green.paint("curl")));
+
+        let c = crate::parser::parse_interactive("curl &").unwrap();
+        let output = statement(&c, &context(&executables, &globals()));
+        assert_eq!(output, format!("{} &", Color::Green.paint("curl")));
+    }
+
     #[test]
     fn render_switch() {
         let sw = Switch {
@@ -226,7 +474,11 @@ mod tests {
             span: Span::default(),
         };
 
-        let output = switch(&sw);
+        let output = switch(
+            &sw,
+            Color::Fixed(39).normal(),
+            &context(&no_executables(), &globals()),
+        );
         assert_eq!(output, Color::Fixed(39).paint("key").to_string());
 
         let sw = Switch {
@@ -240,10 +492,14 @@ mod tests {
             })),
             span: Span::default(),
         };
-        let output = switch(&sw);
+        let output = switch(
+            &sw,
+            Color::Fixed(39).normal(),
+            &context(&no_executables(), &globals()),
+        );
         assert_eq!(
             output,
-            vec![
+            [
                 Color::Fixed(39).paint("key"),
                 Color::Fixed(39).paint("="),
                 Color::Yellow.paint("'value'")
@@ -270,7 +526,7 @@ mod tests {
             span: Span::default(),
         };
 
-        let output = parameter(&param);
+        let output = parameter(&param, None, &context(&no_executables(), &globals()));
         assert_eq!(output, "text".to_string());
     }
 
@@ -288,7 +544,7 @@ mod tests {
             span: Span::default(),
         };
 
-        let output = parameter(&param);
+        let output = parameter(&param, None, &context(&no_executables(), &globals()));
         assert_eq!(
             output,
             format!(
@@ -313,7 +569,7 @@ mod tests {
             span: Span::default(),
         };
 
-        let output = parameter(&param);
+        let output = parameter(&param, None, &context(&no_executables(), &globals()));
         assert_eq!(
             output,
             format!(
@@ -446,7 +702,7 @@ mod tests {
             span: Span::default(),
         };
 
-        let output = parameters(&params, 0);
+        let output = parameters(&params, 0, None, &context(&no_executables(), &globals()));
         assert_eq!(&output, "  ab  cd");
     }
 
@@ -465,7 +721,7 @@ mod tests {
                 },
                 span: Span::default(),
             },
-            &executables,
+            &context(&executables, &globals()),
         );
         assert_eq!(Color::Red.paint("nope").to_string(), output);
 
@@ -477,7 +733,7 @@ mod tests {
                 },
                 span: Span::default(),
             },
-            &executables,
+            &context(&executables, &globals()),
         );
         assert_eq!(Color::Green.paint("clear").to_string(), output);
     }
@@ -528,7 +784,7 @@ mod tests {
                 },
             },
         };
-        let output = command::<()>(&c, &HashMap::new());
+        let output = command::<()>(&c, 0, &context(&HashMap::new(), &globals()));
         assert_eq!(output, format!(" {}", Color::Red.paint("test")));
 
         let c = Command {
@@ -706,7 +962,223 @@ mod tests {
         };
         let mut executables = HashMap::new();
         executables.insert("test".to_string(), ());
-        let output = command(&c, &executables);
+        let output = command(&c, 0, &context(&executables, &globals()));
         assert_eq!(output, format!(" {}   ab cd", Color::Green.paint("test")));
     }
+
+    #[test]
+    fn render_pipeline() {
+        let c = crate::parser::parse_interactive("echo  a|nope |  wc -l ").unwrap();
+        let mut executables = HashMap::new();
+        executables.insert("echo".to_string(), ());
+        executables.insert("wc".to_string(), ());
+        let output = statement(&c, &context(&executables, &globals()));
+        assert_eq!(
+            output,
+            format!(
+                "{}  a{}{} {}  {} {}{} ",
+                Color::Green.paint("echo"),
+                Color::Fixed(39).paint("|"),
+                Color::Red.paint("nope"),
+                Color::Fixed(39).paint("|"),
+                Color::Green.paint("wc"),
+                Color::Fixed(39).paint("-"),
+                Color::Fixed(39).paint("l"),
+            )
+        );
+    }
+
+    #[test]
+    fn render_substitution() {
+        let c = crate::parser::parse_interactive("echo $( echo a|wc )").unwrap();
+        let mut executables = HashMap::new();
+        executables.insert("echo".to_string(), ());
+        let output = statement(&c, &context(&executables, &globals()));
+        assert_eq!(
+            output,
+            format!(
+                "{} {} {} a{}{} {}",
+                Color::Green.paint("echo"),
+                Color::Fixed(93).paint("$("),
+                Color::Green.paint("echo"),
+                Color::Fixed(39).paint("|"),
+                Color::Red.paint("wc"),
+                Color::Fixed(93).paint(")"),
+            )
+        );
+    }
+
+    fn render_light(line: &str) -> String {
+        let c = crate::parser::parse_interactive(line).unwrap();
+        let mut executables = HashMap::new();
+        executables.insert("echo".to_string(), ());
+        let cx = Context {
+            executables: &executables,
+            globals: &globals(),
+            theme: Theme::light(),
+        };
+        statement(&c, &cx)
+    }
+
+    #[test]
+    fn theme_program() {
+        let theme = Theme::light();
+        assert_eq!(
+            render_light("echo"),
+            theme.program.paint("echo").to_string()
+        );
+        assert_eq!(
+            render_light("nope"),
+            theme.unknown_program.paint("nope").to_string()
+        );
+    }
+
+    #[test]
+    fn theme_switch() {
+        let switch = Theme::light().switch;
+        assert_eq!(
+            render_light("echo -key=b -"),
+            format!(
+                "{} {}{}{}b {}",
+                Theme::light().program.paint("echo"),
+                switch.paint("-"),
+                switch.paint("key"),
+                switch.paint("="),
+                switch.paint("-")
+            )
+        );
+    }
+
+    #[test]
+    fn theme_variable() {
+        let theme = Theme::light();
+        assert_eq!(
+            render_light("echo $a$((b))$(echo)"),
+            format!(
+                "{} {}{}{}{}{}{}{}",
+                theme.program.paint("echo"),
+                theme.variable.paint("$a"),
+                theme.variable.paint("$(("),
+                theme.variable.paint("b"),
+                theme.variable.paint("))"),
+                theme.variable.paint("$("),
+                theme.program.paint("echo"),
+                theme.variable.paint(")")
+            )
+        );
+    }
+
+    #[test]
+    fn theme_string() {
+        let theme = Theme::light();
+        assert_eq!(
+            render_light("echo 'a' \"b\""),
+            format!(
+                "{} {} {}{}{}",
+                theme.program.paint("echo"),
+                theme.string.paint("'a'"),
+                theme.string.paint("\""),
+                theme.string.paint("b"),
+                theme.string.paint("\"")
+            )
+        );
+    }
+
+    #[test]
+    fn theme_operator() {
+        let theme = Theme::light();
+        assert_eq!(
+            render_light("echo|echo"),
+            format!(
+                "{}{}{}",
+                theme.program.paint("echo"),
+                theme.operator.paint("|"),
+                theme.program.paint("echo")
+            )
+        );
+    }
+
+    struct Declared(&'static [&'static str]);
+
+    impl Signature for Declared {
+        fn switches(&self) -> Option<Vec<String>> {
+            Some(self.0.iter().map(|name| name.to_string()).collect())
+        }
+    }
+
+    #[test]
+    fn render_undefined_variable() {
+        let theme = Theme::default();
+        let c = crate::parser::parse_interactive("x $v$nope$((v+nope))").unwrap();
+        let output = statement(&c, &context(&no_executables(), &globals()));
+        assert_eq!(
+            output,
+            format!(
+                "{} {}{}{}{}+{}{}",
+                theme.unknown_program.paint("x"),
+                theme.variable.paint("$v"),
+                theme.undefined_variable.paint("$nope"),
+                theme.variable.paint("$(("),
+                theme.variable.paint("v"),
+                theme.undefined_variable.paint("nope"),
+                theme.variable.paint("))"),
+            )
+        );
+    }
+
+    #[test]
+    fn render_unknown_switch() {
+        let theme = Theme::default();
+        let mut executables = HashMap::new();
+        executables.insert("curl".to_string(), Declared(&["o", "json"]));
+        let c = crate::parser::parse_interactive("curl --json -o=a -x --nope=1").unwrap();
+        let output = statement(&c, &context(&executables, &globals()));
+        assert_eq!(
+            output,
+            format!(
+                "{} {}{} {}{}{}a {}{} {}{}{}1",
+                theme.program.paint("curl"),
+                theme.switch.paint("--"),
+                theme.switch.paint("json"),
+                theme.switch.paint("-"),
+                theme.switch.paint("o"),
+                theme.switch.paint("="),
+                theme.unknown_switch.paint("-"),
+                theme.unknown_switch.paint("x"),
+                theme.unknown_switch.paint("--"),
+                theme.unknown_switch.paint("nope"),
+                theme.unknown_switch.paint("="),
+            )
+        );
+    }
+
+    #[test]
+    fn render_recovery() {
+        let theme = Theme::default();
+        let mut executables = HashMap::new();
+        executables.insert("echo".to_string(), ());
+        let globals = globals();
+        let cx = context(&executables, &globals);
+
+        let input = "echo $v $((1+)) x";
+        let err = crate::parser::parse_interactive(input).err().unwrap();
+        let recovery = crate::parser::recover(input, &err);
+        assert_eq!(
+            super::recovery(input, &recovery, &cx),
+            format!(
+                "{} {} {} x",
+                theme.program.paint("echo"),
+                theme.variable.paint("$v"),
+                theme.error.underline().paint("$((1+))"),
+            )
+        );
+
+        let input = " |";
+        let err = crate::parser::parse_interactive(input).err().unwrap();
+        let recovery = crate::parser::recover(input, &err);
+        assert_eq!(
+            super::recovery(input, &recovery, &cx),
+            format!(" {}", theme.error.underline().paint("|"))
+        );
+    }
 }
@@ -0,0 +1,153 @@
+use crate::style::{self, StyleOptions};
+use ansi_term::{Color, Style};
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// Styles of syntax highlighting and other parts of the shell.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    /// Programs which can be found.
+    pub program: Style,
+    /// Programs which can't be found.
+    pub unknown_program: Style,
+    pub switch: Style,
+    /// Switches which aren't declared by the program.
+    pub unknown_switch: Style,
+    /// Variables, arithmetic expansions and command substitutions.
+    pub variable: Style,
+    /// Variables which aren't set.
+    pub undefined_variable: Style,
+    pub string: Style,
+    /// Operators like `|`.
+    pub operator: Style,
+    pub prompt: Style,
+    /// Suggestion from history.
+    pub suggestion: Style,
+    /// Syntax errors while typing.
+    pub error: Style,
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            program: Color::Green.normal(),
+            unknown_program: Color::Red.normal(),
+            switch: Color::Fixed(39).normal(),
+            unknown_switch: Color::Red.underline(),
+            variable: Color::Fixed(93).normal(),
+            undefined_variable: Color::Fixed(208).normal(),
+            string: Color::Yellow.normal(),
+            operator: Color::Fixed(39).normal(),
+            prompt: Color::Purple.normal(),
+            suggestion: Color::Fixed(8).normal(),
+            error: Color::Red.normal(),
+        }
+    }
+
+    /// Darker colors which are readable on white background.
+    pub fn light() -> Theme {
+        Theme {
+            program: Color::Fixed(28).normal(),
+            unknown_program: Color::Fixed(160).normal(),
+            switch: Color::Fixed(25).normal(),
+            unknown_switch: Color::Fixed(160).underline(),
+            variable: Color::Fixed(91).normal(),
+            undefined_variable: Color::Fixed(166).normal(),
+            string: Color::Fixed(130).normal(),
+            operator: Color::Fixed(25).normal(),
+            prompt: Color::Fixed(90).normal(),
+            suggestion: Color::Fixed(245).normal(),
+            error: Color::Fixed(160).normal(),
+        }
+    }
+
+    /// Get a built-in theme by name.
+    pub fn named(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            _ => None,
+        }
+    }
+
+    /// Change style of a token class.
+    pub fn set(&mut self, key: &str, style: Style) -> Result<(), String> {
+        let field = match key {
+            "program" => &mut self.program,
+            "unknownProgram" => &mut self.unknown_program,
+            "switch" => &mut self.switch,
+            "unknownSwitch" => &mut self.unknown_switch,
+            "variable" => &mut self.variable,
+            "undefinedVariable" => &mut self.undefined_variable,
+            "string" => &mut self.string,
+            "operator" => &mut self.operator,
+            "prompt" => &mut self.prompt,
+            "suggestion" => &mut self.suggestion,
+            "error" => &mut self.error,
+            _ => return Err(format!("unknown theme key: {}", key)),
+        };
+        *field = style;
+        Ok(())
+    }
+
+    /// Read a theme from JavaScript. It can be name of a built-in theme,
+    /// or an object whose values are colors or style options like `Stdio.style`.
+    /// Key `base` of the object chooses a built-in theme to start with.
+    pub fn from_js(value: &JsValue) -> Result<Theme, String> {
+        if let Some(name) = value.as_string() {
+            return Theme::named(&name).ok_or_else(|| format!("unknown theme: {}", name));
+        }
+        if !value.is_object() {
+            return Err("theme must be a name or an object".to_string());
+        }
+
+        let base = Reflect::get(value, &JsValue::from("base")).unwrap_or_default();
+        let mut theme = match base.as_string() {
+            Some(name) => Theme::named(&name).ok_or_else(|| format!("unknown theme: {}", name))?,
+            None => Theme::default(),
+        };
+        for key in Object::keys(&Object::from(value.clone())).iter() {
+            let key = key.as_string().unwrap_or_default();
+            if key == "base" {
+                continue;
+            }
+            let spec = Reflect::get(value, &JsValue::from(&key)).unwrap_or_default();
+            let style = match spec.as_string() {
+                Some(name) => style::color(&name)
+                    .map(Style::from)
+                    .ok_or_else(|| format!("invalid color: {}", name))?,
+                None => StyleOptions::from_js(&spec).style(),
+            };
+            theme.set(&key, style)?;
+        }
+        Ok(theme)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes() {
+        assert_eq!(Theme::named("dark"), Some(Theme::default()));
+        assert_eq!(Theme::named("light"), Some(Theme::light()));
+        assert_eq!(Theme::named("solarized"), None);
+    }
+
+    #[test]
+    fn set_style() {
+        let mut theme = Theme::light();
+        theme.set("unknownProgram", Color::Red.underline()).unwrap();
+        assert_eq!(theme.unknown_program, Color::Red.underline());
+        assert_eq!(theme.program, Theme::light().program);
+
+        assert!(theme.set("comment", Style::new()).is_err());
+    }
+}
@@ -1,25 +1,53 @@
+use super::arithmetic::{self, Error};
 use super::Vars;
 use crate::parser::ast::*;
 
 pub enum Argument {
     Text(String),
-    Switch(String, Option<String>),
+    /// A switch with its name, value and dashes it's written with.
+    Switch(String, Option<String>, Prefix),
+}
+
+/// Dashes before a switch, so the switch can be written back as it was.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Prefix {
+    /// A single dash, like `-n` or `-42`.
+    Short,
+    /// Two dashes, like `--name`.
+    Long,
+}
+
+impl Prefix {
+    pub fn dashes(self) -> &'static str {
+        match self {
+            Prefix::Short => "-",
+            Prefix::Long => "--",
+        }
+    }
+}
+
+/// Arguments of an external program, in both raw and structured forms.
+#[derive(Default)]
+pub struct Invocation {
+    /// All arguments as text, with switches written back like `--key=value`.
+    pub texts: Vec<String>,
+    /// Arguments which aren't switches.
+    pub positional: Vec<String>,
+    /// Switches with values, where names keep their dashes like `-f` or `--key`,
+    /// so short and long switches don't collide.
+    pub switches: Vec<(String, Option<String>)>,
 }
 
 pub struct Transformer<'a> {
     variables: &'a Vars,
-    text_only: bool,
 }
 
 impl<'a> Transformer<'a> {
-    pub fn new(variables: &Vars, text_only: bool) -> Transformer {
-        Transformer {
-            variables,
-            text_only,
-        }
+    pub fn new(variables: &'a Vars) -> Transformer<'a> {
+        Transformer { variables }
     }
 
-    pub fn transform(&self, parameters: Parameters) -> Vec<Argument> {
+    pub fn transform(&self, parameters: Parameters) -> Result<Vec<Argument>, Error> {
         parameters
             .params
             .into_iter()
@@ -27,62 +55,58 @@ impl<'a> Transformer<'a> {
             .collect()
     }
 
-    pub fn to_texts(&self, arguments: Vec<Argument>) -> Vec<String> {
-        arguments
-            .into_iter()
-            .map(|argument| match argument {
-                Argument::Text(t) => t,
-                Argument::Switch(_, _) => unreachable!(),
-            })
-            .collect()
+    pub fn invocation(&self, parameters: Parameters) -> Result<Invocation, Error> {
+        let mut invocation = Invocation::default();
+        for parameter in parameters.params {
+            let (switch, prefix) = match parameter.param {
+                Param::Literal(literal) => {
+                    let text = self.template(literal.literal)?;
+                    invocation.texts.push(text.clone());
+                    invocation.positional.push(text);
+                    continue;
+                }
+                Param::LongSwitch(switch) => (switch, Prefix::Long),
+                Param::ShortSwitch(switch) => (switch, Prefix::Short),
+            };
+            let (key, value) = self.switch_to_pair(switch)?;
+            let name = format!("{}{}", prefix.dashes(), key);
+            invocation.texts.push(format!(
+                "{}{}{}",
+                name,
+                if value.is_some() { "=" } else { "" },
+                value.as_deref().unwrap_or_default()
+            ));
+            invocation.switches.push((name, value));
+        }
+        Ok(invocation)
     }
 
-    fn parameter(&self, parameter: Parameter) -> Argument {
+    fn parameter(&self, parameter: Parameter) -> Result<Argument, Error> {
         let Parameter { param, .. } = parameter;
 
         match param {
-            Param::Literal(literal) => Argument::Text(self.template(literal.literal)),
-            Param::LongSwitch(switch) => self.switch(switch, true),
-            Param::ShortSwitch(switch) => self.switch(switch, false),
+            Param::Literal(literal) => Ok(Argument::Text(self.template(literal.literal)?)),
+            Param::LongSwitch(switch) => self.switch(switch, Prefix::Long),
+            Param::ShortSwitch(switch) => self.switch(switch, Prefix::Short),
         }
     }
 
-    fn switch(&self, switch: Switch, long: bool) -> Argument {
-        if self.text_only {
-            Argument::Text(format!(
-                "{}{}",
-                if long { "--" } else { "-" },
-                self.switch_to_text(switch),
-            ))
-        } else {
-            let pair = self.switch_to_pair(switch);
-            Argument::Switch(pair.0, pair.1)
-        }
+    fn switch(&self, switch: Switch, prefix: Prefix) -> Result<Argument, Error> {
+        let pair = self.switch_to_pair(switch)?;
+        Ok(Argument::Switch(pair.0, pair.1, prefix))
     }
 
-    fn switch_to_pair(&self, switch: Switch) -> (String, Option<String>) {
+    fn switch_to_pair(&self, switch: Switch) -> Result<(String, Option<String>), Error> {
         let key = switch.name.name;
-        let value = switch.value.map(|tpl| self.template(tpl));
-
-        (key, value)
-    }
+        let value = switch.value.map(|tpl| self.template(tpl)).transpose()?;
 
-    fn switch_to_text(&self, switch: Switch) -> String {
-        format!(
-            "{}{}{}",
-            switch.name.name,
-            if switch.value.is_some() { "=" } else { "" },
-            switch
-                .value
-                .map(|tpl| self.template(tpl))
-                .unwrap_or_default()
-        )
+        Ok((key, value))
     }
 
-    fn template(&self, template: Template) -> String {
+    fn template(&self, template: Template) -> Result<String, Error> {
         match template {
             Template::Unquoted(body) => self.template_body(body),
-            Template::Single(raw) => self.raw_text(raw),
+            Template::Single(raw) => Ok(self.raw_text(raw)),
             Template::Double(body) => self.template_body(body),
         }
     }
@@ -91,13 +115,20 @@ impl<'a> Transformer<'a> {
         raw_text.text
     }
 
-    fn template_body(&self, body: TemplateBody) -> String {
-        body.parts.into_iter().fold(String::new(), |text, part| {
-            text + &match part {
-                TemplatePart::Raw(raw) => self.template_literal(raw),
-                TemplatePart::Variable(var) => self.variable(var),
-            }
-        })
+    fn template_body(&self, body: TemplateBody) -> Result<String, Error> {
+        body.parts
+            .into_iter()
+            .try_fold(String::new(), |text, part| {
+                Ok(text
+                    + &match part {
+                        TemplatePart::Raw(raw) => self.template_literal(raw),
+                        TemplatePart::Variable(var) => self.variable(var),
+                        TemplatePart::Arithmetic(arith) => self.arithmetic(arith)?,
+                        TemplatePart::Substitution(_) => {
+                            unreachable!("command substitution should be expanded before")
+                        }
+                    })
+            })
     }
 
     fn template_literal(&self, literal: TemplateLiteral) -> String {
@@ -108,6 +139,10 @@ impl<'a> Transformer<'a> {
         let name = &variable.id.name;
         self.variables.get(name).cloned().unwrap_or_default()
     }
+
+    fn arithmetic(&self, arithmetic: Arithmetic) -> Result<String, Error> {
+        arithmetic::evaluate(&arithmetic.expr, self.variables).map(|value| value.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -118,7 +153,7 @@ mod tests {
     #[test]
     fn transform_raw_text() {
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, false);
+        let transformer = Transformer::new(&variables);
         let node = RawText {
             text: "text".to_string(),
             span: Span::default(),
@@ -130,7 +165,7 @@ mod tests {
     #[test]
     fn transform_template_literal() {
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, false);
+        let transformer = Transformer::new(&variables);
         let node = TemplateLiteral {
             value: "text".to_string(),
             span: Span::default(),
@@ -143,7 +178,7 @@ mod tests {
     fn transform_variable() {
         let mut variables = HashMap::with_capacity(1);
         variables.insert("kumiko".to_string(), "reina".to_string());
-        let transformer = Transformer::new(&variables, false);
+        let transformer = Transformer::new(&variables);
 
         let id_node = Identifier {
             name: "kumiko".to_string(),
@@ -170,7 +205,7 @@ mod tests {
     fn transform_template_body() {
         let mut variables = HashMap::with_capacity(1);
         variables.insert("kumiko".to_string(), "reina".to_string());
-        let transformer = Transformer::new(&variables, false);
+        let transformer = Transformer::new(&variables);
 
         let id_node = Identifier {
             name: "kumiko".to_string(),
@@ -193,7 +228,10 @@ mod tests {
             span: Span::default(),
         };
 
-        assert_eq!(transformer.template_body(node), "reina&kumiko");
+        assert_eq!(
+            transformer.template_body(node),
+            Ok("reina&kumiko".to_string())
+        );
     }
 
     #[test]
@@ -216,8 +254,8 @@ mod tests {
         });
 
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, false);
-        assert_eq!(transformer.template(node), "kumiko");
+        let transformer = Transformer::new(&variables);
+        assert_eq!(transformer.template(node), Ok("kumiko".to_string()));
     }
 
     #[test]
@@ -228,8 +266,8 @@ mod tests {
         });
 
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, false);
-        assert_eq!(transformer.template(node), "t");
+        let transformer = Transformer::new(&variables);
+        assert_eq!(transformer.template(node), Ok("t".to_string()));
     }
 
     #[test]
@@ -253,8 +291,8 @@ mod tests {
 
         let mut variables = HashMap::new();
         variables.insert("var".to_string(), "-".to_string());
-        let transformer = Transformer::new(&variables, false);
-        assert_eq!(transformer.template(node), "kumiko-");
+        let transformer = Transformer::new(&variables);
+        assert_eq!(transformer.template(node), Ok("kumiko-".to_string()));
     }
 
     #[test]
@@ -268,11 +306,12 @@ mod tests {
             span: Span::default(),
         };
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, false);
+        let transformer = Transformer::new(&variables);
 
-        if let Argument::Switch(key, value) = transformer.switch(sw, true) {
+        if let Ok(Argument::Switch(key, value, prefix)) = transformer.switch(sw, Prefix::Long) {
             assert_eq!(key, "key".to_string());
             assert_eq!(value, None);
+            assert_eq!(prefix, Prefix::Long);
         } else {
             unreachable!();
         }
@@ -289,49 +328,12 @@ mod tests {
             span: Span::default(),
         };
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, false);
+        let transformer = Transformer::new(&variables);
 
-        if let Argument::Switch(key, value) = transformer.switch(sw, false) {
+        if let Ok(Argument::Switch(key, value, prefix)) = transformer.switch(sw, Prefix::Short) {
             assert_eq!(key, "key".to_string());
             assert_eq!(value, Some("value".to_string()));
-        } else {
-            unreachable!();
-        }
-    }
-
-    #[test]
-    fn transform_switch_to_text() {
-        let sw = Switch {
-            name: Identifier {
-                name: "key".to_string(),
-                span: Span::default(),
-            },
-            value: None,
-            span: Span::default(),
-        };
-        let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, true);
-        if let Argument::Text(text) = transformer.switch(sw, true) {
-            assert_eq!(text, "--key".to_string());
-        } else {
-            unreachable!();
-        }
-
-        let sw = Switch {
-            name: Identifier {
-                name: "key".to_string(),
-                span: Span::default(),
-            },
-            value: Some(Template::Single(RawText {
-                text: "value".to_string(),
-                span: Span::default(),
-            })),
-            span: Span::default(),
-        };
-        let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, true);
-        if let Argument::Text(text) = transformer.switch(sw, false) {
-            assert_eq!(text, "-key=value".to_string());
+            assert_eq!(prefix, Prefix::Short);
         } else {
             unreachable!();
         }
@@ -350,9 +352,9 @@ mod tests {
             span: Span::default(),
         };
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, true);
+        let transformer = Transformer::new(&variables);
 
-        if let Argument::Text(text) = transformer.parameter(param) {
+        if let Ok(Argument::Text(text)) = transformer.parameter(param) {
             assert_eq!(text, "t".to_string());
         } else {
             unreachable!();
@@ -370,10 +372,12 @@ mod tests {
             span: Span::default(),
         };
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, true);
+        let transformer = Transformer::new(&variables);
 
-        if let Argument::Text(text) = transformer.parameter(param) {
-            assert_eq!(text, "--t".to_string());
+        if let Ok(Argument::Switch(key, value, prefix)) = transformer.parameter(param) {
+            assert_eq!(key, "t".to_string());
+            assert_eq!(value, None);
+            assert_eq!(prefix, Prefix::Long);
         } else {
             unreachable!();
         }
@@ -390,46 +394,69 @@ mod tests {
             span: Span::default(),
         };
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, true);
+        let transformer = Transformer::new(&variables);
 
-        if let Argument::Text(text) = transformer.parameter(param) {
-            assert_eq!(text, "-t".to_string());
+        if let Ok(Argument::Switch(key, value, prefix)) = transformer.parameter(param) {
+            assert_eq!(key, "t".to_string());
+            assert_eq!(value, None);
+            assert_eq!(prefix, Prefix::Short);
         } else {
             unreachable!();
         }
     }
 
     #[test]
-    fn from_parameters_to_text() {
+    fn from_parameters_to_invocation() {
+        let literal = |text: &str| Parameter {
+            param: Param::Literal(ParamLiteral {
+                literal: Template::Single(RawText {
+                    text: text.to_string(),
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        };
+        let switch = |name: &str, value: Option<&str>| Switch {
+            name: Identifier {
+                name: name.to_string(),
+                span: Span::default(),
+            },
+            value: value.map(|value| {
+                Template::Single(RawText {
+                    text: value.to_string(),
+                    span: Span::default(),
+                })
+            }),
+            span: Span::default(),
+        };
         let params = Parameters {
             params: vec![
+                literal("1"),
                 Parameter {
-                    param: Param::Literal(ParamLiteral {
-                        literal: Template::Single(RawText {
-                            text: "1".to_string(),
-                            span: Span::default(),
-                        }),
-                        span: Span::default(),
-                    }),
+                    param: Param::LongSwitch(switch("key", Some("value"))),
                     span: Span::default(),
                 },
+                literal("2"),
                 Parameter {
-                    param: Param::Literal(ParamLiteral {
-                        literal: Template::Single(RawText {
-                            text: "2".to_string(),
-                            span: Span::default(),
-                        }),
-                        span: Span::default(),
-                    }),
+                    param: Param::ShortSwitch(switch("f", None)),
                     span: Span::default(),
                 },
             ],
             span: Span::default(),
         };
         let variables = HashMap::new();
-        let transformer = Transformer::new(&variables, true);
-
-        let text = transformer.to_texts(transformer.transform(params));
-        assert_eq!("12", &text.join(""));
+        let transformer = Transformer::new(&variables);
+
+        let invocation = transformer.invocation(params).unwrap_or_default();
+        assert_eq!(invocation.texts, vec!["1", "--key=value", "2", "-f"]);
+        assert_eq!(invocation.positional, vec!["1", "2"]);
+        assert_eq!(
+            invocation.switches,
+            vec![
+                ("--key".to_string(), Some("value".to_string())),
+                ("-f".to_string(), None)
+            ]
+        );
     }
 }
@@ -1,20 +1,242 @@
+use crate::format;
+use crate::json;
+use crate::style::{self, Colors, StyleOptions};
 use crate::terminal::Terminal;
-use ansi_term::Color;
+use crate::widgets::{Progress, Spinner};
+use js_sys::{Array, Object, Reflect, JSON};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+/// Convert a value to text for a table cell.
+fn cell(value: &JsValue) -> String {
+    if value.is_undefined() || value.is_null() {
+        String::new()
+    } else if let Some(text) = value.as_string() {
+        text
+    } else {
+        JSON::stringify(value).map(String::from).unwrap_or_default()
+    }
+}
+
+fn is_record(value: &JsValue) -> bool {
+    value.is_object() && !Array::is_array(value)
+}
+
+/// Convert rows to cells. Objects are looked up by headers,
+/// and if there're no headers, keys of objects will be used.
+fn table_cells(rows: &Array, headers: Option<Array>) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut headers = headers
+        .map(|headers| headers.iter().map(|header| cell(&header)).collect())
+        .unwrap_or_else(Vec::<String>::new);
+    if headers.is_empty() {
+        rows.iter().filter(is_record).for_each(|row| {
+            Object::keys(&Object::from(row))
+                .iter()
+                .filter_map(|key| key.as_string())
+                .for_each(|key| {
+                    if !headers.contains(&key) {
+                        headers.push(key);
+                    }
+                })
+        });
+    }
+
+    let body = rows
+        .iter()
+        .map(|row| {
+            if Array::is_array(&row) {
+                Array::from(&row).iter().map(|value| cell(&value)).collect()
+            } else if is_record(&row) {
+                headers
+                    .iter()
+                    .map(|key| cell(&Reflect::get(&row, &JsValue::from(key)).unwrap_or_default()))
+                    .collect()
+            } else {
+                vec![cell(&row)]
+            }
+        })
+        .collect();
+    (headers, body)
+}
+
+/// Where text goes. Output can be written to terminal, collected for pipes
+/// and command substitution, or both.
+/// Collected output is bytes, so binary data can be passed through pipes.
+#[derive(Clone)]
+struct Output {
+    terminal: Option<Rc<Terminal>>,
+    buffer: Option<Rc<RefCell<Vec<u8>>>>,
+}
+
+impl Output {
+    fn buffer() -> Rc<RefCell<Vec<u8>>> {
+        Rc::new(RefCell::new(vec![]))
+    }
+}
+
+type StopWidget = Box<dyn FnOnce()>;
+
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct Stdio {
-    terminal: Rc<Terminal>,
+    output: Output,
+    /// Where shell errors go. It stays on terminal even if output is captured.
+    error: Output,
+    input: Option<Rc<[u8]>>,
+    closed: Rc<Cell<bool>>,
+    colors: Rc<Colors>,
+    /// Callbacks to stop widgets like spinners, which are left running.
+    widgets: Rc<RefCell<Vec<StopWidget>>>,
 }
 
 impl Stdio {
     pub fn new(terminal: Rc<Terminal>) -> Stdio {
-        Stdio { terminal }
+        let output = Output {
+            terminal: Some(terminal),
+            buffer: None,
+        };
+        Stdio {
+            error: output.clone(),
+            output,
+            input: None,
+            closed: Rc::new(Cell::new(false)),
+            colors: Rc::new(Colors::new()),
+            widgets: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// Create a stdio which collects output instead of writing to terminal.
+    pub fn capture(&self) -> Stdio {
+        Stdio {
+            output: Output {
+                terminal: None,
+                buffer: Some(Output::buffer()),
+            },
+            error: self.error.clone(),
+            input: None,
+            closed: Rc::new(Cell::new(false)),
+            colors: Rc::clone(&self.colors),
+            widgets: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// Create a stdio which writes to terminal and collects output at the same time.
+    pub fn tee(&self) -> Stdio {
+        Stdio {
+            output: Output {
+                terminal: self.error.terminal.clone(),
+                buffer: Some(Output::buffer()),
+            },
+            error: self.error.clone(),
+            input: None,
+            closed: Rc::new(Cell::new(false)),
+            colors: Rc::clone(&self.colors),
+            widgets: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// Create another stdio on the same output, which can be closed
+    /// without affecting this one.
+    pub fn detach(&self) -> Stdio {
+        Stdio {
+            closed: Rc::new(Cell::new(false)),
+            widgets: Rc::new(RefCell::new(vec![])),
+            ..self.clone()
+        }
+    }
+
+    /// Create a stdio on the same output, with given text as standard input.
+    pub fn with_input(&self, input: Option<String>) -> Stdio {
+        self.with_input_bytes(input.map(String::into_bytes))
+    }
+
+    /// Create a stdio on the same output, with given bytes as standard input.
+    pub fn with_input_bytes(&self, input: Option<Vec<u8>>) -> Stdio {
+        Stdio {
+            input: input.map(Rc::from),
+            ..self.clone()
+        }
     }
 
-    pub fn prompt(&self) {
-        self.print(&Color::Purple.paint("❯ ").to_string());
+    /// Text collected so far. Line breaks are always LF.
+    pub fn captured(&self) -> String {
+        String::from_utf8_lossy(&self.captured_bytes()).into_owned()
+    }
+
+    /// Bytes collected so far, including binary data written by `write`.
+    pub fn captured_bytes(&self) -> Vec<u8> {
+        self.output
+            .buffer
+            .as_ref()
+            .map(|buffer| buffer.borrow().clone())
+            .unwrap_or_default()
+    }
+
+    /// Print an error message from the shell itself, which won't be captured.
+    pub fn eprintln(&self, data: &str) {
+        if self.closed.get() {
+            return;
+        }
+        let error = Stdio {
+            output: self.error.clone(),
+            ..self.clone()
+        };
+        error.println(data);
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.output.terminal.is_some()
+    }
+
+    /// Color settings, which are shared with all stdio derived from this one.
+    pub fn colors(&self) -> &Colors {
+        &self.colors
+    }
+
+    /// Check if colors will be kept in output.
+    pub fn is_colored(&self) -> bool {
+        self.colors.enabled(self.is_terminal())
+    }
+
+    /// Write control sequences to terminal. They won't be captured.
+    pub fn control(&self, sequence: &str) {
+        if let Some(terminal) = &self.output.terminal {
+            if !self.closed.get() {
+                terminal.write(sequence);
+            }
+        }
+    }
+
+    /// Clear the screen. It does nothing if output is captured.
+    pub fn clear(&self) {
+        if let Some(terminal) = &self.output.terminal {
+            terminal.clear();
+        }
+    }
+
+    /// Print a JSON value with indentation, and colors if they're enabled.
+    pub fn print_json(&self, value: &serde_json::Value) {
+        let theme = self.colors.theme();
+        let text = json::pretty(value, Some(&theme).filter(|_| self.is_colored()));
+        self.println(&text.replace('\n', "\r\n"));
+    }
+
+    /// Register a callback to stop a widget, once its program exits.
+    pub fn add_widget(&self, stop: impl FnOnce() + 'static) {
+        self.widgets.borrow_mut().push(Box::new(stop));
+    }
+
+    /// Stop widgets which the program left running, so they won't be redrawn
+    /// and cursor is shown again.
+    pub fn stop_widgets(&self) {
+        let widgets = self.widgets.take();
+        widgets.into_iter().for_each(|stop| stop());
+    }
+
+    /// Stop writing anything to the terminal from now on.
+    pub fn close(&self) {
+        self.closed.set(true);
     }
 }
 
@@ -22,7 +244,24 @@ impl Stdio {
 impl Stdio {
     /// Print text to the terminal.
     pub fn print(&self, data: &str) {
-        self.terminal.write(data);
+        if self.closed.get() {
+            return;
+        }
+        if let Some(terminal) = &self.output.terminal {
+            if self.colors.enabled(true) {
+                terminal.write(data);
+            } else {
+                terminal.write(&style::strip(data));
+            }
+        }
+        if let Some(buffer) = &self.output.buffer {
+            let data = data.replace("\r\n", "\n");
+            if self.colors.enabled(false) {
+                buffer.borrow_mut().extend(data.as_bytes());
+            } else {
+                buffer.borrow_mut().extend(style::strip(&data).as_bytes());
+            }
+        }
     }
 
     /// Print text to the terminal, with a line break (CRLF).
@@ -31,18 +270,95 @@ impl Stdio {
         self.print("\r\n");
     }
 
+    /// Standard input, which is the output of previous program in a pipeline.
+    #[wasm_bindgen(getter)]
+    pub fn stdin(&self) -> Option<String> {
+        self.input
+            .as_ref()
+            .map(|input| String::from_utf8_lossy(input).into_owned())
+    }
+
+    /// Standard input as bytes, which is binary-safe.
+    #[wasm_bindgen(getter, js_name = stdinBytes)]
+    pub fn stdin_bytes(&self) -> Option<Vec<u8>> {
+        self.input.as_ref().map(|input| input.to_vec())
+    }
+
+    /// Write bytes as is. They're kept intact when collected for pipes,
+    /// and written to terminal as text, where invalid UTF-8 is replaced
+    /// and line breaks are always CRLF.
+    pub fn write(&self, data: &[u8]) {
+        if self.closed.get() {
+            return;
+        }
+        if let Some(terminal) = &self.output.terminal {
+            let text = String::from_utf8_lossy(data).replace("\r\n", "\n");
+            terminal.write(&text.replace('\n', "\r\n"));
+        }
+        if let Some(buffer) = &self.output.buffer {
+            buffer.borrow_mut().extend(data);
+        }
+    }
+
+    /// Print rows as a table with aligned columns. A row can be an array of cells,
+    /// or an object whose keys become headers if `headers` isn't given.
+    pub fn table(&self, rows: Array, headers: Option<Array>) {
+        let (headers, body) = table_cells(&rows, headers);
+        let text = format::table(&headers, &body, self.is_colored());
+        if !text.is_empty() {
+            self.println(&text.replace('\n', "\r\n"));
+        }
+    }
+
+    /// Style text with options `{ fg, bg, bold, underline }`. Colors can be
+    /// names, indexes of 256 colors or hex codes like `#ff8000`.
+    /// Text is returned as is if colors are disabled.
+    pub fn style(&self, text: &str, options: JsValue) -> String {
+        if self.is_colored() {
+            StyleOptions::from_js(&options)
+                .style()
+                .paint(text)
+                .to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Print a value as formatted JSON.
+    pub fn json(&self, value: &JsValue) {
+        let text = JSON::stringify(value)
+            .ok()
+            .and_then(|json| json.as_string())
+            .unwrap_or_default();
+        if let Ok(value) = serde_json::from_str(&text) {
+            self.print_json(&value);
+        }
+    }
+
+    /// Show a spinner with text, until it succeeds or fails.
+    pub fn spinner(&self, text: String) -> Spinner {
+        Spinner::new(self.clone(), text)
+    }
+
+    /// Show a progress bar, which will be updated in place.
+    pub fn progress(&self, total: usize) -> Progress {
+        Progress::new(self.clone(), total)
+    }
+
     /// Reset current line and move cursor to the start.
     pub fn reset(&self) {
         // Move cursor to left edge
-        self.print("\u{001b}[1000D");
+        self.control("\u{001b}[1000D");
         // Clear line
-        self.print("\u{001b}[0K");
+        self.control("\u{001b}[0K");
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::style::ColorMode;
+    use ansi_term::Color;
 
     #[test]
     fn print() {
@@ -61,4 +377,93 @@ mod tests {
         stdio.println("text");
         assert_eq!("text\r\n", &terminal.get());
     }
+
+    #[test]
+    fn close_detached() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let detached = stdio.detach();
+
+        detached.close();
+        detached.print("muted");
+        stdio.print("text");
+        assert_eq!("text", &terminal.get());
+    }
+
+    #[test]
+    fn capture() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        stdio.reset();
+        stdio.println("a");
+        stdio.print("b\r\n");
+        stdio.clear();
+        assert_eq!("a\nb\n", &stdio.captured());
+        assert!(!stdio.is_terminal());
+
+        let piped = stdio.with_input(Some(stdio.captured()));
+        assert_eq!(Some("a\nb\n".to_string()), piped.stdin());
+        piped.print("c");
+        assert_eq!("a\nb\nc", &stdio.captured());
+
+        piped.eprintln("error");
+        assert_eq!("error\r\n", &terminal.get());
+    }
+
+    #[test]
+    fn binary() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        stdio.write(&[0x89, b'P', b'\r', b'\n', 0xff]);
+        stdio.print("a\r\n");
+        assert_eq!(stdio.captured_bytes(), b"\x89P\r\n\xffa\n");
+
+        let piped = stdio.with_input_bytes(Some(stdio.captured_bytes()));
+        assert_eq!(piped.stdin_bytes().unwrap(), b"\x89P\r\n\xffa\n");
+        assert_eq!(piped.stdin().unwrap(), "\u{fffd}P\r\n\u{fffd}a\n");
+    }
+
+    #[test]
+    fn write_to_terminal() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        stdio.write(b"a\nb\r\n\xff");
+        assert_eq!(terminal.get(), "a\r\nb\r\n\u{fffd}");
+    }
+
+    #[test]
+    fn tee() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).tee();
+        stdio.reset();
+        stdio.println("a");
+        assert_eq!("a\n", &stdio.captured());
+        assert_eq!("\u{001b}[1000D\u{001b}[0Ka\r\n", &terminal.get());
+    }
+
+    #[test]
+    fn strip_colors() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let captured = stdio.tee();
+        let red = Color::Red.paint("red").to_string();
+
+        captured.print(&red);
+        assert_eq!(red, terminal.get());
+        assert_eq!("red", &captured.captured());
+
+        terminal.clear();
+        stdio.colors().set_no_color(true);
+        captured.reset();
+        captured.print(&red);
+        assert_eq!("\u{001b}[1000D\u{001b}[0Kred", &terminal.get());
+
+        stdio.colors().set_mode(ColorMode::Always);
+        captured.print(&red);
+        assert_eq!(format!("redred{}", red), captured.captured());
+
+        stdio.colors().set_mode(ColorMode::Never);
+        assert!(!captured.is_colored());
+        assert_eq!(captured.style("ok", JsValue::UNDEFINED), "ok");
+    }
 }
@@ -0,0 +1,234 @@
+use crate::shell::theme::Theme;
+use ansi_term::{Color, Style};
+use js_sys::Reflect;
+use std::cell::Cell;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// When to use colors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    Always,
+    Never,
+    /// Use colors only when writing to terminal, and `NO_COLOR` isn't set.
+    Auto,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            "auto" => Ok(ColorMode::Auto),
+            _ => Err(format!("invalid color mode: {}", mode)),
+        }
+    }
+}
+
+/// Color settings shared by a shell and all its stdio.
+pub struct Colors {
+    mode: Cell<ColorMode>,
+    no_color: Cell<bool>,
+    theme: Cell<Theme>,
+}
+
+impl Colors {
+    pub fn new() -> Self {
+        Colors {
+            mode: Cell::new(ColorMode::Auto),
+            no_color: Cell::new(false),
+            theme: Cell::new(Theme::default()),
+        }
+    }
+
+    pub fn set_mode(&self, mode: ColorMode) {
+        self.mode.set(mode);
+    }
+
+    /// Set whether `NO_COLOR` is present. See https://no-color.org/
+    pub fn set_no_color(&self, no_color: bool) {
+        self.no_color.set(no_color);
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme.get()
+    }
+
+    pub fn set_theme(&self, theme: Theme) {
+        self.theme.set(theme);
+    }
+
+    /// Check if colors can be written to terminal or captured output.
+    pub fn enabled(&self, terminal: bool) -> bool {
+        match self.mode.get() {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => terminal && !self.no_color.get(),
+        }
+    }
+}
+
+/// Remove SGR sequences which set colors and styles, and leave other
+/// control sequences like cursor movements alone.
+pub fn strip(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("\u{001b}[") {
+        stripped.push_str(&rest[..start]);
+        let sequence = &rest[start + 2..];
+        let end = sequence
+            .find(|c: char| ('@'..='~').contains(&c))
+            .unwrap_or(sequence.len());
+        if sequence[end..].starts_with('m') {
+            rest = &sequence[end + 1..];
+        } else {
+            let end = (end + 1).min(sequence.len());
+            stripped.push_str(&rest[start..start + 2 + end]);
+            rest = &sequence[end..];
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Parse a color, which can be a name, an index of 256 colors, or a hex code.
+pub fn color(name: &str) -> Option<Color> {
+    let color = match name {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "purple" | "magenta" => Color::Purple,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        _ => match name.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 => {
+                let channel = |i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+                Color::RGB(channel(0)?, channel(2)?, channel(4)?)
+            }
+            Some(_) => return None,
+            None => Color::Fixed(name.parse().ok()?),
+        },
+    };
+    Some(color)
+}
+
+/// Options of `Stdio.style`.
+#[derive(Default)]
+pub struct StyleOptions {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: bool,
+    pub underline: bool,
+}
+
+impl StyleOptions {
+    pub fn from_js(options: &JsValue) -> Self {
+        if !options.is_object() {
+            return StyleOptions::default();
+        }
+        let get = |key: &str| Reflect::get(options, &JsValue::from(key)).unwrap_or_default();
+        let color = |key: &str| {
+            let value = get(key);
+            value
+                .as_string()
+                .or_else(|| value.as_f64().map(|n| n.to_string()))
+        };
+        StyleOptions {
+            fg: color("fg"),
+            bg: color("bg"),
+            bold: get("bold").is_truthy(),
+            underline: get("underline").is_truthy(),
+        }
+    }
+
+    pub fn style(&self) -> Style {
+        let mut style = Style::new();
+        if let Some(fg) = self.fg.as_deref().and_then(color) {
+            style = style.fg(fg);
+        }
+        if let Some(bg) = self.bg.as_deref().and_then(color) {
+            style = style.on(bg);
+        }
+        if self.bold {
+            style = style.bold();
+        }
+        if self.underline {
+            style = style.underline();
+        }
+        style
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mode() {
+        assert_eq!("always".parse(), Ok(ColorMode::Always));
+        assert_eq!("never".parse(), Ok(ColorMode::Never));
+        assert_eq!("auto".parse(), Ok(ColorMode::Auto));
+        assert!("sometimes".parse::<ColorMode>().is_err());
+    }
+
+    #[test]
+    fn color_mode() {
+        let colors = Colors::new();
+        assert!(colors.enabled(true));
+        assert!(!colors.enabled(false));
+
+        colors.set_no_color(true);
+        assert!(!colors.enabled(true));
+
+        colors.set_mode(ColorMode::Always);
+        assert!(colors.enabled(true));
+        assert!(colors.enabled(false));
+
+        colors.set_mode(ColorMode::Never);
+        colors.set_no_color(false);
+        assert!(!colors.enabled(true));
+    }
+
+    #[test]
+    fn strip_colors() {
+        assert_eq!(strip(&Color::Red.bold().paint("a").to_string()), "a");
+        assert_eq!(strip("\u{001b}[38;5;127mb\u{001b}[0mc"), "bc");
+        assert_eq!(
+            strip("\u{001b}[1000D\u{001b}[0K\u{001b}[32m✔\u{001b}[0m"),
+            "\u{001b}[1000D\u{001b}[0K✔"
+        );
+        assert_eq!(strip("\u{001b}[?25h"), "\u{001b}[?25h");
+        assert_eq!(strip("broken\u{001b}[12"), "broken\u{001b}[12");
+    }
+
+    #[test]
+    fn parse_color() {
+        assert_eq!(color("red"), Some(Color::Red));
+        assert_eq!(color("magenta"), Some(Color::Purple));
+        assert_eq!(color("127"), Some(Color::Fixed(127)));
+        assert_eq!(color("#ff8000"), Some(Color::RGB(255, 128, 0)));
+        assert_eq!(color("#ff80"), None);
+        assert_eq!(color("#gg0000"), None);
+        assert_eq!(color("256"), None);
+        assert_eq!(color("pink"), None);
+    }
+
+    #[test]
+    fn build_style() {
+        let options = StyleOptions {
+            fg: Some("green".to_string()),
+            bg: Some("8".to_string()),
+            bold: true,
+            underline: true,
+        };
+        assert_eq!(
+            options.style(),
+            Color::Green.on(Color::Fixed(8)).bold().underline()
+        );
+        assert_eq!(StyleOptions::default().style(), Style::new());
+    }
+}
@@ -0,0 +1,390 @@
+use crate::format;
+use crate::stdio::Stdio;
+use crate::symbols::Symbol;
+use ansi_term::Color;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const BAR_SIZE: usize = 30;
+
+/// What a timer calls back.
+type Callback = Box<dyn FnMut()>;
+
+/// A handle of a running timer, which stops the timer once it's dropped.
+type Timer = Box<dyn Any>;
+
+/// A timer of `setInterval`, which calls back repeatedly until it's dropped.
+struct Interval {
+    id: i32,
+    _callback: Closure<dyn FnMut()>,
+}
+
+impl Interval {
+    fn start(timeout: i32, callback: Callback) -> Option<Timer> {
+        let callback = Closure::wrap(callback);
+        let id = web_sys::window()?
+            .set_interval_with_callback_and_timeout_and_arguments_0(
+                callback.as_ref().unchecked_ref(),
+                timeout,
+            )
+            .ok()?;
+        Some(Box::new(Interval {
+            id,
+            _callback: callback,
+        }))
+    }
+}
+
+impl Drop for Interval {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            window.clear_interval_with_handle(self.id);
+        }
+    }
+}
+
+struct SpinnerState {
+    stdio: Stdio,
+    text: RefCell<String>,
+    frame: Cell<usize>,
+    timer: RefCell<Option<Timer>>,
+    animated: bool,
+    stopped: Cell<bool>,
+}
+
+impl SpinnerState {
+    /// Draw next frame in place. Frames are never captured.
+    fn draw(&self) {
+        let frame = self.frame.get();
+        self.frame.set((frame + 1) % FRAMES.len());
+        let frame = if self.stdio.is_colored() {
+            Color::Cyan.paint(FRAMES[frame]).to_string()
+        } else {
+            FRAMES[frame].to_string()
+        };
+        self.stdio.reset();
+        self.stdio
+            .control(&format!("{} {}", frame, self.text.borrow()));
+    }
+
+    /// Stop animation, clear the spinner and show cursor again.
+    /// It returns false if the spinner has been stopped.
+    fn halt(&self) -> bool {
+        if self.stopped.replace(true) {
+            return false;
+        }
+        self.timer.replace(None);
+        if self.animated {
+            self.stdio.reset();
+            // Show cursor
+            self.stdio.control("\u{001b}[?25h");
+        }
+        true
+    }
+}
+
+/// A spinner showing that something is in progress.
+/// It's animated only when writing to terminal,
+/// and it's stopped if the program exits without stopping it.
+#[wasm_bindgen]
+pub struct Spinner {
+    state: Rc<SpinnerState>,
+}
+
+impl Spinner {
+    pub fn new(stdio: Stdio, text: String) -> Spinner {
+        Spinner::with_scheduler(stdio, text, Interval::start)
+    }
+
+    /// Create a spinner whose frames are drawn by a timer from `schedule`.
+    fn with_scheduler(
+        stdio: Stdio,
+        text: String,
+        schedule: impl FnOnce(i32, Callback) -> Option<Timer>,
+    ) -> Spinner {
+        let animated = stdio.is_terminal();
+        let state = Rc::new(SpinnerState {
+            stdio,
+            text: RefCell::new(text),
+            frame: Cell::new(0),
+            timer: RefCell::new(None),
+            animated,
+            stopped: Cell::new(false),
+        });
+        if animated {
+            // Hide cursor
+            state.stdio.control("\u{001b}[?25l");
+            state.draw();
+            let weak = Rc::downgrade(&state);
+            let timer = schedule(
+                80,
+                Box::new(move || match weak.upgrade() {
+                    Some(state) if !state.stopped.get() => state.draw(),
+                    _ => {}
+                }),
+            );
+            state.timer.replace(timer);
+            let weak = Rc::downgrade(&state);
+            state.stdio.add_widget(move || {
+                if let Some(state) = weak.upgrade() {
+                    state.halt();
+                }
+            });
+        }
+
+        Spinner { state }
+    }
+
+    fn stop(&self, line: impl FnOnce(String) -> String, text: Option<String>) {
+        if !self.state.halt() {
+            return;
+        }
+        let text = text.unwrap_or_else(|| self.state.text.borrow().clone());
+        self.state.stdio.println(&line(text));
+    }
+}
+
+#[wasm_bindgen]
+impl Spinner {
+    /// Change the text next to the spinner. It's shown since next frame.
+    #[wasm_bindgen(setter)]
+    pub fn set_text(&self, text: String) {
+        self.state.text.replace(text);
+    }
+
+    /// Stop the spinner and mark it as succeeded.
+    pub fn succeed(&mut self, text: Option<String>) {
+        self.stop(Symbol::success, text);
+    }
+
+    /// Stop the spinner and mark it as failed.
+    pub fn fail(&mut self, text: Option<String>) {
+        self.stop(Symbol::error, text);
+    }
+}
+
+/// A progress bar, which is redrawn in place when writing to terminal,
+/// or printed once it's finished if output is captured.
+#[wasm_bindgen]
+pub struct Progress {
+    stdio: Stdio,
+    total: usize,
+    current: usize,
+    drawn: String,
+    finished: bool,
+}
+
+impl Progress {
+    pub fn new(stdio: Stdio, total: usize) -> Progress {
+        let mut progress = Progress {
+            stdio,
+            total,
+            current: 0,
+            drawn: String::new(),
+            finished: false,
+        };
+        progress.draw();
+        progress
+    }
+
+    fn draw(&mut self) {
+        let bar = format::progress(self.current, self.total, BAR_SIZE);
+        if self.finished || !self.stdio.is_terminal() || bar == self.drawn {
+            return;
+        }
+        self.stdio.reset();
+        self.stdio.control(&bar);
+        self.drawn = bar;
+    }
+}
+
+#[wasm_bindgen]
+impl Progress {
+    /// Advance by `step`, which is 1 by default.
+    pub fn tick(&mut self, step: Option<usize>) {
+        self.current = self.current.saturating_add(step.unwrap_or(1));
+        self.draw();
+    }
+
+    /// Set current progress.
+    pub fn update(&mut self, current: usize) {
+        self.current = current;
+        self.draw();
+    }
+
+    /// Complete the progress bar and move to next line.
+    pub fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.stdio.reset();
+        self.stdio
+            .println(&format::progress(self.current, self.total, BAR_SIZE));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::ColorMode;
+    use crate::terminal::Terminal;
+
+    /// A fake timer, whose callback is called manually.
+    /// Its marker is kept by the spinner until the timer is stopped.
+    #[derive(Default)]
+    struct FakeTimer {
+        callback: Rc<RefCell<Option<Callback>>>,
+        marker: Rc<()>,
+    }
+
+    impl FakeTimer {
+        fn schedule(&self) -> impl FnOnce(i32, Callback) -> Option<Timer> {
+            let scheduled = Rc::clone(&self.callback);
+            let marker = Rc::clone(&self.marker);
+            move |timeout, callback| {
+                assert_eq!(timeout, 80);
+                scheduled.replace(Some(callback));
+                Some(Box::new(marker) as Timer)
+            }
+        }
+
+        fn tick(&self) {
+            self.callback.borrow_mut().as_mut().unwrap()();
+        }
+
+        fn is_running(&self) -> bool {
+            Rc::strong_count(&self.marker) > 1
+        }
+    }
+
+    #[test]
+    fn spin_on_terminal() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let timer = FakeTimer::default();
+        let mut spinner = Spinner::with_scheduler(stdio, "loading".to_string(), timer.schedule());
+        assert!(timer.is_running());
+        timer.tick();
+        assert_eq!(
+            terminal.get(),
+            format!(
+                "\u{001b}[?25l\u{001b}[1000D\u{001b}[0K{} loading\u{001b}[1000D\u{001b}[0K{} loading",
+                Color::Cyan.paint("⠋"),
+                Color::Cyan.paint("⠙")
+            )
+        );
+
+        terminal.clear();
+        spinner.succeed(None);
+        assert_eq!(
+            terminal.get(),
+            format!(
+                "\u{001b}[1000D\u{001b}[0K\u{001b}[?25h{}\r\n",
+                Symbol::success("loading".to_string())
+            )
+        );
+        assert!(!timer.is_running());
+
+        terminal.clear();
+        spinner.fail(None);
+        assert_eq!(terminal.get(), "");
+    }
+
+    #[test]
+    fn spin_without_colors() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        stdio.colors().set_mode(ColorMode::Never);
+        let timer = FakeTimer::default();
+        let mut spinner = Spinner::with_scheduler(stdio, "loading".to_string(), timer.schedule());
+        assert_eq!(
+            terminal.get(),
+            "\u{001b}[?25l\u{001b}[1000D\u{001b}[0K⠋ loading"
+        );
+        spinner.succeed(None);
+    }
+
+    #[test]
+    fn stop_when_program_exits() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let timer = FakeTimer::default();
+        let mut spinner =
+            Spinner::with_scheduler(stdio.clone(), "loading".to_string(), timer.schedule());
+        terminal.clear();
+
+        stdio.stop_widgets();
+        assert_eq!(terminal.get(), "\u{001b}[1000D\u{001b}[0K\u{001b}[?25h");
+        assert!(!timer.is_running());
+
+        // The program may still try to tick or stop it.
+        terminal.clear();
+        timer.tick();
+        spinner.succeed(None);
+        drop(spinner);
+        timer.tick();
+        assert_eq!(terminal.get(), "");
+    }
+
+    #[test]
+    fn spin_when_captured() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let mut spinner = Spinner::new(stdio.clone(), "loading".to_string());
+        spinner.set_text("still loading".to_string());
+        spinner.fail(Some("failed".to_string()));
+        assert_eq!(stdio.captured(), "✖ failed\n");
+        assert_eq!(terminal.get(), "");
+    }
+
+    #[test]
+    fn redraw_progress() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal));
+        let mut progress = Progress::new(stdio, 100);
+        terminal.clear();
+
+        // Nothing changes, so nothing is drawn.
+        progress.update(0);
+        assert_eq!(terminal.get(), "");
+
+        progress.tick(Some(50));
+        assert_eq!(
+            terminal.get(),
+            format!(
+                "\u{001b}[1000D\u{001b}[0K{}",
+                format::progress(50, 100, BAR_SIZE)
+            )
+        );
+
+        terminal.clear();
+        progress.finish();
+        progress.tick(None);
+        assert_eq!(
+            terminal.get(),
+            format!(
+                "\u{001b}[1000D\u{001b}[0K{}\r\n",
+                format::progress(50, 100, BAR_SIZE)
+            )
+        );
+    }
+
+    #[test]
+    fn progress_when_captured() {
+        let terminal = Rc::new(Terminal::new());
+        let stdio = Stdio::new(Rc::clone(&terminal)).capture();
+        let mut progress = Progress::new(stdio.clone(), 2);
+        progress.tick(None);
+        progress.tick(None);
+        progress.finish();
+        assert_eq!(
+            stdio.captured(),
+            format!("{}\n", format::progress(2, 2, BAR_SIZE))
+        );
+        assert_eq!(terminal.get(), "");
+    }
+}